@@ -587,6 +587,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::similar_names)]
     fn from_position_conversions() {
         use std::convert::TryFrom;
 
@@ -606,6 +607,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::similar_names)]
     fn from_uposition_conversions() {
         use std::convert::TryFrom;
 
@@ -628,6 +630,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::similar_names)]
     fn from_fposition_conversions() {
         use std::convert::TryFrom;
 
@@ -31,7 +31,8 @@
  * POSSIBILITY OF SUCH DAMAGE.
  */
 
-//! Line drawing using the Bresenham algorithm.
+//! Line drawing using the Bresenham algorithm, along with supercover lines, which include every
+//! cell a line passes through, and circle drawing using the midpoint circle algorithm.
 
 use crate::base::Position;
 use std::cmp::Ordering;
@@ -132,10 +133,233 @@ impl Iterator for Bresenham {
     }
 }
 
+/// Returns an iterator over the points of the line from `from` to `to`, including both.
+pub fn line_iter(from: Position, to: Position) -> BresenhamLine {
+    BresenhamLine {
+        start: Some(from),
+        bresenham: Bresenham::init(from, to),
+    }
+}
+
+/// Returns the number of points of the line from `from` to `to`, including both, which is one
+/// more than the larger of the horizontal and vertical distances between them.
+pub fn line_length(from: Position, to: Position) -> usize {
+    let dx = (i64::from(to.x) - i64::from(from.x)).unsigned_abs();
+    let dy = (i64::from(to.y) - i64::from(from.y)).unsigned_abs();
+    dx.max(dy) as usize + 1
+}
+
+/// Returns the `n`th point of the line from `from` to `to`, the same as
+/// `line_iter(from, to).nth(n)`, but computed directly rather than by stepping through the
+/// points before it. Point 0 is `from`; `None` is returned if `n` is past the end of the line.
+pub fn line_nth(from: Position, to: Position, n: usize) -> Option<Position> {
+    if n >= line_length(from, to) {
+        return None;
+    }
+
+    let dx = i64::from(to.x) - i64::from(from.x);
+    let dy = i64::from(to.y) - i64::from(from.y);
+    /* Like `Bresenham::step`, the major axis is the x axis only if it's strictly longer; each
+    step moves one along it, and one along the minor axis whenever the error term, starting at
+    the major distance and going down by twice the minor distance per step, would go negative */
+    let (major, minor) = if dx.abs() > dy.abs() {
+        (dx.abs(), dy.abs())
+    } else {
+        (dy.abs(), dx.abs())
+    };
+    let k = n as i64;
+    let minor_steps = if major == 0 {
+        0
+    } else {
+        let deficit = 2 * k * minor - major;
+        -(-deficit).div_euclid(2 * major)
+    };
+    let (step_x, step_y) = if dx.abs() > dy.abs() {
+        (k, minor_steps)
+    } else {
+        (minor_steps, k)
+    };
+
+    Some(Position::new(
+        (i64::from(from.x) + dx.signum() * step_x) as i32,
+        (i64::from(from.y) + dy.signum() * step_y) as i32,
+    ))
+}
+
+/// An iterator over the points of a bresenham line, created by [`line_iter`](fn.line_iter.html).
+///
+/// Unlike iterating over a [`Bresenham`](struct.Bresenham.html) directly, the starting point is
+/// included. A line from a point to itself yields just that point.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct BresenhamLine {
+    start: Option<Position>,
+    bresenham: Bresenham,
+}
+
+impl Iterator for BresenhamLine {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.start.take().or_else(|| self.bresenham.step())
+    }
+}
+
+/// Returns the points of the supercover line from `from` to `to`, including both; see
+/// [`supercover_line_iter`](fn.supercover_line_iter.html).
+pub fn supercover_line(from: Position, to: Position) -> Vec<Position> {
+    supercover_line_iter(from, to).collect()
+}
+
+/// Returns an iterator over the points of the supercover line from `from` to `to`, including both.
+///
+/// Where a bresenham line takes diagonal steps, a supercover line contains every cell the
+/// segment between the centers of `from` and `to` passes through, so each point is an
+/// orthogonal neighbor of the one before it. Where the segment passes exactly through the corner
+/// between four cells, the cell horizontally next to the previous one is included before the one
+/// diagonally across from it. This way, a line can't slip between two diagonally adjacent cells.
+pub fn supercover_line_iter(from: Position, to: Position) -> SupercoverLine {
+    SupercoverLine {
+        current: from,
+        step_x: (to.x - from.x).signum(),
+        step_y: (to.y - from.y).signum(),
+        delta_x: i64::from((to.x - from.x).abs()),
+        delta_y: i64::from((to.y - from.y).abs()),
+        done_x: 0,
+        done_y: 0,
+        start: Some(from),
+    }
+}
+
+/// An iterator over the points of a supercover line, created by
+/// [`supercover_line_iter`](fn.supercover_line_iter.html).
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct SupercoverLine {
+    current: Position,
+    step_x: i32,
+    step_y: i32,
+    delta_x: i64,
+    delta_y: i64,
+    done_x: i64,
+    done_y: i64,
+    start: Option<Position>,
+}
+
+impl Iterator for SupercoverLine {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(start) = self.start.take() {
+            return Some(start);
+        }
+        if self.done_x == self.delta_x && self.done_y == self.delta_y {
+            return None;
+        }
+
+        /* Compares where the segment crosses the next vertical and horizontal cell edges */
+        let decision = (1 + 2 * self.done_x) * self.delta_y - (1 + 2 * self.done_y) * self.delta_x;
+        /* At an exact corner, the horizontal step is taken first */
+        if decision > 0 {
+            self.current.y += self.step_y;
+            self.done_y += 1;
+        } else {
+            self.current.x += self.step_x;
+            self.done_x += 1;
+        }
+
+        Some(self.current)
+    }
+}
+
+/// Returns the points of the outline of a circle around `center` with the given `radius`, using
+/// the midpoint circle algorithm. No point is included more than once, and the points are
+/// ordered by row, then by column.
+///
+/// A `radius` of `0` gives just the `center`, and a negative `radius` gives no points.
+pub fn circle(center: Position, radius: i32) -> Vec<Position> {
+    if radius < 0 {
+        return Vec::new();
+    }
+
+    let mut points = Vec::new();
+    let (mut x, mut y) = (radius, 0);
+    let mut error = 1 - radius;
+    while x >= y {
+        for &(dx, dy) in &[
+            (x, y),
+            (y, x),
+            (-y, x),
+            (-x, y),
+            (-x, -y),
+            (-y, -x),
+            (y, -x),
+            (x, -y),
+        ] {
+            points.push(Position::new(center.x + dx, center.y + dy));
+        }
+
+        y += 1;
+        if error < 0 {
+            error += 2 * y + 1;
+        } else {
+            x -= 1;
+            error += 2 * (y - x) + 1;
+        }
+    }
+
+    /* The octants overlap where they meet */
+    points.sort_by_key(|p| (p.y, p.x));
+    points.dedup();
+    points
+}
+
+/// Returns every point within `radius` of the `center`, that is, every point where
+/// `dx * dx + dy * dy <= radius * radius`, ordered by row, then by column.
+///
+/// A `radius` of `0` gives just the `center`, and a negative `radius` gives no points.
+pub fn filled_circle(center: Position, radius: i32) -> Vec<Position> {
+    if radius < 0 {
+        return Vec::new();
+    }
+
+    let radius_squared = radius * radius;
+    let mut points = Vec::new();
+    for dy in -radius..=radius {
+        /* The widest row offset that still falls within the radius */
+        let mut half_width = 0;
+        while (half_width + 1) * (half_width + 1) + dy * dy <= radius_squared {
+            half_width += 1;
+        }
+        for dx in -half_width..=half_width {
+            points.push(Position::new(center.x + dx, center.y + dy));
+        }
+    }
+    points
+}
+
 #[cfg(test)]
 mod tests {
     use crate::base::Position;
-    use crate::bresenham::Bresenham;
+    use crate::bresenham::{
+        circle, filled_circle, line_iter, line_length, line_nth, supercover_line,
+        supercover_line_iter, Bresenham,
+    };
+
+    fn stepped(from: Position, to: Position) -> Vec<Position> {
+        let mut bresenham = Bresenham::init(from, to);
+        let mut points = vec![from];
+        while let Some(point) = bresenham.step() {
+            points.push(point);
+        }
+        points
+    }
 
     #[test]
     fn calculate_straight_x_line() {
@@ -169,7 +393,171 @@ mod tests {
         let sut = Bresenham::init(Position::ORIGIN, Position::new(20, 10));
         for (i, Position { x, y }) in sut.enumerate() {
             assert_eq!(i as i32 + 1, x);
-            assert_eq!(((i + 1) / 2) as i32, y);
+            assert_eq!(i.div_ceil(2) as i32, y);
+        }
+    }
+
+    #[test]
+    fn line_nth_matches_line_iter() {
+        let from = Position::new(1, -2);
+        for x in -7..=9 {
+            for y in -10..=6 {
+                let to = Position::new(x, y);
+                let line: Vec<_> = line_iter(from, to).collect();
+                assert_eq!(line.len(), line_length(from, to));
+                for (n, &point) in line.iter().enumerate() {
+                    assert_eq!(Some(point), line_nth(from, to, n), "{} to {}", from, to);
+                }
+                assert_eq!(None, line_nth(from, to, line.len()));
+            }
+        }
+        assert_eq!(1, line_length(from, from));
+        assert_eq!(Some(from), line_nth(from, from, 0));
+    }
+
+    #[test]
+    fn line_iter_includes_both_ends() {
+        let diagonal: Vec<_> = line_iter(Position::new(2, 2), Position::new(-3, -3)).collect();
+        assert_eq!(6, diagonal.len());
+        assert_eq!(Position::new(2, 2), diagonal[0]);
+        assert_eq!(Position::new(-3, -3), diagonal[5]);
+        assert_eq!(
+            stepped(Position::new(2, 2), Position::new(-3, -3)),
+            diagonal
+        );
+
+        let shallow: Vec<_> = line_iter(Position::ORIGIN, Position::new(7, 2)).collect();
+        assert_eq!(
+            vec![
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(2, 1),
+                Position::new(3, 1),
+                Position::new(4, 1),
+                Position::new(5, 1),
+                Position::new(6, 2),
+                Position::new(7, 2),
+            ],
+            shallow
+        );
+        assert_eq!(stepped(Position::ORIGIN, Position::new(7, 2)), shallow);
+    }
+
+    #[test]
+    fn line_iter_handles_every_octant() {
+        let ends = [
+            (5, 2),
+            (2, 5),
+            (-2, 5),
+            (-5, 2),
+            (-5, -2),
+            (-2, -5),
+            (2, -5),
+            (5, -2),
+            (4, 0),
+            (-4, 0),
+            (0, 4),
+            (0, -4),
+        ];
+        for &(x, y) in &ends {
+            let to = Position::new(x, y);
+            let points: Vec<_> = line_iter(Position::ORIGIN, to).collect();
+            assert_eq!(Some(&Position::ORIGIN), points.first());
+            assert_eq!(Some(&to), points.last());
+            assert_eq!(x.abs().max(y.abs()) as usize + 1, points.len());
+            for pair in points.windows(2) {
+                assert!((pair[1].x - pair[0].x).abs() <= 1 && (pair[1].y - pair[0].y).abs() <= 1);
+            }
+        }
+
+        let same: Vec<_> = line_iter(Position::new(3, 4), Position::new(3, 4)).collect();
+        assert_eq!(vec![Position::new(3, 4)], same);
+    }
+
+    #[test]
+    fn supercover_line_includes_corner_cells() {
+        let diagonal = supercover_line(Position::ORIGIN, Position::new(2, 2));
+        assert_eq!(
+            vec![
+                Position::new(0, 0),
+                Position::new(1, 0),
+                Position::new(1, 1),
+                Position::new(2, 1),
+                Position::new(2, 2),
+            ],
+            diagonal
+        );
+
+        for &(from, to) in &[
+            (Position::new(-1, 3), Position::new(4, 1)),
+            (Position::new(2, 2), Position::new(-1, -7)),
+            (Position::new(0, 0), Position::new(-4, 4)),
+            (Position::new(5, -1), Position::new(1, 1)),
+        ] {
+            let line = supercover_line(from, to);
+            assert_eq!(Some(&from), line.first());
+            assert_eq!(Some(&to), line.last());
+            assert_eq!(
+                ((to.x - from.x).abs() + (to.y - from.y).abs() + 1) as usize,
+                line.len()
+            );
+            for pair in line.windows(2) {
+                let (dx, dy) = (pair[1].x - pair[0].x, pair[1].y - pair[0].y);
+                assert_eq!(1, dx.abs() + dy.abs());
+            }
+        }
+
+        for &to in &[
+            Position::new(6, 0),
+            Position::new(0, -6),
+            Position::new(-3, 0),
+        ] {
+            let bresenham: Vec<_> = line_iter(Position::ORIGIN, to).collect();
+            assert_eq!(bresenham, supercover_line(Position::ORIGIN, to));
+        }
+        assert_eq!(
+            1,
+            supercover_line_iter(Position::ORIGIN, Position::ORIGIN).count()
+        );
+    }
+
+    #[test]
+    fn circle_outlines() {
+        let center = Position::new(4, -2);
+        assert!(circle(center, -1).is_empty());
+        assert_eq!(vec![center], circle(center, 0));
+        assert_eq!(4, circle(center, 1).len());
+        assert_eq!(12, circle(center, 2).len());
+        assert_eq!(16, circle(center, 3).len());
+
+        for radius in 1..10 {
+            let outline = circle(center, radius);
+            for point in &outline {
+                let (dx, dy) = (point.x - center.x, point.y - center.y);
+                assert!(((dx * dx + dy * dy) as f32).sqrt() - radius as f32 <= 0.75);
+                assert!(outline.contains(&Position::new(center.x - dx, center.y + dy)));
+                assert!(outline.contains(&Position::new(center.x + dy, center.y + dx)));
+            }
+        }
+    }
+
+    #[test]
+    fn filled_circles() {
+        let center = Position::new(-3, 5);
+        assert!(filled_circle(center, -2).is_empty());
+        assert_eq!(vec![center], filled_circle(center, 0));
+
+        for radius in 1..8 {
+            let disk = filled_circle(center, radius);
+            let mut expected = Vec::new();
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    if dx * dx + dy * dy <= radius * radius {
+                        expected.push(Position::new(center.x + dx, center.y + dy));
+                    }
+                }
+            }
+            assert_eq!(expected, disk);
         }
     }
 }
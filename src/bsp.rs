@@ -0,0 +1,699 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * Copyright © 2008-2019, Jice and the libtcod contributors.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Binary space partitioning.
+//!
+//! This module lets you recursively split a rectangular area into smaller ones, forming a binary
+//! tree, which is commonly used for laying out rooms in dungeons.
+
+use crate::random::Rng;
+use crate::{Position, Rectangle};
+
+/// A node of a binary space partition tree, covering a rectangular area. A node is either a
+/// leaf, or split into two children covering each their part of the node's area.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Bsp {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    split_position: i32,
+    horizontal: bool,
+    level: usize,
+    children: Option<Box<[Self; 2]>>,
+}
+
+/// Parameters for [`Bsp::generate_dungeon`](struct.Bsp.html#method.generate_dungeon).
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct DungeonParams {
+    /// How many levels of splits to do, at most.
+    pub max_depth: usize,
+    /// The minimum width and height of a room.
+    pub min_room_size: i32,
+    /// How many cells to keep free between a room and the edges of its node.
+    pub room_padding: i32,
+}
+
+impl Default for DungeonParams {
+    fn default() -> Self {
+        Self {
+            max_depth: 5,
+            min_room_size: 4,
+            room_padding: 1,
+        }
+    }
+}
+
+impl Bsp {
+    /// Returns a new, unsplit, tree covering the given area.
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self::new_at_level(x, y, width, height, 0)
+    }
+
+    fn new_at_level(x: i32, y: i32, width: i32, height: i32, level: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            split_position: 0,
+            horizontal: false,
+            level,
+            children: None,
+        }
+    }
+
+    /// Returns the x coordinate of the node's upper-left corner.
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    /// Returns the y coordinate of the node's upper-left corner.
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+
+    /// Returns the width of the node.
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Returns the height of the node.
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Returns the area covered by the node.
+    pub fn rectangle(&self) -> Rectangle {
+        Rectangle::new_from_raw(self.x, self.y, self.width as u32, self.height as u32)
+    }
+
+    /// Returns the depth of the node in its tree; the root is at level `0`.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Returns whether the node was split horizontally, i.e. into a top and a bottom part. Only
+    /// meaningful when the node isn't a leaf.
+    pub fn is_horizontal(&self) -> bool {
+        self.horizontal
+    }
+
+    /// Returns the coordinate the node was split at; a y coordinate if it was split
+    /// horizontally, an x coordinate otherwise. Only meaningful when the node isn't a leaf.
+    pub fn split_position(&self) -> i32 {
+        self.split_position
+    }
+
+    /// Returns whether the node has no children.
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_none()
+    }
+
+    /// Returns the left (or top) child of the node, if it has been split.
+    pub fn left(&self) -> Option<&Self> {
+        self.children.as_ref().map(|children| &children[0])
+    }
+
+    /// Returns the left (or top) child of the node, if it has been split.
+    pub fn left_mut(&mut self) -> Option<&mut Self> {
+        self.children.as_mut().map(|children| &mut children[0])
+    }
+
+    /// Returns the right (or bottom) child of the node, if it has been split.
+    pub fn right(&self) -> Option<&Self> {
+        self.children.as_ref().map(|children| &children[1])
+    }
+
+    /// Returns the right (or bottom) child of the node, if it has been split.
+    pub fn right_mut(&mut self) -> Option<&mut Self> {
+        self.children.as_mut().map(|children| &mut children[1])
+    }
+
+    /// Returns whether the given position is inside the node's area.
+    pub fn contains(&self, position: Position) -> bool {
+        position.x >= self.x
+            && position.y >= self.y
+            && position.x < self.x + self.width
+            && position.y < self.y + self.height
+    }
+
+    /// Returns the leaf containing the given position, or `None` if the position is outside the
+    /// node's area.
+    pub fn find_node(&self, position: Position) -> Option<&Self> {
+        if !self.contains(position) {
+            return None;
+        }
+        match &self.children {
+            Some(children) => children.iter().find_map(|child| child.find_node(position)),
+            None => Some(self),
+        }
+    }
+
+    /// Removes the children of the node, making it a leaf.
+    pub fn remove_children(&mut self) {
+        self.children = None;
+    }
+
+    /// Splits the node in two at the given `position`; a y coordinate if `horizontal`, an x
+    /// coordinate otherwise. Any existing children are replaced.
+    pub fn split_once(&mut self, horizontal: bool, position: i32) {
+        self.horizontal = horizontal;
+        self.split_position = position;
+        let level = self.level + 1;
+        let (x, y, w, h) = (self.x, self.y, self.width, self.height);
+        let children = if horizontal {
+            [
+                Self::new_at_level(x, y, w, position - y, level),
+                Self::new_at_level(x, position, w, y + h - position, level),
+            ]
+        } else {
+            [
+                Self::new_at_level(x, y, position - x, h, level),
+                Self::new_at_level(position, y, x + w - position, h, level),
+            ]
+        };
+        self.children = Some(Box::new(children));
+    }
+
+    /// Recursively splits the node at random positions, at most `count` levels deep.
+    ///
+    /// # Parameters
+    /// * `rng` - The random number generator used to pick where to split.
+    /// * `count` - How many levels of splits to do, at most.
+    /// * `min_width` - The minimum width of the resulting nodes.
+    /// * `min_height` - The minimum height of the resulting nodes.
+    /// * `max_width_ratio` - Nodes wider than this multiple of their height are always split
+    ///   vertically.
+    /// * `max_height_ratio` - Nodes taller than this multiple of their width are always split
+    ///   horizontally.
+    pub fn split_recursive<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        count: usize,
+        min_width: i32,
+        min_height: i32,
+        max_width_ratio: f32,
+        max_height_ratio: f32,
+    ) {
+        if count == 0 || (self.width < 2 * min_width && self.height < 2 * min_height) {
+            return;
+        }
+
+        /* Promote square rooms */
+        let horizontal = if self.height < 2 * min_height
+            || self.width as f32 > self.height as f32 * max_width_ratio
+        {
+            false
+        } else if self.width < 2 * min_width
+            || self.height as f32 > self.width as f32 * max_height_ratio
+        {
+            true
+        } else {
+            rng.get_i32(0, 1) == 0
+        };
+        let position = if horizontal {
+            rng.get_i32(self.y + min_height, self.y + self.height - min_height)
+        } else {
+            rng.get_i32(self.x + min_width, self.x + self.width - min_width)
+        };
+
+        self.split_once(horizontal, position);
+        if let Some(children) = &mut self.children {
+            for child in children.iter_mut() {
+                child.split_recursive(
+                    rng,
+                    count - 1,
+                    min_width,
+                    min_height,
+                    max_width_ratio,
+                    max_height_ratio,
+                );
+            }
+        }
+    }
+
+    /// Moves and resizes the node to the given area, along with its children, keeping the split
+    /// positions as they are.
+    pub fn resize(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        self.x = x;
+        self.y = y;
+        self.width = width;
+        self.height = height;
+
+        let (horizontal, position) = (self.horizontal, self.split_position);
+        if let Some(children) = &mut self.children {
+            let [left, right] = &mut **children;
+            if horizontal {
+                left.resize(x, y, width, position - y);
+                right.resize(x, position, width, y + height - position);
+            } else {
+                left.resize(x, y, position - x, height);
+                right.resize(position, y, x + width - position, height);
+            }
+        }
+    }
+
+    /// Splits the node recursively according to `params`, then places a room inside every leaf
+    /// and digs corridors between the rooms of every pair of sibling subtrees, so that every
+    /// room is reachable from every other room.
+    ///
+    /// Every room is passed to `on_room`. Corridors are made up of horizontal and vertical
+    /// segments, whose end points, both inclusive, are passed to `on_corridor`. A corridor
+    /// starts and ends at room centers; it may pass through other rooms.
+    ///
+    /// Any existing children of the node are replaced.
+    ///
+    /// # Panics
+    ///
+    /// If the node is too small to fit a room of `params.min_room_size` along with its
+    /// padding.
+    pub fn generate_dungeon<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        params: DungeonParams,
+        mut on_room: impl FnMut(Rectangle),
+        mut on_corridor: impl FnMut(Position, Position),
+    ) {
+        let min_size = params.min_room_size + 2 * params.room_padding;
+        assert!(
+            params.min_room_size > 0 && self.width >= min_size && self.height >= min_size,
+            "the node is too small to fit a room"
+        );
+
+        self.remove_children();
+        /* Same ratios as the libtcod samples, which keeps rooms from getting too narrow */
+        self.split_recursive(rng, params.max_depth, min_size, min_size, 1.5, 1.5);
+        self.dig_rooms(rng, &params, &mut on_room, &mut on_corridor);
+    }
+
+    /* Returns the rooms dug in this subtree */
+    fn dig_rooms<R: Rng>(
+        &self,
+        rng: &mut R,
+        params: &DungeonParams,
+        on_room: &mut impl FnMut(Rectangle),
+        on_corridor: &mut impl FnMut(Position, Position),
+    ) -> Vec<Rectangle> {
+        let [left, right] = if let Some(children) = &self.children {
+            &**children
+        } else {
+            let padding = params.room_padding;
+            let width = rng.get_i32(params.min_room_size, self.width - 2 * padding);
+            let height = rng.get_i32(params.min_room_size, self.height - 2 * padding);
+            let x = rng.get_i32(self.x + padding, self.x + self.width - padding - width);
+            let y = rng.get_i32(self.y + padding, self.y + self.height - padding - height);
+            let room = Rectangle::new_from_raw(x, y, width as u32, height as u32);
+            on_room(room);
+            return vec![room];
+        };
+
+        let mut rooms = left.dig_rooms(rng, params, on_room, on_corridor);
+        let right_rooms = right.dig_rooms(rng, params, on_room, on_corridor);
+
+        let center = |room: &Rectangle| {
+            Position::new(
+                room.position.x + room.size.width as i32 / 2,
+                room.position.y + room.size.height as i32 / 2,
+            )
+        };
+        let from = center(&rooms[rng.get_i32(0, rooms.len() as i32 - 1) as usize]);
+        let to = center(&right_rooms[rng.get_i32(0, right_rooms.len() as i32 - 1) as usize]);
+        let corner = Position::new(to.x, from.y);
+        on_corridor(from, corner);
+        on_corridor(corner, to);
+
+        rooms.extend(right_rooms);
+        rooms
+    }
+
+    /// Visits the node, then its left subtree, then its right subtree.
+    ///
+    /// As soon as the `callback` returns `false`, the traversal stops, and `false` is returned.
+    pub fn traverse_pre_order<F: FnMut(&mut Self) -> bool>(&mut self, mut callback: F) -> bool {
+        self.pre_order(&mut callback)
+    }
+
+    /// Visits the node's left subtree, then the node, then its right subtree.
+    ///
+    /// As soon as the `callback` returns `false`, the traversal stops, and `false` is returned.
+    pub fn traverse_in_order<F: FnMut(&mut Self) -> bool>(&mut self, mut callback: F) -> bool {
+        self.in_order(&mut callback)
+    }
+
+    /// Visits the node's left subtree, then its right subtree, then the node.
+    ///
+    /// As soon as the `callback` returns `false`, the traversal stops, and `false` is returned.
+    pub fn traverse_post_order<F: FnMut(&mut Self) -> bool>(&mut self, mut callback: F) -> bool {
+        self.post_order(&mut callback)
+    }
+
+    /// Visits the nodes one level at a time, starting with the node itself, and going from left
+    /// to right within each level.
+    ///
+    /// As soon as the `callback` returns `false`, the traversal stops, and `false` is returned.
+    pub fn traverse_level_order<F: FnMut(&mut Self) -> bool>(&mut self, mut callback: F) -> bool {
+        for depth in 0.. {
+            match self.visit_level(depth, false, &mut callback) {
+                None => return false,
+                Some(false) => break,
+                Some(true) => {}
+            }
+        }
+        true
+    }
+
+    /// Visits the nodes in the exact opposite order of
+    /// [`traverse_level_order`](#method.traverse_level_order): one level at a time, starting
+    /// with the deepest level, and going from right to left within each level. Every node is
+    /// visited after all of its children.
+    ///
+    /// As soon as the `callback` returns `false`, the traversal stops, and `false` is returned.
+    pub fn traverse_inverted_level_order<F: FnMut(&mut Self) -> bool>(
+        &mut self,
+        mut callback: F,
+    ) -> bool {
+        for depth in (0..=self.depth()).rev() {
+            if self.visit_level(depth, true, &mut callback).is_none() {
+                return false;
+            }
+        }
+        true
+    }
+
+    /* How many levels the subtree has below this node */
+    fn depth(&self) -> usize {
+        self.children.as_ref().map_or(0, |children| {
+            1 + children[0].depth().max(children[1].depth())
+        })
+    }
+
+    fn pre_order<F: FnMut(&mut Self) -> bool>(&mut self, callback: &mut F) -> bool {
+        if !callback(self) {
+            return false;
+        }
+        match &mut self.children {
+            Some(children) => children.iter_mut().all(|child| child.pre_order(callback)),
+            None => true,
+        }
+    }
+
+    fn in_order<F: FnMut(&mut Self) -> bool>(&mut self, callback: &mut F) -> bool {
+        if let Some(left) = self.left_mut() {
+            if !left.in_order(callback) {
+                return false;
+            }
+        }
+        if !callback(self) {
+            return false;
+        }
+        self.right_mut()
+            .is_none_or(|right| right.in_order(callback))
+    }
+
+    fn post_order<F: FnMut(&mut Self) -> bool>(&mut self, callback: &mut F) -> bool {
+        if let Some(children) = &mut self.children {
+            if !children.iter_mut().all(|child| child.post_order(callback)) {
+                return false;
+            }
+        }
+        callback(self)
+    }
+
+    /* Visits the nodes `depth` levels below this one. Returns `None` if the callback stopped the
+     * traversal, otherwise whether there were any nodes at that depth */
+    fn visit_level<F: FnMut(&mut Self) -> bool>(
+        &mut self,
+        depth: usize,
+        reverse: bool,
+        callback: &mut F,
+    ) -> Option<bool> {
+        if depth == 0 {
+            return if callback(self) { Some(true) } else { None };
+        }
+
+        let children = match &mut self.children {
+            Some(children) => children,
+            None => return Some(false),
+        };
+        let [left, right] = &mut **children;
+        let (first, second) = if reverse {
+            (right, left)
+        } else {
+            (left, right)
+        };
+        let found_first = first.visit_level(depth - 1, reverse, callback)?;
+        let found_second = second.visit_level(depth - 1, reverse, callback)?;
+        Some(found_first || found_second)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bsp::{Bsp, DungeonParams};
+    use crate::random::Random;
+    use crate::{Position, Rectangle};
+
+    /* A tree with three levels, where only the left child of the root is split again */
+    fn tree() -> Bsp {
+        let mut bsp = Bsp::new(0, 0, 20, 10);
+        bsp.split_once(false, 8);
+        bsp.left_mut().unwrap().split_once(true, 4);
+        bsp
+    }
+
+    type Node = (i32, i32, usize);
+    type Traversal = fn(&mut Bsp, &mut dyn FnMut(&mut Bsp) -> bool) -> bool;
+
+    fn visited(traverse: Traversal) -> Vec<Node> {
+        let mut bsp = tree();
+        let mut nodes = Vec::new();
+        assert!(traverse(&mut bsp, &mut |node| {
+            nodes.push((node.x(), node.y(), node.level()));
+            true
+        }));
+        nodes
+    }
+
+    const ROOT: Node = (0, 0, 0);
+    const LEFT: Node = (0, 0, 1);
+    const RIGHT: Node = (8, 0, 1);
+    const LEFT_TOP: Node = (0, 0, 2);
+    const LEFT_BOTTOM: Node = (0, 4, 2);
+
+    #[test]
+    fn split_once_divides_the_area() {
+        let bsp = tree();
+        assert!(!bsp.is_leaf());
+        let right = bsp.right().unwrap();
+        assert_eq!(
+            (8, 0, 12, 10),
+            (right.x(), right.y(), right.width(), right.height())
+        );
+        let bottom = bsp.left().unwrap().right().unwrap();
+        assert_eq!(
+            (0, 4, 8, 6),
+            (bottom.x(), bottom.y(), bottom.width(), bottom.height())
+        );
+        assert!(bottom.is_leaf());
+
+        let found = bsp.find_node(Position::new(3, 7)).unwrap();
+        assert_eq!((0, 4), (found.x(), found.y()));
+        assert!(bsp.find_node(Position::new(20, 0)).is_none());
+    }
+
+    #[test]
+    fn traversal_orders() {
+        assert_eq!(
+            vec![ROOT, LEFT, LEFT_TOP, LEFT_BOTTOM, RIGHT],
+            visited(|bsp, f| bsp.traverse_pre_order(f))
+        );
+        assert_eq!(
+            vec![LEFT_TOP, LEFT, LEFT_BOTTOM, ROOT, RIGHT],
+            visited(|bsp, f| bsp.traverse_in_order(f))
+        );
+        assert_eq!(
+            vec![LEFT_TOP, LEFT_BOTTOM, LEFT, RIGHT, ROOT],
+            visited(|bsp, f| bsp.traverse_post_order(f))
+        );
+        assert_eq!(
+            vec![ROOT, LEFT, RIGHT, LEFT_TOP, LEFT_BOTTOM],
+            visited(|bsp, f| bsp.traverse_level_order(f))
+        );
+        assert_eq!(
+            vec![LEFT_BOTTOM, LEFT_TOP, RIGHT, LEFT, ROOT],
+            visited(|bsp, f| bsp.traverse_inverted_level_order(f))
+        );
+    }
+
+    #[test]
+    fn inverted_level_order_visits_children_first() {
+        let mut bsp = Bsp::new(0, 0, 80, 50);
+        bsp.split_recursive(&mut Random::new_mt_from_seed(7), 5, 4, 4, 1.5, 1.5);
+
+        /* Every node counts its own descendants; a node visited before its children would
+         * count fewer than it has */
+        let mut visited = 0;
+        assert!(bsp.traverse_inverted_level_order(|node| {
+            let descendants = match (node.left(), node.right()) {
+                (Some(left), Some(right)) => left.split_position() + right.split_position() + 2,
+                _ => 0,
+            };
+            node.split_position = descendants;
+            visited += 1;
+            true
+        }));
+        assert_eq!(visited, bsp.split_position() + 1);
+        assert!(visited > 8);
+    }
+
+    #[test]
+    fn returning_false_stops_the_traversal() {
+        let mut bsp = tree();
+        let mut count = 0;
+        assert!(!bsp.traverse_pre_order(|node| {
+            count += 1;
+            node.level() < 2
+        }));
+        assert_eq!(3, count);
+
+        count = 0;
+        assert!(!bsp.traverse_inverted_level_order(|_| {
+            count += 1;
+            count < 2
+        }));
+        assert_eq!(2, count);
+    }
+
+    #[test]
+    fn split_recursive_respects_minimum_sizes() {
+        let mut bsp = Bsp::new(0, 0, 60, 40);
+        bsp.split_recursive(&mut Random::new_mt_from_seed(11), 6, 5, 4, 2.0, 2.0);
+        let mut leaves = 0;
+        let mut area = 0;
+        bsp.traverse_pre_order(|node| {
+            if node.is_leaf() {
+                assert!(node.width() >= 5 && node.height() >= 4);
+                leaves += 1;
+                area += node.width() * node.height();
+            }
+            true
+        });
+        assert!(leaves > 8);
+        assert_eq!(60 * 40, area);
+
+        bsp.resize(10, 10, 70, 50);
+        assert_eq!(Some(10), bsp.left().map(Bsp::x));
+        bsp.remove_children();
+        assert!(bsp.is_leaf());
+    }
+
+    #[test]
+    fn generated_dungeons_are_connected() {
+        const WIDTH: i32 = 80;
+        const HEIGHT: i32 = 50;
+
+        let mut bsp = Bsp::new(0, 0, WIDTH, HEIGHT);
+        let mut rooms = Vec::new();
+        let mut corridors = Vec::new();
+        bsp.generate_dungeon(
+            &mut Random::new_mt_from_seed(3),
+            DungeonParams::default(),
+            |room| rooms.push(room),
+            |from, to| corridors.push((from, to)),
+        );
+
+        let mut leaves = 0;
+        bsp.traverse_pre_order(|node| {
+            if node.is_leaf() {
+                let inside = |room: &&Rectangle| {
+                    room.position.x > node.x()
+                        && room.position.y > node.y()
+                        && room.position.x + (room.size.width as i32) < node.x() + node.width()
+                        && room.position.y + (room.size.height as i32) < node.y() + node.height()
+                };
+                assert_eq!(1, rooms.iter().filter(inside).count());
+                leaves += 1;
+            }
+            true
+        });
+        assert_eq!(leaves, rooms.len());
+        assert!(leaves > 8);
+
+        let mut open = vec![false; (WIDTH * HEIGHT) as usize];
+        for room in &rooms {
+            for y in room.position.y..room.position.y + room.size.height as i32 {
+                for x in room.position.x..room.position.x + room.size.width as i32 {
+                    open[(x + y * WIDTH) as usize] = true;
+                }
+            }
+        }
+        for (from, to) in &corridors {
+            assert!(from.x == to.x || from.y == to.y);
+            for y in from.y.min(to.y)..=from.y.max(to.y) {
+                for x in from.x.min(to.x)..=from.x.max(to.x) {
+                    open[(x + y * WIDTH) as usize] = true;
+                }
+            }
+        }
+
+        let start = rooms[0].position;
+        let mut reached = vec![false; open.len()];
+        reached[(start.x + start.y * WIDTH) as usize] = true;
+        let mut stack = vec![start];
+        while let Some(cell) = stack.pop() {
+            for &(dx, dy) in &[(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let (x, y) = (cell.x + dx, cell.y + dy);
+                if x < 0 || y < 0 || x >= WIDTH || y >= HEIGHT {
+                    continue;
+                }
+                let index = (x + y * WIDTH) as usize;
+                if open[index] && !reached[index] {
+                    reached[index] = true;
+                    stack.push(Position::new(x, y));
+                }
+            }
+        }
+        assert!(rooms
+            .iter()
+            .all(|room| reached[(room.position.x + room.position.y * WIDTH) as usize]));
+    }
+}
@@ -77,8 +77,11 @@
 //! <tr><td>WHITE</td><td title="WHITE (255, 255, 255)" style="background-color: rgb(255, 255, 255); --darkreader-inline-bgcolor:#181a1b;" data-darkreader-inline-bgcolor=""></td></tr>
 //! </tbody></table>
 
+pub mod colors;
+
 use crate::util::FloorRem;
-use std::ops::{Add, Mul, Sub};
+use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+use std::str::FromStr;
 
 pub use Color as Colour;
 
@@ -132,6 +135,171 @@ impl Color {
         Self { r, g, b, a }
     }
 
+    /// Returns a new Color from a hexadecimal string like `"#ff8000"`.
+    ///
+    /// The leading `#` is optional, and the digits are case-insensitive. Three digits (`#rgb`)
+    /// give each component as a single digit, six digits (`#rrggbb`) give each component as
+    /// two digits, and eight digits (`#rrggbbaa`) additionally give the opacity.
+    ///
+    /// # Errors
+    /// If the string doesn't have 3, 6 or 8 digits, or contains non-hexadecimal characters.
+    ///
+    /// # Example
+    /// ```
+    /// # use doryen_extra::color::Color;
+    /// assert_eq!(Color::from_hex("#ff8000"), Ok(Color::new(255, 128, 0)));
+    /// assert_eq!(Color::from_hex("F80"), Ok(Color::new(255, 136, 0)));
+    /// assert_eq!(Color::from_hex("#ff800080"), Ok(Color::new_with_alpha(255, 128, 0, 128)));
+    /// ```
+    pub fn from_hex(s: &str) -> Result<Self, ColorParseError> {
+        let error = |kind| ColorParseError {
+            input: s.to_owned(),
+            kind,
+        };
+
+        let digits = s.strip_prefix('#').unwrap_or(s);
+        let mut values = [0_u8; 8];
+        let mut count = 0;
+        for c in digits.chars() {
+            let value = c
+                .to_digit(16)
+                .ok_or_else(|| error(ColorParseErrorKind::InvalidCharacter(c)))?;
+            if count < values.len() {
+                values[count] = value as u8;
+            }
+            count += 1;
+        }
+
+        let component = |i: usize| values[2 * i] << 4 | values[2 * i + 1];
+        match count {
+            3 => Ok(Self::new(
+                values[0] * 0x11,
+                values[1] * 0x11,
+                values[2] * 0x11,
+            )),
+            6 => Ok(Self::new(component(0), component(1), component(2))),
+            8 => Ok(Self::new_with_alpha(
+                component(0),
+                component(1),
+                component(2),
+                component(3),
+            )),
+            _ => Err(error(ColorParseErrorKind::InvalidLength(count))),
+        }
+    }
+
+    /// Returns the color as a lowercase hexadecimal string like `"#ff8000"`.
+    ///
+    /// The opacity isn't included.
+    ///
+    /// # Example
+    /// ```
+    /// # use doryen_extra::color::Color;
+    /// assert_eq!(Color::new(255, 128, 0).to_hex(), "#ff8000");
+    /// ```
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// Returns the squared Euclidean distance between two colors' RGB components.
+    ///
+    /// The opacity isn't taken into account.
+    ///
+    /// # Example
+    /// ```
+    /// # use doryen_extra::color::Color;
+    /// assert_eq!(Color::new(1, 2, 3).distance_squared(Color::new(4, 6, 3)), 25);
+    /// ```
+    pub fn distance_squared(self, other: Self) -> u32 {
+        let square = |a: u8, b: u8| {
+            let d = u32::from(a.max(b) - a.min(b));
+            d * d
+        };
+
+        square(self.r, other.r) + square(self.g, other.g) + square(self.b, other.b)
+    }
+
+    /// Returns the index of, and a reference to, the color in `palette` closest to this color,
+    /// as measured by [`distance_squared`], or `None` if the palette is empty.
+    ///
+    /// If several colors are equally close, the first of them is returned.
+    ///
+    /// [`distance_squared`]: #method.distance_squared
+    ///
+    /// # Example
+    /// ```
+    /// # use doryen_extra::color::Color;
+    /// let palette = [Color::BLACK, Color::RED, Color::WHITE];
+    /// assert_eq!(Color::DARK_RED.nearest_in(&palette), Some((1, &Color::RED)));
+    /// ```
+    pub fn nearest_in(self, palette: &[Self]) -> Option<(usize, &Self)> {
+        palette
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &color)| self.distance_squared(color))
+    }
+
+    /// Blends `other` onto this color using the given blend mode, and returns the result.
+    ///
+    /// This color is the background and `other` is the color laid on top of it. The `alpha` is
+    /// the opacity with which the blended color is applied, clamped to the range \[0, 1\]; with an
+    /// `alpha` of 1, each mode gives the same result as libtcod's background flag of the same
+    /// name. The opacity of this color is kept as is.
+    ///
+    /// # Example
+    /// ```
+    /// # use doryen_extra::color::{BlendMode, Color};
+    /// let color = Color::new(200, 100, 50);
+    /// assert_eq!(color.blend(Color::WHITE, BlendMode::Multiply, 1.0), color);
+    /// assert_eq!(color.blend(Color::WHITE, BlendMode::Alpha, 0.0), color);
+    /// ```
+    pub fn blend(self, other: Self, mode: BlendMode, alpha: f32) -> Self {
+        let channel = |back: u8, front: u8| {
+            let (back, front) = (u32::from(back), u32::from(front));
+            let value = match mode {
+                BlendMode::Alpha => front,
+                BlendMode::Add => back + front,
+                BlendMode::Multiply => back * front / 255,
+                BlendMode::Screen => 255 - (255 - back) * (255 - front) / 255,
+                BlendMode::Darken => back.min(front),
+                BlendMode::Lighten => back.max(front),
+                BlendMode::Overlay => {
+                    if front <= 128 {
+                        2 * front * back / 255
+                    } else {
+                        255 - (2 * (255 - front) * (255 - back) / 255).min(255)
+                    }
+                }
+            };
+            value.min(255) as u8
+        };
+
+        let blended = Self::new_with_alpha(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+            self.a,
+        );
+        self.lerp_rgb(blended, alpha.clamp(0.0, 1.0))
+    }
+
+    /// Lays this color over the `background` with the given `alpha`, and returns the result.
+    ///
+    /// Each channel, including the opacity, is `self * alpha + background * (1 - alpha)`, with
+    /// the `alpha` clamped to the range \[0, 1\], so an `alpha` of 0 gives the background and
+    /// one of 1 gives this color. This is the same as
+    /// `background.blend(self, BlendMode::Alpha, alpha)`, except for the opacity, which `blend`
+    /// keeps from the background.
+    ///
+    /// # Example
+    /// ```
+    /// # use doryen_extra::color::Color;
+    /// assert_eq!(Color::BLACK.over(Color::WHITE, 0.5), Color::GRAY);
+    /// ```
+    pub fn over(self, background: Self, alpha: f32) -> Self {
+        background.lerp_rgb(self, alpha.clamp(0.0, 1.0))
+    }
+
     /// Returns a new Color from HSV values.
     ///
     /// The saturation and value parameters are automatically clamped to 0 and 1.
@@ -173,7 +341,7 @@ impl Color {
     /// let translucent_light_blue = Color::new_hsv_with_opacity(240.0, 0.75, 1.0, 0.5);
     /// ```
     pub fn new_hsv_with_opacity(hue: f32, saturation: f32, value: f32, opacity: f32) -> Self {
-        let a = (opacity.max(0.0).min(1.0) * 255.0).round() as u8;
+        let a = (opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
         let mut color = Self::new_with_alpha(0, 0, 0, a);
         color.set_hsv(hue, saturation, value);
 
@@ -190,8 +358,8 @@ impl Color {
     /// Values outside the given ranges are clipped to fit within the allowed range.
     #[allow(clippy::many_single_char_names)]
     pub fn set_hsv(&mut self, hue: f32, saturation: f32, value: f32) {
-        let saturation = saturation.max(0.0).min(1.0);
-        let value = value.max(0.0).min(1.0);
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
 
         if saturation == 0.0 {
             /* achromatic (gray) */
@@ -238,7 +406,7 @@ impl Color {
     ///
     /// # Parameters
     /// * `hue` - The color's hue in degrees. Values outside the
-    /// given range loop around to fit within the allowed range.
+    ///   given range loop around to fit within the allowed range.
     ///
     /// # Examples
     /// ```rust
@@ -312,7 +480,7 @@ impl Color {
     ///
     /// # Parameters
     /// * `saturation` - The color's saturation, from 0 to 1. Values outside the
-    /// given range are clipped to fit within the allowed range.
+    ///   given range are clipped to fit within the allowed range.
     ///
     /// # Examples
     /// ```rust
@@ -458,7 +626,7 @@ impl Color {
     /// # Parameters
     /// * `key_colors` -  The colors to make gradients between.
     /// * `gradient_spans` -  How many interpolated colors to generate between each
-    /// pair of key colors.
+    ///   pair of key colors.
     ///
     /// # Panics
     /// * If `gradient_spans`' length isn't one less than `key_colors`' length.
@@ -525,7 +693,7 @@ impl Color {
     /// # Parameters
     /// * `key_colors` -  The colors to make gradients between.
     /// * `gradient_spans` -  How many interpolated colors to generate between each
-    /// pair of key colors.
+    ///   pair of key colors.
     ///
     /// # Panics
     /// * If `gradient_spans`' length isn't one less than `key_colors`' length.
@@ -592,7 +760,7 @@ impl Color {
     /// If `coefficient` is outside the range \[0, 1\].
     pub fn lerp_rgb(self, other: Self, coefficient: f32) -> Self {
         assert!(
-            coefficient >= 0.0 && coefficient <= 1.0,
+            (0.0..=1.0).contains(&coefficient),
             "coefficient is outside the acceptable range [0, 1]"
         );
 
@@ -615,7 +783,7 @@ impl Color {
     /// If `coefficient` is outside the range \[0, 1\].
     pub fn lerp_hsv(self, other: Self, coefficient: f32) -> Self {
         assert!(
-            coefficient >= 0.0 && coefficient <= 1.0,
+            (0.0..=1.0).contains(&coefficient),
             "coefficient is outside the acceptable range [0, 1]"
         );
         let (self_hue, self_saturation, self_value) = self.get_hsv();
@@ -645,6 +813,140 @@ impl Color {
             opacity_interpolated,
         )
     }
+
+    /// Interpolate two colors together using their RGB representation and return the result,
+    /// like libtcod's `TCOD_color_lerp`.
+    ///
+    /// This is the same as `a.lerp_rgb(b, coefficient)`, except that the coefficient is clamped
+    /// to the range \[0, 1\] instead of causing a panic.
+    ///
+    /// # Parameters
+    /// * `a` - The first color.
+    /// * `b` - The second color.
+    /// * `coefficient` - The coefficient. 0 for entirely the first color, 1 for entirely the second.
+    ///
+    /// # Example
+    /// ```
+    /// # use doryen_extra::color::Color;
+    /// assert_eq!(Color::lerp(Color::BLACK, Color::WHITE, 2.0), Color::WHITE);
+    /// ```
+    pub fn lerp(a: Self, b: Self, coefficient: f32) -> Self {
+        a.lerp_rgb(b, coefficient.clamp(0.0, 1.0))
+    }
+
+    /// Returns this color with its red, green and blue channels multiplied by `factor`, like
+    /// libtcod's `TCOD_color_multiply_scalar`.
+    ///
+    /// The channels are rounded to the nearest integer and clamped to the range \[0, 255\], so
+    /// a negative `factor` gives black. Unlike multiplying the color by an `f32`, the opacity is
+    /// kept as is.
+    ///
+    /// # Example
+    /// ```
+    /// # use doryen_extra::color::Color;
+    /// assert_eq!(Color::new(10, 100, 200).scale(2.0), Color::new(20, 200, 255));
+    /// ```
+    pub fn scale(self, factor: f32) -> Self {
+        let channel = |value: u8| (f32::from(value) * factor).round().clamp(0.0, 255.0) as u8;
+
+        Self::new_with_alpha(channel(self.r), channel(self.g), channel(self.b), self.a)
+    }
+
+    /// Returns the gray with the same luma as this color, using the Rec. 601 weights of 0.299 for
+    /// red, 0.587 for green and 0.114 for blue. The opacity is kept as is.
+    ///
+    /// # Example
+    /// ```
+    /// # use doryen_extra::color::Color;
+    /// assert_eq!(Color::new(255, 0, 0).grayscale(), Color::new(76, 76, 76));
+    /// ```
+    pub fn grayscale(self) -> Self {
+        let luma =
+            0.299 * f32::from(self.r) + 0.587 * f32::from(self.g) + 0.114 * f32::from(self.b);
+        let luma = luma.round().min(255.0) as u8;
+
+        Self::new_with_alpha(luma, luma, luma, self.a)
+    }
+
+    /// Returns this color moved towards its [`grayscale`](#method.grayscale) by `amount`,
+    /// clamped to the range \[0, 1\]; 0 keeps the color as is, and 1 gives its grayscale.
+    ///
+    /// # Example
+    /// ```
+    /// # use doryen_extra::color::Color;
+    /// let color = Color::new(200, 100, 50);
+    /// assert_eq!(color.desaturate(1.0), color.grayscale());
+    /// assert_eq!(color.desaturate(-1.0), color);
+    /// ```
+    pub fn desaturate(self, amount: f32) -> Self {
+        self.lerp_rgb(self.grayscale(), amount.clamp(0.0, 1.0))
+    }
+
+    /// Fills `out` with an interpolated gradient of colors using RGB interpolation, like
+    /// libtcod's `TCOD_color_gen_map`.
+    ///
+    /// `key_colors[i]` is placed at `out[key_indexes[i]]`, and the colors between each pair of
+    /// keys are interpolated between the two key colors. Colors in `out` before the first key
+    /// index or after the last one are left untouched.
+    ///
+    /// # Parameters
+    /// * `key_colors` - The colors to make gradients between.
+    /// * `key_indexes` - The index in `out` of each of the key colors.
+    /// * `out` - The colors to fill.
+    ///
+    /// # Errors
+    /// * If `key_colors` and `key_indexes` don't have the same length.
+    /// * If `key_indexes` isn't strictly increasing.
+    /// * If any of the `key_indexes` is outside the bounds of `out`.
+    ///
+    /// # Example
+    /// ```
+    /// # use doryen_extra::color::Color;
+    /// let mut map = [Color::BLACK; 5];
+    /// Color::fill_gradient_map(&[Color::BLACK, Color::WHITE], &[0, 4], &mut map).unwrap();
+    ///
+    /// assert_eq!(map[2], Color::GRAY);
+    /// ```
+    pub fn fill_gradient_map(
+        key_colors: &[Self],
+        key_indexes: &[usize],
+        out: &mut [Self],
+    ) -> Result<(), GradientMapError> {
+        if key_colors.len() != key_indexes.len() {
+            return Err(GradientMapError::LengthMismatch {
+                key_colors: key_colors.len(),
+                key_indexes: key_indexes.len(),
+            });
+        }
+        for (position, pair) in key_indexes.windows(2).enumerate() {
+            if pair[1] <= pair[0] {
+                return Err(GradientMapError::NotIncreasing {
+                    position: position + 1,
+                });
+            }
+        }
+        if let Some(&index) = key_indexes.last() {
+            if index >= out.len() {
+                return Err(GradientMapError::OutOfBounds {
+                    index,
+                    len: out.len(),
+                });
+            }
+        }
+
+        for (colors, indexes) in Iterator::zip(key_colors.windows(2), key_indexes.windows(2)) {
+            let (start, end) = (indexes[0], indexes[1]);
+            for (i, color) in out[start..=end].iter_mut().enumerate() {
+                let coefficient = i as f32 / (end - start) as f32;
+                *color = colors[0].lerp_rgb(colors[1], coefficient);
+            }
+        }
+        if let (Some(&color), Some(&index)) = (key_colors.first(), key_indexes.first()) {
+            out[index] = color;
+        }
+
+        Ok(())
+    }
 }
 
 // Enums-to-color
@@ -1139,14 +1441,51 @@ impl Mul<f32> for Color {
     /// Multiply a color with a scalar value and return the result.
     fn mul(self, rhs: f32) -> Self::Output {
         Self::new_with_alpha(
-            (f32::from(self.r) * rhs).min(255.0).max(0.0) as u8,
-            (f32::from(self.g) * rhs).min(255.0).max(0.0) as u8,
-            (f32::from(self.b) * rhs).min(255.0).max(0.0) as u8,
-            (f32::from(self.a) * rhs).min(255.0).max(0.0) as u8,
+            (f32::from(self.r) * rhs).clamp(0.0, 255.0) as u8,
+            (f32::from(self.g) * rhs).clamp(0.0, 255.0) as u8,
+            (f32::from(self.b) * rhs).clamp(0.0, 255.0) as u8,
+            (f32::from(self.a) * rhs).clamp(0.0, 255.0) as u8,
         )
     }
 }
 
+impl Mul<Color> for f32 {
+    type Output = Color;
+
+    /// Multiply a scalar value with a color and return the result.
+    fn mul(self, rhs: Color) -> Self::Output {
+        rhs * self
+    }
+}
+
+impl AddAssign for Color {
+    /// Add a color to this one.
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Color {
+    /// Subtract a color from this one.
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for Color {
+    /// Multiply this color with another one.
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl MulAssign<f32> for Color {
+    /// Multiply this color with a scalar value.
+    fn mul_assign(&mut self, rhs: f32) {
+        *self = *self * rhs;
+    }
+}
+
 impl From<Color> for (u8, u8, u8) {
     fn from(c: Color) -> Self {
         (c.r, c.g, c.b)
@@ -1222,9 +1561,138 @@ pub enum Level {
     Darkest,
 }
 
+/// The ways [`Color::blend`] can combine two colors, after libtcod's background flags.
+///
+/// In the descriptions, the background is the color being blended onto and the foreground is
+/// the color being laid on top of it.
+///
+/// [`Color::blend`]: struct.Color.html#method.blend
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum BlendMode {
+    /// The foreground replaces the background.
+    Alpha,
+    /// The foreground is added to the background.
+    Add,
+    /// The background is multiplied by the foreground, which darkens it.
+    Multiply,
+    /// The inverse of multiplying the inverted colors, which lightens the background.
+    Screen,
+    /// The darker of the background and the foreground, per component.
+    Darken,
+    /// The lighter of the background and the foreground, per component.
+    Lighten,
+    /// Multiplies dark foreground components and screens light ones, which increases contrast.
+    Overlay,
+}
+
+/// The error type returned by [`Color::fill_gradient_map`] when its key colors or indexes are
+/// invalid.
+///
+/// [`Color::fill_gradient_map`]: struct.Color.html#method.fill_gradient_map
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum GradientMapError {
+    /// There wasn't the same number of key colors and key indexes.
+    LengthMismatch {
+        /// The number of key colors.
+        key_colors: usize,
+        /// The number of key indexes.
+        key_indexes: usize,
+    },
+    /// The key index at `position` wasn't greater than the one before it.
+    NotIncreasing {
+        /// The position of the offending key index.
+        position: usize,
+    },
+    /// A key index was outside the bounds of the colors to fill.
+    OutOfBounds {
+        /// The offending key index.
+        index: usize,
+        /// The number of colors to fill.
+        len: usize,
+    },
+}
+
+impl std::fmt::Display for GradientMapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LengthMismatch {
+                key_colors,
+                key_indexes,
+            } => write!(
+                f,
+                "got {} key colors but {} key indexes",
+                key_colors, key_indexes
+            ),
+            Self::NotIncreasing { position } => write!(
+                f,
+                "key index at position {} isn't greater than the one before it",
+                position
+            ),
+            Self::OutOfBounds { index, len } => {
+                write!(f, "key index {} is out of bounds for {} colors", index, len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GradientMapError {}
+
+impl FromStr for Color {
+    type Err = ColorParseError;
+
+    /// Parses a color from a hexadecimal string; see [`Color::from_hex`].
+    ///
+    /// [`Color::from_hex`]: struct.Color.html#method.from_hex
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+/// The error type returned when parsing a `Color` from a hexadecimal string fails.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ColorParseError {
+    /// The string that failed to parse.
+    pub input: String,
+    /// What was wrong with the string.
+    pub kind: ColorParseErrorKind,
+}
+
+/// The kinds of errors that can occur when parsing a `Color`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ColorParseErrorKind {
+    /// The string didn't have 3, 6 or 8 hexadecimal digits; the number of digits it had is
+    /// included.
+    InvalidLength(usize),
+    /// The string contained a character that isn't a hexadecimal digit.
+    InvalidCharacter(char),
+}
+
+impl std::fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            ColorParseErrorKind::InvalidLength(count) => write!(
+                f,
+                "invalid color {:?}: expected 3, 6 or 8 hexadecimal digits but found {}",
+                self.input, count
+            ),
+            ColorParseErrorKind::InvalidCharacter(c) => write!(
+                f,
+                "invalid color {:?}: {:?} isn't a hexadecimal digit",
+                self.input, c
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ColorParseError {}
+
 #[cfg(test)]
 mod tests {
-    use crate::color::Color;
+    use crate::color::{BlendMode, Color, ColorParseError, ColorParseErrorKind, GradientMapError};
 
     #[test]
     fn hsv() {
@@ -1256,6 +1724,144 @@ mod tests {
     }
 
     #[test]
+    fn hsv_round_trip() {
+        fn assert_close(expected: Color, actual: Color) {
+            for &(e, a) in &[
+                (expected.r, actual.r),
+                (expected.g, actual.g),
+                (expected.b, actual.b),
+            ] {
+                assert!(
+                    (i32::from(e) - i32::from(a)).abs() <= 1,
+                    "{:?} became {:?}",
+                    expected,
+                    actual
+                );
+            }
+        }
+
+        for r in (0..=255).step_by(15) {
+            for g in (0..=255).step_by(15) {
+                for b in (0..=255).step_by(15) {
+                    let color = Color::new(r, g, b);
+                    let (hue, saturation, value) = color.get_hsv();
+                    assert_close(color, Color::new_hsv(hue, saturation, value));
+                }
+            }
+        }
+
+        /* Hue is undefined on the gray axis, so any hue must give back the same gray */
+        for v in 0..=255 {
+            let gray = Color::new(v, v, v);
+            let (hue, saturation, value) = gray.get_hsv();
+            assert_eq!(0.0, saturation);
+            assert_close(gray, Color::new_hsv(hue, saturation, value));
+            assert_close(gray, Color::new_hsv(123.0, saturation, value));
+        }
+    }
+
+    #[test]
+    fn hsv_wraps_hue_and_clamps_saturation_and_value() {
+        assert_eq!(
+            Color::new_hsv(120.0, 1.0, 1.0),
+            Color::new_hsv(480.0, 1.0, 1.0)
+        );
+        assert_eq!(
+            Color::new_hsv(120.0, 1.0, 1.0),
+            Color::new_hsv(-240.0, 1.0, 1.0)
+        );
+        assert_eq!(
+            Color::new_hsv(30.0, 1.0, 1.0),
+            Color::new_hsv(30.0, 2.0, 3.0)
+        );
+        assert_eq!(
+            Color::new_hsv(30.0, 0.0, 0.0),
+            Color::new_hsv(30.0, -1.0, -2.0)
+        );
+
+        let mut color = Color::new_hsv(350.0, 0.5, 0.5);
+        color.shift_hue(20.0);
+        assert!((color.get_hue() - 10.0).abs() < 1.0);
+        color.shift_hue(-40.0);
+        assert!((color.get_hue() - 330.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn over_composites_with_straight_alpha() {
+        let foreground = Color::new_with_alpha(200, 100, 50, 200);
+        let background = Color::new_with_alpha(10, 20, 30, 40);
+        assert_eq!(background, foreground.over(background, 0.0));
+        assert_eq!(background, foreground.over(background, -3.0));
+        assert_eq!(foreground, foreground.over(background, 1.0));
+        assert_eq!(foreground, foreground.over(background, 3.0));
+        assert_eq!(
+            Color::new_with_alpha(162, 84, 46, 168),
+            foreground.over(background, 0.8)
+        );
+
+        let grey = Color::BLACK.over(Color::WHITE, 0.5);
+        assert_eq!(grey, Color::WHITE.over(Color::BLACK, 0.5));
+        assert!(grey.r == grey.g && grey.g == grey.b && (127..=128).contains(&grey.r));
+    }
+
+    #[test]
+    fn arithmetic_saturates() {
+        let color = Color::new_with_alpha(200, 30, 120, 255);
+        assert_eq!(
+            Color::new_with_alpha(255, 130, 220, 255),
+            color + Color::new_with_alpha(100, 100, 100, 100)
+        );
+        assert_eq!(
+            Color::new_with_alpha(0, 0, 70, 205),
+            color - Color::new_with_alpha(250, 50, 50, 50)
+        );
+        assert_eq!(color, Color::WHITE * color);
+        assert_eq!(color, color * Color::WHITE);
+        assert_eq!(
+            Color::new_with_alpha(0, 0, 0, 0),
+            color * Color::new_with_alpha(0, 0, 0, 0)
+        );
+        assert_eq!(color, color * 1.0);
+        assert_eq!(color * 0.5, 0.5 * color);
+        assert_eq!(Color::WHITE, color * 100.0);
+        assert_eq!(Color::new_with_alpha(0, 0, 0, 0), color * -1.0);
+
+        let mut assigned = color;
+        assigned += Color::new(100, 0, 0);
+        assert_eq!(Color::new(255, 30, 120), assigned);
+        assigned -= Color::new_with_alpha(255, 0, 0, 0);
+        assert_eq!(Color::new(0, 30, 120), assigned);
+        assigned *= Color::new(255, 0, 255);
+        assert_eq!(Color::new(0, 0, 120), assigned);
+        assigned *= 2.0;
+        assert_eq!(Color::new(0, 0, 240), assigned);
+    }
+
+    #[test]
+    fn scale_grayscale_and_desaturate() {
+        assert_eq!(Color::new(128, 128, 128), Color::WHITE.scale(0.5));
+        assert_eq!(Color::BLACK, Color::WHITE.scale(-1.0));
+        assert_eq!(Color::WHITE, Color::new(200, 200, 200).scale(10.0));
+        assert_eq!(42, Color::new_with_alpha(1, 2, 3, 42).scale(3.0).a);
+
+        assert_eq!(Color::new(76, 76, 76), Color::new(255, 0, 0).grayscale());
+        assert_eq!(Color::new(150, 150, 150), Color::new(0, 255, 0).grayscale());
+        assert_eq!(Color::new(29, 29, 29), Color::new(0, 0, 255).grayscale());
+        assert_eq!(Color::WHITE, Color::WHITE.grayscale());
+
+        for &color in &[Color::ORANGE, Color::LIGHT_BLUE, Color::new(12, 34, 56)] {
+            assert_eq!(color.grayscale(), color.desaturate(1.0));
+            assert_eq!(color.grayscale(), color.desaturate(5.0));
+            assert_eq!(color, color.desaturate(0.0));
+
+            let half = color.desaturate(0.5);
+            let gray = color.grayscale();
+            assert!(color.distance_squared(half) < color.distance_squared(gray));
+        }
+    }
+
+    #[test]
+    #[allow(clippy::shadow_unrelated)]
     fn lerp() {
         let black = Color::BLACK;
         let white = Color::WHITE;
@@ -1292,6 +1898,199 @@ mod tests {
         assert_eq!(middle, Color::LIGHTER_FUCHSIA);
     }
 
+    #[test]
+    fn clamped_lerp() {
+        assert_eq!(Color::lerp(Color::BLACK, Color::WHITE, -1.0), Color::BLACK);
+        assert_eq!(Color::lerp(Color::BLACK, Color::WHITE, 0.5), Color::GRAY);
+        assert_eq!(Color::lerp(Color::BLACK, Color::WHITE, 3.0), Color::WHITE);
+    }
+
+    #[test]
+    fn gradient_map() {
+        let mut map = [Color::new(1, 2, 3); 256];
+        Color::fill_gradient_map(
+            &[Color::BLACK, Color::RED, Color::WHITE],
+            &[0, 128, 255],
+            &mut map,
+        )
+        .unwrap();
+
+        assert_eq!(map[0], Color::BLACK);
+        assert_eq!(map[64], Color::new(127, 0, 0));
+        assert_eq!(map[128], Color::RED);
+        assert_eq!(map[255], Color::WHITE);
+        for pair in map.windows(2) {
+            assert!(pair[0].r <= pair[1].r);
+            assert!(pair[0].g <= pair[1].g);
+        }
+    }
+
+    #[test]
+    fn gradient_map_rejects_invalid_keys() {
+        let mut map = [Color::BLACK; 10];
+        let keys = [Color::BLACK, Color::WHITE];
+        assert_eq!(
+            Color::fill_gradient_map(&keys, &[0], &mut map),
+            Err(GradientMapError::LengthMismatch {
+                key_colors: 2,
+                key_indexes: 1
+            })
+        );
+        assert_eq!(
+            Color::fill_gradient_map(&keys, &[5, 5], &mut map),
+            Err(GradientMapError::NotIncreasing { position: 1 })
+        );
+        assert_eq!(
+            Color::fill_gradient_map(&keys, &[0, 10], &mut map),
+            Err(GradientMapError::OutOfBounds { index: 10, len: 10 })
+        );
+        assert_eq!(map, [Color::BLACK; 10]);
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        for &color in &[
+            Color::BLACK,
+            Color::WHITE,
+            Color::CELADON,
+            Color::DESATURATED_HAN,
+            Color::new(1, 2, 3),
+        ] {
+            let hex = color.to_hex();
+            assert_eq!(Color::from_hex(&hex), Ok(color));
+            assert_eq!(hex.trim_start_matches('#').parse(), Ok(color));
+        }
+        assert_eq!(Color::from_hex("#AbCdEf"), Ok(Color::new(0xab, 0xcd, 0xef)));
+        assert_eq!(Color::from_hex("#abc"), Ok(Color::new(0xaa, 0xbb, 0xcc)));
+        assert_eq!(
+            Color::from_hex("12345678"),
+            Ok(Color::new_with_alpha(0x12, 0x34, 0x56, 0x78))
+        );
+    }
+
+    #[test]
+    fn hex_rejects_invalid_strings() {
+        let error = |input: &str, kind| ColorParseError {
+            input: input.to_owned(),
+            kind,
+        };
+
+        assert_eq!(
+            Color::from_hex("#12"),
+            Err(error("#12", ColorParseErrorKind::InvalidLength(2)))
+        );
+        assert_eq!(
+            Color::from_hex("oops"),
+            Err(error("oops", ColorParseErrorKind::InvalidCharacter('o')))
+        );
+        assert_eq!(
+            Color::from_hex(""),
+            Err(error("", ColorParseErrorKind::InvalidLength(0)))
+        );
+        assert_eq!(
+            Color::from_hex("#"),
+            Err(error("#", ColorParseErrorKind::InvalidLength(0)))
+        );
+        assert_eq!(
+            Color::from_hex("#1234567890"),
+            Err(error("#1234567890", ColorParseErrorKind::InvalidLength(10)))
+        );
+        assert!(Color::from_hex("oops")
+            .unwrap_err()
+            .to_string()
+            .contains("\"oops\""));
+    }
+
+    #[test]
+    fn nearest_in_palette() {
+        let palette = [
+            Color::BLACK,
+            Color::new(100, 0, 0),
+            Color::new(0, 100, 0),
+            Color::new(100, 0, 0),
+            Color::WHITE,
+        ];
+
+        assert_eq!(Color::BLACK.nearest_in(&[]), None);
+        for (i, &color) in palette.iter().enumerate() {
+            let (index, nearest) = color.nearest_in(&palette).unwrap();
+            assert_eq!(0, color.distance_squared(*nearest));
+            /* The duplicate at index 3 always resolves to the first one */
+            assert_eq!(if i == 3 { 1 } else { i }, index);
+        }
+
+        /* Equally far from index 1 and 2 */
+        assert_eq!(
+            Some((1, &palette[1])),
+            Color::new(60, 60, 0).nearest_in(&palette)
+        );
+        assert_eq!(
+            Some((4, &Color::WHITE)),
+            Color::LIGHT_GRAY.nearest_in(&palette)
+        );
+    }
+
+    #[test]
+    fn blend_modes() {
+        let color = Color::new(200, 100, 50);
+        let other = Color::new(100, 200, 0);
+
+        assert_eq!(color.blend(other, BlendMode::Alpha, 1.0), other);
+        assert_eq!(
+            color.blend(other, BlendMode::Alpha, 0.5),
+            Color::new(150, 150, 25)
+        );
+        assert_eq!(color.blend(other, BlendMode::Alpha, -1.0), color);
+        assert_eq!(color.blend(other, BlendMode::Alpha, 2.0), other);
+
+        assert_eq!(
+            color.blend(other, BlendMode::Add, 1.0),
+            Color::new(255, 255, 50)
+        );
+        assert_eq!(color.blend(Color::BLACK, BlendMode::Add, 1.0), color);
+
+        assert_eq!(color.blend(Color::WHITE, BlendMode::Multiply, 1.0), color);
+        assert_eq!(
+            color.blend(Color::BLACK, BlendMode::Multiply, 1.0),
+            Color::BLACK
+        );
+        assert_eq!(
+            color.blend(other, BlendMode::Multiply, 1.0),
+            Color::new(78, 78, 0)
+        );
+
+        assert_eq!(color.blend(Color::BLACK, BlendMode::Screen, 1.0), color);
+        assert_eq!(
+            color.blend(Color::WHITE, BlendMode::Screen, 1.0),
+            Color::WHITE
+        );
+        assert_eq!(
+            color.blend(other, BlendMode::Screen, 1.0),
+            Color::new(222, 222, 50)
+        );
+
+        assert_eq!(
+            color.blend(other, BlendMode::Darken, 1.0),
+            Color::new(100, 100, 0)
+        );
+        assert_eq!(
+            color.blend(other, BlendMode::Lighten, 1.0),
+            Color::new(200, 200, 50)
+        );
+
+        assert_eq!(
+            color.blend(other, BlendMode::Overlay, 1.0),
+            Color::new(156, 189, 0)
+        );
+        assert_eq!(
+            Color::WHITE.blend(Color::WHITE, BlendMode::Overlay, 1.0),
+            Color::WHITE
+        );
+
+        let translucent = Color::new_with_alpha(1, 2, 3, 4);
+        assert_eq!(translucent.blend(other, BlendMode::Alpha, 1.0).a, 4);
+    }
+
     #[test]
     fn operations() {
         let color1 = Color::new(31, 63, 127);
@@ -0,0 +1,281 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * Copyright © 2008-2019, Jice and the libtcod contributors.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Libtcod's named colors as free constants.
+//!
+//! These are the same colors as the associated constants on [`Color`], so code ported from
+//! libtcod can refer to e.g. `TCOD_light_blue` as `LIGHT_BLUE` after a
+//! `use doryen_extra::color::colors::*`.
+//!
+//! [`Color`]: ../struct.Color.html
+
+#![allow(missing_docs)]
+
+use crate::color::Color;
+
+/* color values */
+pub const BLACK: Color = Color::BLACK;
+pub const DARKEST_GRAY: Color = Color::DARKEST_GRAY;
+pub const DARKER_GRAY: Color = Color::DARKER_GRAY;
+pub const DARK_GRAY: Color = Color::DARK_GRAY;
+pub const GRAY: Color = Color::GRAY;
+pub const LIGHT_GRAY: Color = Color::LIGHT_GRAY;
+pub const LIGHTER_GRAY: Color = Color::LIGHTER_GRAY;
+pub const LIGHTEST_GRAY: Color = Color::LIGHTEST_GRAY;
+pub const DARKEST_GREY: Color = Color::DARKEST_GREY;
+pub const DARKER_GREY: Color = Color::DARKER_GREY;
+pub const DARK_GREY: Color = Color::DARK_GREY;
+pub const GREY: Color = Color::GREY;
+pub const LIGHT_GREY: Color = Color::LIGHT_GREY;
+pub const LIGHTER_GREY: Color = Color::LIGHTER_GREY;
+pub const LIGHTEST_GREY: Color = Color::LIGHTEST_GREY;
+pub const WHITE: Color = Color::WHITE;
+pub const DARKEST_SEPIA: Color = Color::DARKEST_SEPIA;
+pub const DARKER_SEPIA: Color = Color::DARKER_SEPIA;
+pub const DARK_SEPIA: Color = Color::DARK_SEPIA;
+pub const SEPIA: Color = Color::SEPIA;
+pub const LIGHT_SEPIA: Color = Color::LIGHT_SEPIA;
+pub const LIGHTER_SEPIA: Color = Color::LIGHTER_SEPIA;
+pub const LIGHTEST_SEPIA: Color = Color::LIGHTEST_SEPIA;
+
+/* desaturated */
+pub const DESATURATED_RED: Color = Color::DESATURATED_RED;
+pub const DESATURATED_FLAME: Color = Color::DESATURATED_FLAME;
+pub const DESATURATED_ORANGE: Color = Color::DESATURATED_ORANGE;
+pub const DESATURATED_AMBER: Color = Color::DESATURATED_AMBER;
+pub const DESATURATED_YELLOW: Color = Color::DESATURATED_YELLOW;
+pub const DESATURATED_LIME: Color = Color::DESATURATED_LIME;
+pub const DESATURATED_CHARTREUSE: Color = Color::DESATURATED_CHARTREUSE;
+pub const DESATURATED_GREEN: Color = Color::DESATURATED_GREEN;
+pub const DESATURATED_SEA: Color = Color::DESATURATED_SEA;
+pub const DESATURATED_TURQUOISE: Color = Color::DESATURATED_TURQUOISE;
+pub const DESATURATED_CYAN: Color = Color::DESATURATED_CYAN;
+pub const DESATURATED_SKY: Color = Color::DESATURATED_SKY;
+pub const DESATURATED_AZURE: Color = Color::DESATURATED_AZURE;
+pub const DESATURATED_BLUE: Color = Color::DESATURATED_BLUE;
+pub const DESATURATED_HAN: Color = Color::DESATURATED_HAN;
+pub const DESATURATED_VIOLET: Color = Color::DESATURATED_VIOLET;
+pub const DESATURATED_PURPLE: Color = Color::DESATURATED_PURPLE;
+pub const DESATURATED_FUCHSIA: Color = Color::DESATURATED_FUCHSIA;
+pub const DESATURATED_MAGENTA: Color = Color::DESATURATED_MAGENTA;
+pub const DESATURATED_PINK: Color = Color::DESATURATED_PINK;
+pub const DESATURATED_CRIMSON: Color = Color::DESATURATED_CRIMSON;
+
+/* lightest */
+pub const LIGHTEST_RED: Color = Color::LIGHTEST_RED;
+pub const LIGHTEST_FLAME: Color = Color::LIGHTEST_FLAME;
+pub const LIGHTEST_ORANGE: Color = Color::LIGHTEST_ORANGE;
+pub const LIGHTEST_AMBER: Color = Color::LIGHTEST_AMBER;
+pub const LIGHTEST_YELLOW: Color = Color::LIGHTEST_YELLOW;
+pub const LIGHTEST_LIME: Color = Color::LIGHTEST_LIME;
+pub const LIGHTEST_CHARTREUSE: Color = Color::LIGHTEST_CHARTREUSE;
+pub const LIGHTEST_GREEN: Color = Color::LIGHTEST_GREEN;
+pub const LIGHTEST_SEA: Color = Color::LIGHTEST_SEA;
+pub const LIGHTEST_TURQUOISE: Color = Color::LIGHTEST_TURQUOISE;
+pub const LIGHTEST_CYAN: Color = Color::LIGHTEST_CYAN;
+pub const LIGHTEST_SKY: Color = Color::LIGHTEST_SKY;
+pub const LIGHTEST_AZURE: Color = Color::LIGHTEST_AZURE;
+pub const LIGHTEST_BLUE: Color = Color::LIGHTEST_BLUE;
+pub const LIGHTEST_HAN: Color = Color::LIGHTEST_HAN;
+pub const LIGHTEST_VIOLET: Color = Color::LIGHTEST_VIOLET;
+pub const LIGHTEST_PURPLE: Color = Color::LIGHTEST_PURPLE;
+pub const LIGHTEST_FUCHSIA: Color = Color::LIGHTEST_FUCHSIA;
+pub const LIGHTEST_MAGENTA: Color = Color::LIGHTEST_MAGENTA;
+pub const LIGHTEST_PINK: Color = Color::LIGHTEST_PINK;
+pub const LIGHTEST_CRIMSON: Color = Color::LIGHTEST_CRIMSON;
+
+/* lighter */
+pub const LIGHTER_RED: Color = Color::LIGHTER_RED;
+pub const LIGHTER_FLAME: Color = Color::LIGHTER_FLAME;
+pub const LIGHTER_ORANGE: Color = Color::LIGHTER_ORANGE;
+pub const LIGHTER_AMBER: Color = Color::LIGHTER_AMBER;
+pub const LIGHTER_YELLOW: Color = Color::LIGHTER_YELLOW;
+pub const LIGHTER_LIME: Color = Color::LIGHTER_LIME;
+pub const LIGHTER_CHARTREUSE: Color = Color::LIGHTER_CHARTREUSE;
+pub const LIGHTER_GREEN: Color = Color::LIGHTER_GREEN;
+pub const LIGHTER_SEA: Color = Color::LIGHTER_SEA;
+pub const LIGHTER_TURQUOISE: Color = Color::LIGHTER_TURQUOISE;
+pub const LIGHTER_CYAN: Color = Color::LIGHTER_CYAN;
+pub const LIGHTER_SKY: Color = Color::LIGHTER_SKY;
+pub const LIGHTER_AZURE: Color = Color::LIGHTER_AZURE;
+pub const LIGHTER_BLUE: Color = Color::LIGHTER_BLUE;
+pub const LIGHTER_HAN: Color = Color::LIGHTER_HAN;
+pub const LIGHTER_VIOLET: Color = Color::LIGHTER_VIOLET;
+pub const LIGHTER_PURPLE: Color = Color::LIGHTER_PURPLE;
+pub const LIGHTER_FUCHSIA: Color = Color::LIGHTER_FUCHSIA;
+pub const LIGHTER_MAGENTA: Color = Color::LIGHTER_MAGENTA;
+pub const LIGHTER_PINK: Color = Color::LIGHTER_PINK;
+pub const LIGHTER_CRIMSON: Color = Color::LIGHTER_CRIMSON;
+
+/* light */
+pub const LIGHT_RED: Color = Color::LIGHT_RED;
+pub const LIGHT_FLAME: Color = Color::LIGHT_FLAME;
+pub const LIGHT_ORANGE: Color = Color::LIGHT_ORANGE;
+pub const LIGHT_AMBER: Color = Color::LIGHT_AMBER;
+pub const LIGHT_YELLOW: Color = Color::LIGHT_YELLOW;
+pub const LIGHT_LIME: Color = Color::LIGHT_LIME;
+pub const LIGHT_CHARTREUSE: Color = Color::LIGHT_CHARTREUSE;
+pub const LIGHT_GREEN: Color = Color::LIGHT_GREEN;
+pub const LIGHT_SEA: Color = Color::LIGHT_SEA;
+pub const LIGHT_TURQUOISE: Color = Color::LIGHT_TURQUOISE;
+pub const LIGHT_CYAN: Color = Color::LIGHT_CYAN;
+pub const LIGHT_SKY: Color = Color::LIGHT_SKY;
+pub const LIGHT_AZURE: Color = Color::LIGHT_AZURE;
+pub const LIGHT_BLUE: Color = Color::LIGHT_BLUE;
+pub const LIGHT_HAN: Color = Color::LIGHT_HAN;
+pub const LIGHT_VIOLET: Color = Color::LIGHT_VIOLET;
+pub const LIGHT_PURPLE: Color = Color::LIGHT_PURPLE;
+pub const LIGHT_FUCHSIA: Color = Color::LIGHT_FUCHSIA;
+pub const LIGHT_MAGENTA: Color = Color::LIGHT_MAGENTA;
+pub const LIGHT_PINK: Color = Color::LIGHT_PINK;
+pub const LIGHT_CRIMSON: Color = Color::LIGHT_CRIMSON;
+
+/* normal */
+pub const RED: Color = Color::RED;
+pub const FLAME: Color = Color::FLAME;
+pub const ORANGE: Color = Color::ORANGE;
+pub const AMBER: Color = Color::AMBER;
+pub const YELLOW: Color = Color::YELLOW;
+pub const LIME: Color = Color::LIME;
+pub const CHARTREUSE: Color = Color::CHARTREUSE;
+pub const GREEN: Color = Color::GREEN;
+pub const SEA: Color = Color::SEA;
+pub const TURQUOISE: Color = Color::TURQUOISE;
+pub const CYAN: Color = Color::CYAN;
+pub const SKY: Color = Color::SKY;
+pub const AZURE: Color = Color::AZURE;
+pub const BLUE: Color = Color::BLUE;
+pub const HAN: Color = Color::HAN;
+pub const VIOLET: Color = Color::VIOLET;
+pub const PURPLE: Color = Color::PURPLE;
+pub const FUCHSIA: Color = Color::FUCHSIA;
+pub const MAGENTA: Color = Color::MAGENTA;
+pub const PINK: Color = Color::PINK;
+pub const CRIMSON: Color = Color::CRIMSON;
+
+/* dark */
+pub const DARK_RED: Color = Color::DARK_RED;
+pub const DARK_FLAME: Color = Color::DARK_FLAME;
+pub const DARK_ORANGE: Color = Color::DARK_ORANGE;
+pub const DARK_AMBER: Color = Color::DARK_AMBER;
+pub const DARK_YELLOW: Color = Color::DARK_YELLOW;
+pub const DARK_LIME: Color = Color::DARK_LIME;
+pub const DARK_CHARTREUSE: Color = Color::DARK_CHARTREUSE;
+pub const DARK_GREEN: Color = Color::DARK_GREEN;
+pub const DARK_SEA: Color = Color::DARK_SEA;
+pub const DARK_TURQUOISE: Color = Color::DARK_TURQUOISE;
+pub const DARK_CYAN: Color = Color::DARK_CYAN;
+pub const DARK_SKY: Color = Color::DARK_SKY;
+pub const DARK_AZURE: Color = Color::DARK_AZURE;
+pub const DARK_BLUE: Color = Color::DARK_BLUE;
+pub const DARK_HAN: Color = Color::DARK_HAN;
+pub const DARK_VIOLET: Color = Color::DARK_VIOLET;
+pub const DARK_PURPLE: Color = Color::DARK_PURPLE;
+pub const DARK_FUCHSIA: Color = Color::DARK_FUCHSIA;
+pub const DARK_MAGENTA: Color = Color::DARK_MAGENTA;
+pub const DARK_PINK: Color = Color::DARK_PINK;
+pub const DARK_CRIMSON: Color = Color::DARK_CRIMSON;
+
+/* darker */
+pub const DARKER_RED: Color = Color::DARKER_RED;
+pub const DARKER_FLAME: Color = Color::DARKER_FLAME;
+pub const DARKER_ORANGE: Color = Color::DARKER_ORANGE;
+pub const DARKER_AMBER: Color = Color::DARKER_AMBER;
+pub const DARKER_YELLOW: Color = Color::DARKER_YELLOW;
+pub const DARKER_LIME: Color = Color::DARKER_LIME;
+pub const DARKER_CHARTREUSE: Color = Color::DARKER_CHARTREUSE;
+pub const DARKER_GREEN: Color = Color::DARKER_GREEN;
+pub const DARKER_SEA: Color = Color::DARKER_SEA;
+pub const DARKER_TURQUOISE: Color = Color::DARKER_TURQUOISE;
+pub const DARKER_CYAN: Color = Color::DARKER_CYAN;
+pub const DARKER_SKY: Color = Color::DARKER_SKY;
+pub const DARKER_AZURE: Color = Color::DARKER_AZURE;
+pub const DARKER_BLUE: Color = Color::DARKER_BLUE;
+pub const DARKER_HAN: Color = Color::DARKER_HAN;
+pub const DARKER_VIOLET: Color = Color::DARKER_VIOLET;
+pub const DARKER_PURPLE: Color = Color::DARKER_PURPLE;
+pub const DARKER_FUCHSIA: Color = Color::DARKER_FUCHSIA;
+pub const DARKER_MAGENTA: Color = Color::DARKER_MAGENTA;
+pub const DARKER_PINK: Color = Color::DARKER_PINK;
+pub const DARKER_CRIMSON: Color = Color::DARKER_CRIMSON;
+
+/* darkest */
+pub const DARKEST_RED: Color = Color::DARKEST_RED;
+pub const DARKEST_FLAME: Color = Color::DARKEST_FLAME;
+pub const DARKEST_ORANGE: Color = Color::DARKEST_ORANGE;
+pub const DARKEST_AMBER: Color = Color::DARKEST_AMBER;
+pub const DARKEST_YELLOW: Color = Color::DARKEST_YELLOW;
+pub const DARKEST_LIME: Color = Color::DARKEST_LIME;
+pub const DARKEST_CHARTREUSE: Color = Color::DARKEST_CHARTREUSE;
+pub const DARKEST_GREEN: Color = Color::DARKEST_GREEN;
+pub const DARKEST_SEA: Color = Color::DARKEST_SEA;
+pub const DARKEST_TURQUOISE: Color = Color::DARKEST_TURQUOISE;
+pub const DARKEST_CYAN: Color = Color::DARKEST_CYAN;
+pub const DARKEST_SKY: Color = Color::DARKEST_SKY;
+pub const DARKEST_AZURE: Color = Color::DARKEST_AZURE;
+pub const DARKEST_BLUE: Color = Color::DARKEST_BLUE;
+pub const DARKEST_HAN: Color = Color::DARKEST_HAN;
+pub const DARKEST_VIOLET: Color = Color::DARKEST_VIOLET;
+pub const DARKEST_PURPLE: Color = Color::DARKEST_PURPLE;
+pub const DARKEST_FUCHSIA: Color = Color::DARKEST_FUCHSIA;
+pub const DARKEST_MAGENTA: Color = Color::DARKEST_MAGENTA;
+pub const DARKEST_PINK: Color = Color::DARKEST_PINK;
+pub const DARKEST_CRIMSON: Color = Color::DARKEST_CRIMSON;
+
+/* metallic */
+pub const BRASS: Color = Color::BRASS;
+pub const COPPER: Color = Color::COPPER;
+pub const GOLD: Color = Color::GOLD;
+pub const SILVER: Color = Color::SILVER;
+
+/* miscellaneous */
+pub const CELADON: Color = Color::CELADON;
+pub const PEACH: Color = Color::PEACH;
+
+#[cfg(test)]
+mod tests {
+    use crate::color::colors::*;
+    use crate::color::Color;
+
+    #[test]
+    fn matches_libtcod() {
+        assert_eq!(RED, Color::new(255, 0, 0));
+        assert_eq!(LIGHT_BLUE, Color::new(63, 63, 255));
+        assert_eq!(DARKER_GREEN, Color::new(0, 127, 0));
+        assert_eq!(DESATURATED_ORANGE, Color::new(127, 95, 63));
+        assert_eq!(LIGHTEST_GREY, Color::new(223, 223, 223));
+        assert_eq!(SEPIA, Color::new(127, 101, 63));
+        assert_eq!(GOLD, Color::new(229, 191, 0));
+        assert_eq!(CELADON, Color::new(172, 255, 175));
+    }
+}
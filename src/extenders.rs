@@ -328,8 +328,8 @@ impl ConsoleExtender<'_> {
                 Position::new(x + (w / 2) as i32, y),
                 &text,
                 TextAlign::Center,
-                fore.map(Into::into),
-                back.map(Into::into),
+                fore,
+                back,
             );
         }
     }
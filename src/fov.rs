@@ -0,0 +1,858 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * Copyright © 2008-2019, Jice and the libtcod contributors.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Field of view.
+//!
+//! This module lets you compute which cells of a map are visible from a given position, given
+//! which of the map's cells can be seen through.
+
+mod permissive;
+mod shadowcasting;
+mod symmetric_shadowcasting;
+
+use crate::bresenham::line_iter;
+use crate::{Position, UPosition};
+
+/// The algorithms that can be used for computing the field of view.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum FovAlgorithm {
+    /// Recursive shadowcasting. Fast, but not symmetric; in some configurations a cell `A` can
+    /// see a cell `B` without `B` being able to see `A`.
+    Shadow,
+    /// Albert Ford's symmetric shadowcasting. Whenever a floor cell `A` can see a floor cell `B`,
+    /// `B` can also see `A`.
+    SymmetricShadowcast,
+    /// Precise permissive field of view, with a permissiveness from `0` (the most restrictive)
+    /// to `8` (the most permissive), like libtcod's `FOV_PERMISSIVE_0` to `FOV_PERMISSIVE_8`.
+    /// Higher values let you see further around corners and past pillars. Values above `8` are
+    /// treated as `8`.
+    Permissive(u8),
+}
+
+/// The shapes the range of a field of view can have. Each shape contains the cells as far away
+/// from the origin as the radius, measured in its own way.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum FovShape {
+    /// The cells whose euclidean distance to the origin is at most the radius, like in libtcod.
+    Circle,
+    /// The cells at most the radius away from the origin both horizontally and vertically.
+    Square,
+    /// The cells whose horizontal and vertical distances to the origin add up to at most the
+    /// radius.
+    Diamond,
+    /// A square with its corners cut off; the cells at most the radius away from the origin when
+    /// each diagonal step counts as one and a half steps.
+    Octagon,
+}
+
+impl FovShape {
+    fn contains(self, dx: i64, dy: i64, radius: i64) -> bool {
+        let (dx, dy) = (dx.abs(), dy.abs());
+        match self {
+            Self::Circle => dx * dx + dy * dy <= radius * radius,
+            Self::Square => dx.max(dy) <= radius,
+            Self::Diamond => dx + dy <= radius,
+            Self::Octagon => 2 * dx.max(dy) + dx.min(dy) <= 2 * radius,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+struct Cell {
+    transparent: bool,
+    walkable: bool,
+    in_fov: bool,
+}
+
+/// A map of cells, each of which can be transparent and/or walkable, used for computing the
+/// field of view.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct FovMap {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+    /* Indexes of the cells in the field of view, in the order they were marked */
+    visible: Vec<usize>,
+    /* The origin, radius and wall lighting of the last computed field of view */
+    last_fov: Option<((usize, usize), usize, bool)>,
+}
+
+impl FovMap {
+    /// Returns a new map with the given width and height. Initially, all the cells of the map
+    /// are opaque, not walkable and not in the field of view.
+    ///
+    /// # Panics
+    ///
+    /// If the `width` or the `height` is 0.
+    pub fn new(width: usize, height: usize) -> Self {
+        assert!(width > 0 && height > 0);
+
+        Self {
+            width,
+            height,
+            cells: vec![Cell::default(); width * height],
+            visible: Vec::new(),
+            last_fov: None,
+        }
+    }
+
+    /// Returns the width of the map.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the map.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Sets every cell of the map to the given properties, and removes them from the field of view.
+    pub fn clear(&mut self, transparent: bool, walkable: bool) {
+        for cell in &mut self.cells {
+            *cell = Cell {
+                transparent,
+                walkable,
+                in_fov: false,
+            };
+        }
+        self.visible.clear();
+    }
+
+    /// Sets the properties of the cell at the given position.
+    ///
+    /// # Panics
+    ///
+    /// If the position is outside the range of the map.
+    pub fn set_properties(&mut self, position: UPosition, transparent: bool, walkable: bool) {
+        let cell = self.cell_mut(position);
+        cell.transparent = transparent;
+        cell.walkable = walkable;
+    }
+
+    /// Returns whether the cell at the given position can be seen through.
+    ///
+    /// # Panics
+    ///
+    /// If the position is outside the range of the map.
+    pub fn is_transparent(&self, position: UPosition) -> bool {
+        self.cell(position).transparent
+    }
+
+    /// Returns whether the cell at the given position can be walked on.
+    ///
+    /// # Panics
+    ///
+    /// If the position is outside the range of the map.
+    pub fn is_walkable(&self, position: UPosition) -> bool {
+        self.cell(position).walkable
+    }
+
+    /// Returns whether the cell at the given position was in the field of view the last time it
+    /// was computed.
+    ///
+    /// # Panics
+    ///
+    /// If the position is outside the range of the map.
+    pub fn is_in_fov(&self, position: UPosition) -> bool {
+        self.cell(position).in_fov
+    }
+
+    /// Returns whether `to` can be seen from `from`, i.e. whether every cell on the bresenham line
+    /// between them can be seen through. Unlike [`compute_fov`](#method.compute_fov), this only
+    /// looks at the cells on the line, and doesn't change the field of view.
+    ///
+    /// If `check_endpoints` is `false`, only the cells between `from` and `to` need to be
+    /// transparent, so that walls can be seen, and seen from. Since a bresenham line moves
+    /// diagonally from one cell to the next, it can pass between two walls that only touch at
+    /// their corners.
+    ///
+    /// # Panics
+    ///
+    /// If either position is outside the range of the map.
+    pub fn is_in_line_of_sight(
+        &self,
+        from: UPosition,
+        to: UPosition,
+        check_endpoints: bool,
+    ) -> bool {
+        let from_index = self.index_of(from);
+        let to_index = self.index_of(to);
+        let from = Position::new(from.x as i32, from.y as i32);
+        let to = Position::new(to.x as i32, to.y as i32);
+
+        line_iter(from, to).all(|position| {
+            let index = position.x as usize + position.y as usize * self.width;
+            (!check_endpoints && (index == from_index || index == to_index))
+                || self.cells[index].transparent
+        })
+    }
+
+    /// Marks the cell at the given position as in or out of the field of view.
+    ///
+    /// # Panics
+    ///
+    /// If the position is outside the range of the map.
+    pub fn set_in_fov(&mut self, position: UPosition, in_fov: bool) {
+        let index = self.index_of(position);
+        if in_fov {
+            self.mark_in_fov(index);
+        } else if self.cells[index].in_fov {
+            self.cells[index].in_fov = false;
+            self.visible.retain(|&i| i != index);
+        }
+    }
+
+    /// Returns an iterator over the positions of the cells in the field of view, as `(x, y)`.
+    ///
+    /// This only visits the cells that are in view, rather than the whole map. Before the field of
+    /// view has been computed, it yields nothing.
+    pub fn visible_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.visible
+            .iter()
+            .map(move |&index| (index % self.width, index / self.width))
+    }
+
+    /// Computes the field of view from the given `origin`, replacing the previously computed one.
+    ///
+    /// Only the cells of the previous field of view are cleared first, so recomputing the field
+    /// of view as the `origin` moves around takes time in proportion to how much is seen, not to
+    /// the size of the map.
+    ///
+    /// # Parameters
+    /// * `origin` - The position the map is being looked at from; it is always in view.
+    /// * `max_radius` - How far away from the `origin` cells can be seen. If `0`, there is no limit.
+    /// * `shape` - The shape of the range given by `max_radius`. Ignored if there is no limit.
+    /// * `light_walls` - Whether the opaque cells bordering the field of view are in view as well.
+    /// * `algorithm` - The algorithm used for the computation.
+    ///
+    /// # Panics
+    ///
+    /// If the `origin` is outside the range of the map.
+    pub fn compute_fov(
+        &mut self,
+        origin: UPosition,
+        max_radius: usize,
+        shape: FovShape,
+        light_walls: bool,
+        algorithm: FovAlgorithm,
+    ) {
+        let origin = (origin.x as usize, origin.y as usize);
+        assert!(origin.0 < self.width && origin.1 < self.height);
+
+        /* Only the cells that were in view need clearing, which keeps recomputing cheap on maps
+        much larger than the field of view */
+        for &index in &self.visible {
+            self.cells[index].in_fov = false;
+        }
+        self.visible.clear();
+
+        let (max_radius, shape) = if max_radius == 0 {
+            /* A circle far enough to reach every corner of the map */
+            let max_radius_x = (self.width - origin.0).max(origin.0);
+            let max_radius_y = (self.height - origin.1).max(origin.1);
+            let radius = ((max_radius_x * max_radius_x + max_radius_y * max_radius_y) as f64).sqrt()
+                as usize
+                + 1;
+            (radius, FovShape::Circle)
+        } else {
+            (max_radius, shape)
+        };
+
+        match algorithm {
+            FovAlgorithm::Shadow => {
+                shadowcasting::compute(self, origin, max_radius, shape, light_walls)
+            }
+            FovAlgorithm::SymmetricShadowcast => {
+                symmetric_shadowcasting::compute(self, origin, max_radius, shape, light_walls)
+            }
+            FovAlgorithm::Permissive(permissiveness) => {
+                /* documented on the variant: values above 8 are treated as 8 */
+                permissive::compute(
+                    self,
+                    origin,
+                    max_radius,
+                    shape,
+                    light_walls,
+                    permissiveness.min(8),
+                )
+            }
+        }
+
+        self.mark_in_fov(origin.0 + origin.1 * self.width);
+        self.last_fov = Some((origin, max_radius, light_walls));
+    }
+
+    /// Cleans up which walls are in the last computed field of view, like libtcod's
+    /// post-processing step. Algorithms lighting walls on their own sometimes light walls around
+    /// corners that look out of place.
+    ///
+    /// Afterwards, a wall is only in view if a neighboring floor cell that is closer to the
+    /// origin is in view, i.e. a floor cell on the origin's side of the wall horizontally,
+    /// vertically or diagonally. Each quadrant around the origin is handled independently. This
+    /// gives walls the same lighting as libtcod gives when its algorithms are followed by its
+    /// post-processing.
+    ///
+    /// Does nothing if the field of view hasn't been computed yet, or if it was computed with
+    /// `light_walls` off, since its walls are meant to stay out of view then.
+    pub fn postprocess_walls(&mut self) {
+        let ((x, y), radius) = match self.last_fov {
+            Some((origin, radius, true)) => (origin, radius),
+            _ => return,
+        };
+
+        let origin = x + y * self.width;
+        let cells = &mut self.cells;
+        self.visible.retain(|&index| {
+            let keep = index == origin || cells[index].transparent;
+            cells[index].in_fov = keep;
+            keep
+        });
+
+        let min_x = x.saturating_sub(radius);
+        let min_y = y.saturating_sub(radius);
+        let max_x = (x + radius).min(self.width - 1);
+        let max_y = (y + radius).min(self.height - 1);
+        self.postprocess_quadrant((min_x, min_y), (x, y), (-1, -1));
+        self.postprocess_quadrant((x, min_y), (max_x, y), (1, -1));
+        self.postprocess_quadrant((min_x, y), (x, max_y), (-1, 1));
+        self.postprocess_quadrant((x, y), (max_x, max_y), (1, 1));
+    }
+
+    /* Lights the walls next to the lit floor cells of the quadrant, in the quadrant's direction */
+    fn postprocess_quadrant(
+        &mut self,
+        (x0, y0): (usize, usize),
+        (x1, y1): (usize, usize),
+        (dx, dy): (i64, i64),
+    ) {
+        for cy in y0..=y1 {
+            for cx in x0..=x1 {
+                let index = cx + cy * self.width;
+                if !self.cells[index].in_fov || !self.cells[index].transparent {
+                    continue;
+                }
+
+                let x2 = cx as i64 + dx;
+                let y2 = cy as i64 + dy;
+                let in_x = x2 >= x0 as i64 && x2 <= x1 as i64;
+                let in_y = y2 >= y0 as i64 && y2 <= y1 as i64;
+                for &(nx, ny, inside) in &[
+                    (x2, cy as i64, in_x),
+                    (cx as i64, y2, in_y),
+                    (x2, y2, in_x && in_y),
+                ] {
+                    if !inside {
+                        continue;
+                    }
+                    let neighbor = nx as usize + ny as usize * self.width;
+                    if !self.cells[neighbor].transparent {
+                        self.mark_in_fov(neighbor);
+                    }
+                }
+            }
+        }
+    }
+
+    fn index_of(&self, position: UPosition) -> usize {
+        let (x, y) = (position.x as usize, position.y as usize);
+        assert!(x < self.width && y < self.height);
+        x + y * self.width
+    }
+
+    fn cell(&self, position: UPosition) -> &Cell {
+        &self.cells[self.index_of(position)]
+    }
+
+    fn cell_mut(&mut self, position: UPosition) -> &mut Cell {
+        let index = self.index_of(position);
+        &mut self.cells[index]
+    }
+
+    fn mark_in_fov(&mut self, index: usize) {
+        let cell = &mut self.cells[index];
+        if !cell.in_fov {
+            cell.in_fov = true;
+            self.visible.push(index);
+        }
+    }
+
+    /* Used by the algorithms, which work in signed coordinates that may leave the map */
+    fn index(&self, x: i64, y: i64) -> Option<usize> {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            Some(x as usize + y as usize * self.width)
+        } else {
+            None
+        }
+    }
+}
+
+/* Builds a map for tests from rows of text, where '#' is a wall and anything else is floor */
+#[cfg(test)]
+pub(crate) fn fixture(rows: &[&str]) -> FovMap {
+    let mut map = FovMap::new(rows[0].len(), rows.len());
+    for (y, row) in rows.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            let floor = c != '#';
+            map.set_properties((x as u32, y as u32).into(), floor, floor);
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fov::{fixture, FovAlgorithm, FovMap, FovShape};
+
+    const ALGORITHMS: [FovAlgorithm; 4] = [
+        FovAlgorithm::Shadow,
+        FovAlgorithm::SymmetricShadowcast,
+        FovAlgorithm::Permissive(0),
+        FovAlgorithm::Permissive(8),
+    ];
+
+    const PILLARS: [&str; 9] = [
+        "############",
+        "#....#.....#",
+        "#..#...#...#",
+        "#.....##...#",
+        "##.#.......#",
+        "#....#..#..#",
+        "#.#......#.#",
+        "#....##....#",
+        "############",
+    ];
+
+    #[test]
+    fn open_map_is_fully_visible() {
+        for &algorithm in &ALGORITHMS {
+            let mut map = FovMap::new(7, 5);
+            map.clear(true, true);
+            map.compute_fov((3, 2).into(), 0, FovShape::Circle, true, algorithm);
+            for y in 0..5 {
+                for x in 0..7 {
+                    assert!(
+                        map.is_in_fov((x, y).into()),
+                        "{:?} ({}, {})",
+                        algorithm,
+                        x,
+                        y
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn walls_block_sight() {
+        let rows = ["#######", "#..#..#", "#######"];
+        for &algorithm in &ALGORITHMS {
+            let mut map = fixture(&rows);
+            map.compute_fov((1, 1).into(), 0, FovShape::Circle, true, algorithm);
+            assert!(map.is_in_fov((1, 1).into()));
+            assert!(map.is_in_fov((2, 1).into()));
+            assert!(map.is_in_fov((3, 1).into()));
+            assert!(!map.is_in_fov((4, 1).into()));
+            assert!(!map.is_in_fov((5, 1).into()));
+
+            map.compute_fov((1, 1).into(), 0, FovShape::Circle, false, algorithm);
+            assert!(!map.is_in_fov((3, 1).into()));
+            assert!(!map.is_in_fov((0, 0).into()));
+        }
+    }
+
+    #[test]
+    fn shapes_limit_sight() {
+        /* '*' is in view, from the center of an open 11x11 map with a radius of 5 */
+        let shapes = [
+            (
+                FovShape::Circle,
+                [
+                    ".....*.....",
+                    "..*******..",
+                    ".*********.",
+                    ".*********.",
+                    ".*********.",
+                    "***********",
+                    ".*********.",
+                    ".*********.",
+                    ".*********.",
+                    "..*******..",
+                    ".....*.....",
+                ],
+            ),
+            (
+                FovShape::Square,
+                [
+                    "***********",
+                    "***********",
+                    "***********",
+                    "***********",
+                    "***********",
+                    "***********",
+                    "***********",
+                    "***********",
+                    "***********",
+                    "***********",
+                    "***********",
+                ],
+            ),
+            (
+                FovShape::Diamond,
+                [
+                    ".....*.....",
+                    "....***....",
+                    "...*****...",
+                    "..*******..",
+                    ".*********.",
+                    "***********",
+                    ".*********.",
+                    "..*******..",
+                    "...*****...",
+                    "....***....",
+                    ".....*.....",
+                ],
+            ),
+            (
+                FovShape::Octagon,
+                [
+                    ".....*.....",
+                    "...*****...",
+                    "..*******..",
+                    ".*********.",
+                    ".*********.",
+                    "***********",
+                    ".*********.",
+                    ".*********.",
+                    "..*******..",
+                    "...*****...",
+                    ".....*.....",
+                ],
+            ),
+        ];
+
+        for &algorithm in &ALGORITHMS {
+            for (shape, expected) in &shapes {
+                let mut map = FovMap::new(11, 11);
+                map.clear(true, true);
+                map.compute_fov((5, 5).into(), 5, *shape, true, algorithm);
+                for (y, row) in expected.iter().enumerate() {
+                    for (x, c) in row.chars().enumerate() {
+                        assert_eq!(
+                            c == '*',
+                            map.is_in_fov((x as u32, y as u32).into()),
+                            "{:?} {:?} ({}, {})",
+                            algorithm,
+                            shape,
+                            x,
+                            y
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn no_radius_ignores_shape() {
+        for &algorithm in &ALGORITHMS {
+            let mut map = FovMap::new(15, 9);
+            map.clear(true, true);
+            map.compute_fov((2, 7).into(), 0, FovShape::Diamond, true, algorithm);
+            assert_eq!(15 * 9, map.visible_cells().count());
+        }
+    }
+
+    #[test]
+    fn radius_limits_sight() {
+        for &algorithm in &ALGORITHMS {
+            let mut map = FovMap::new(9, 1);
+            map.clear(true, true);
+            map.compute_fov((0, 0).into(), 3, FovShape::Circle, true, algorithm);
+            for x in 0..9 {
+                assert_eq!(
+                    x <= 3,
+                    map.is_in_fov((x, 0).into()),
+                    "{:?} {}",
+                    algorithm,
+                    x
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn visible_cells_match_a_full_scan() {
+        let maps = [
+            fixture(&PILLARS),
+            fixture(&["#######", "#..#..#", "#######"]),
+            fixture(&["....", ".##.", "....", "#..#"]),
+        ];
+        for &algorithm in &ALGORITHMS {
+            for map in &maps {
+                let mut map = map.clone();
+                assert_eq!(0, map.visible_cells().count());
+
+                for &(radius, light_walls) in &[(0, true), (3, false)] {
+                    map.compute_fov(
+                        (1, 1).into(),
+                        radius,
+                        FovShape::Circle,
+                        light_walls,
+                        algorithm,
+                    );
+                    let mut cells: Vec<_> = map.visible_cells().collect();
+                    cells.sort_by_key(|&(x, y)| (y, x));
+                    let scanned: Vec<_> = (0..map.height())
+                        .flat_map(|y| (0..map.width()).map(move |x| (x, y)))
+                        .filter(|&(x, y)| map.is_in_fov((x as u32, y as u32).into()))
+                        .collect();
+                    assert_eq!(scanned, cells);
+                }
+            }
+        }
+
+        let mut map = fixture(&PILLARS);
+        map.compute_fov(
+            (1, 1).into(),
+            0,
+            FovShape::Circle,
+            true,
+            FovAlgorithm::Shadow,
+        );
+        map.set_in_fov((1, 1).into(), false);
+        map.set_in_fov((10, 7).into(), true);
+        map.set_in_fov((10, 7).into(), true);
+        assert!(!map.visible_cells().any(|cell| cell == (1, 1)));
+        assert_eq!(
+            1,
+            map.visible_cells().filter(|&cell| cell == (10, 7)).count()
+        );
+        map.clear(true, true);
+        assert_eq!(0, map.visible_cells().count());
+    }
+
+    #[test]
+    fn symmetric_shadowcast_is_symmetric() {
+        let mut map = fixture(&PILLARS);
+        let floors: Vec<(u32, u32)> = (0..map.height() as u32)
+            .flat_map(|y| (0..map.width() as u32).map(move |x| (x, y)))
+            .filter(|&(x, y)| map.is_transparent((x, y).into()))
+            .collect();
+
+        let mut seen = Vec::with_capacity(floors.len());
+        for &from in &floors {
+            map.compute_fov(
+                from.into(),
+                0,
+                FovShape::Circle,
+                true,
+                FovAlgorithm::SymmetricShadowcast,
+            );
+            let visible: Vec<bool> = floors.iter().map(|&to| map.is_in_fov(to.into())).collect();
+            seen.push(visible);
+        }
+
+        for a in 0..floors.len() {
+            for b in 0..floors.len() {
+                assert_eq!(seen[a][b], seen[b][a], "{:?} {:?}", floors[a], floors[b]);
+            }
+        }
+    }
+
+    #[test]
+    fn higher_permissiveness_sees_more() {
+        let mut map = fixture(&PILLARS);
+        for &origin in &[(1, 1), (4, 3), (9, 6)] {
+            map.compute_fov(
+                origin.into(),
+                0,
+                FovShape::Circle,
+                true,
+                FovAlgorithm::Permissive(0),
+            );
+            assert!(map.is_in_fov(origin.into()));
+            let restrictive: Vec<_> = map.visible_cells().collect();
+
+            map.compute_fov(
+                origin.into(),
+                0,
+                FovShape::Circle,
+                true,
+                FovAlgorithm::Permissive(8),
+            );
+            assert!(map.is_in_fov(origin.into()));
+            assert!(restrictive
+                .iter()
+                .all(|&(x, y)| map.is_in_fov((x as u32, y as u32).into())));
+            assert!(map.visible_cells().count() > restrictive.len());
+
+            map.compute_fov(
+                origin.into(),
+                0,
+                FovShape::Circle,
+                true,
+                FovAlgorithm::Permissive(200),
+            );
+            assert!(map.visible_cells().count() > restrictive.len());
+        }
+    }
+
+    #[test]
+    fn postprocessing_darkens_walls_seen_around_corners() {
+        let rows = [
+            "..#.#.#", "#....#.", ".......", "....#..", "#......", "####...",
+        ];
+        let mut map = fixture(&rows);
+        map.postprocess_walls();
+        assert_eq!(0, map.visible_cells().count());
+
+        map.compute_fov(
+            (1, 1).into(),
+            0,
+            FovShape::Circle,
+            true,
+            FovAlgorithm::SymmetricShadowcast,
+        );
+        /* Only seen diagonally between the two walls below and to the left of it */
+        assert!(map.is_in_fov((6, 0).into()));
+        assert!(!map.is_in_fov((5, 0).into()) && !map.is_in_fov((6, 1).into()));
+        map.postprocess_walls();
+        assert!(!map.is_in_fov((6, 0).into()));
+        assert!(map.is_in_fov((2, 0).into()));
+        assert!(map.is_in_fov((1, 1).into()));
+
+        /* Walls stay out of view if they weren't meant to be lit */
+        map.compute_fov(
+            (1, 1).into(),
+            0,
+            FovShape::Circle,
+            false,
+            FovAlgorithm::SymmetricShadowcast,
+        );
+        let mut unlit: Vec<_> = map.visible_cells().collect();
+        map.postprocess_walls();
+        let mut cells: Vec<_> = map.visible_cells().collect();
+        unlit.sort_unstable();
+        cells.sort_unstable();
+        assert_eq!(unlit, cells);
+        assert!(!map.is_in_fov((2, 0).into()));
+    }
+
+    #[test]
+    fn line_of_sight() {
+        let map = fixture(&["......", "..#...", ".#....", "......", "######"]);
+        let los = |from: (u32, u32), to: (u32, u32), check_endpoints| {
+            map.is_in_line_of_sight(from.into(), to.into(), check_endpoints)
+        };
+
+        assert!(los((0, 0), (5, 0), true));
+        assert!(los((5, 3), (3, 0), true));
+        assert!(!los((0, 3), (5, 0), true));
+        assert!(!los((3, 0), (0, 3), false));
+
+        /* adjacent cells, and the cell itself, can always be seen unless the endpoints count */
+        assert!(los((2, 3), (2, 4), false));
+        assert!(!los((2, 3), (2, 4), true));
+        assert!(los((3, 3), (3, 3), true));
+        assert!(los((0, 0), (1, 1), true));
+
+        /* the line slips diagonally between the two walls touching at their corners */
+        assert!(los((1, 1), (2, 2), true));
+    }
+
+    #[test]
+    fn recomputing_matches_computing_from_scratch() {
+        let moves = [
+            (1, 1),
+            (2, 1),
+            (2, 2),
+            (4, 3),
+            (7, 4),
+            (10, 6),
+            (10, 7),
+            (4, 3),
+        ];
+        for &algorithm in &ALGORITHMS {
+            let mut map = fixture(&PILLARS);
+            for &(radius, light_walls) in &[(0, true), (4, false), (2, true)] {
+                for &origin in &moves {
+                    map.compute_fov(
+                        origin.into(),
+                        radius,
+                        FovShape::Circle,
+                        light_walls,
+                        algorithm,
+                    );
+                    let mut fresh = fixture(&PILLARS);
+                    fresh.compute_fov(
+                        origin.into(),
+                        radius,
+                        FovShape::Circle,
+                        light_walls,
+                        algorithm,
+                    );
+
+                    let mut cells: Vec<_> = map.visible_cells().collect();
+                    let mut expected: Vec<_> = fresh.visible_cells().collect();
+                    cells.sort_unstable();
+                    expected.sort_unstable();
+                    assert_eq!(expected, cells, "{:?} {:?}", algorithm, origin);
+                    for y in 0..map.height() as u32 {
+                        for x in 0..map.width() as u32 {
+                            assert_eq!(
+                                fresh.is_in_fov((x, y).into()),
+                                map.is_in_fov((x, y).into())
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,311 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * Copyright © 2008-2019, Jice and the libtcod contributors.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+/* Precise permissive field of view, originally by Jonathon Duerig, as adapted by libtcod */
+
+use crate::fov::{FovMap, FovShape};
+
+/* Cells are this many units wide, which lets the permissiveness move the end points of the view
+ * lines to within a cell */
+const STEP_SIZE: i64 = 16;
+
+/* Quadrant directions, as (dx, dy) */
+const DIRECTIONS: [(i64, i64); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+
+pub(super) fn compute(
+    map: &mut FovMap,
+    origin: (usize, usize),
+    max_radius: usize,
+    shape: FovShape,
+    light_walls: bool,
+    permissiveness: u8,
+) {
+    let radius = max_radius as i64;
+    let (x, y) = (origin.0 as i64, origin.1 as i64);
+    let (width, height) = (map.width as i64, map.height as i64);
+
+    let mut quadrant = Quadrant {
+        origin: (x, y),
+        direction: (0, 0),
+        radius,
+        shape,
+        light_walls,
+        offset: 8 - i64::from(permissiveness),
+        limit: 8 + i64::from(permissiveness),
+        views: Vec::new(),
+        bumps: Vec::new(),
+        current: 0,
+    };
+    for &direction in &DIRECTIONS {
+        let extent_x = if direction.0 > 0 { width - x - 1 } else { x };
+        let extent_y = if direction.1 > 0 { height - y - 1 } else { y };
+        quadrant.direction = direction;
+        quadrant.check(map, extent_x.min(radius), extent_y.min(radius));
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Line {
+    xi: i64,
+    yi: i64,
+    xf: i64,
+    yf: i64,
+}
+
+impl Line {
+    fn relative_slope(&self, x: i64, y: i64) -> i64 {
+        (self.yf - self.yi) * (self.xf - x) - (self.xf - self.xi) * (self.yf - y)
+    }
+
+    fn is_below(&self, x: i64, y: i64) -> bool {
+        self.relative_slope(x, y) > 0
+    }
+
+    fn is_below_or_colinear(&self, x: i64, y: i64) -> bool {
+        self.relative_slope(x, y) >= 0
+    }
+
+    fn is_above(&self, x: i64, y: i64) -> bool {
+        self.relative_slope(x, y) < 0
+    }
+
+    fn is_above_or_colinear(&self, x: i64, y: i64) -> bool {
+        self.relative_slope(x, y) <= 0
+    }
+
+    fn is_colinear(&self, x: i64, y: i64) -> bool {
+        self.relative_slope(x, y) == 0
+    }
+
+    fn is_line_colinear(&self, other: &Self) -> bool {
+        self.is_colinear(other.xi, other.yi) && self.is_colinear(other.xf, other.yf)
+    }
+}
+
+/* A corner the view has been narrowed around; each bump remembers the earlier one on the same side */
+#[derive(Copy, Clone)]
+struct Bump {
+    x: i64,
+    y: i64,
+    parent: Option<usize>,
+}
+
+#[derive(Copy, Clone)]
+struct View {
+    shallow_line: Line,
+    steep_line: Line,
+    shallow_bump: Option<usize>,
+    steep_bump: Option<usize>,
+}
+
+struct Quadrant {
+    origin: (i64, i64),
+    direction: (i64, i64),
+    radius: i64,
+    shape: FovShape,
+    light_walls: bool,
+    offset: i64,
+    limit: i64,
+    /* the views that are still open, ordered from steep to shallow */
+    views: Vec<View>,
+    bumps: Vec<Bump>,
+    current: usize,
+}
+
+impl Quadrant {
+    fn check(&mut self, map: &mut FovMap, extent_x: i64, extent_y: i64) {
+        self.bumps.clear();
+        self.views.clear();
+        /* Unlike in libtcod, the lines always have some length, so that a quadrant running
+         * along the edge of the map isn't closed from the start */
+        self.views.push(View {
+            shallow_line: Line {
+                xi: self.offset,
+                yi: self.limit,
+                xf: extent_x.max(1) * STEP_SIZE,
+                yf: 0,
+            },
+            steep_line: Line {
+                xi: self.limit,
+                yi: self.offset,
+                xf: 0,
+                yf: extent_y.max(1) * STEP_SIZE,
+            },
+            shallow_bump: None,
+            steep_bump: None,
+        });
+
+        /* Visits the cells one diagonal at a time, moving away from the origin */
+        for i in 1..=extent_x + extent_y {
+            if self.views.is_empty() {
+                break;
+            }
+
+            self.current = 0;
+            for j in (i - extent_x).max(0)..=i.min(extent_y) {
+                if self.current == self.views.len() {
+                    break;
+                }
+                self.visit(map, (i - j) * STEP_SIZE, j * STEP_SIZE);
+            }
+        }
+    }
+
+    fn visit(&mut self, map: &mut FovMap, x: i64, y: i64) {
+        let (top_left_x, top_left_y) = (x, y + STEP_SIZE);
+        let (bottom_right_x, bottom_right_y) = (x + STEP_SIZE, y);
+
+        while self.current < self.views.len()
+            && self.views[self.current]
+                .steep_line
+                .is_below_or_colinear(bottom_right_x, bottom_right_y)
+        {
+            self.current += 1;
+        }
+        if self.current == self.views.len()
+            || self.views[self.current]
+                .shallow_line
+                .is_above_or_colinear(top_left_x, top_left_y)
+        {
+            /* The cell isn't in any of the views */
+            return;
+        }
+
+        if !self.is_blocked(map, x, y) {
+            return;
+        }
+
+        let view = self.views[self.current];
+        let shallow_bump = view.shallow_line.is_above(bottom_right_x, bottom_right_y);
+        let steep_bump = view.steep_line.is_below(top_left_x, top_left_y);
+        if shallow_bump && steep_bump {
+            /* The cell blocks the whole view */
+            self.views.remove(self.current);
+        } else if shallow_bump {
+            self.add_shallow_bump(top_left_x, top_left_y, self.current);
+            self.check_view(self.current);
+        } else if steep_bump {
+            self.add_steep_bump(bottom_right_x, bottom_right_y, self.current);
+            self.check_view(self.current);
+        } else {
+            /* The cell splits the view in two; the shallower one goes first */
+            let shallower = self.current;
+            self.views.insert(shallower, view);
+            self.add_steep_bump(bottom_right_x, bottom_right_y, shallower);
+            let steeper = if self.check_view(shallower) {
+                shallower + 1
+            } else {
+                shallower
+            };
+            self.add_shallow_bump(top_left_x, top_left_y, steeper);
+            self.check_view(steeper);
+            self.current = self.current.min(self.views.len());
+        }
+    }
+
+    /* Marks the cell as seen, unless it's a wall and walls aren't lit, and returns whether it
+     * blocks the view */
+    fn is_blocked(&self, map: &mut FovMap, x: i64, y: i64) -> bool {
+        let (dx, dy) = (x / STEP_SIZE, y / STEP_SIZE);
+        let index = (self.origin.0 + dx * self.direction.0) as usize
+            + (self.origin.1 + dy * self.direction.1) as usize * map.width;
+        let blocked = !map.cells[index].transparent;
+        if (!blocked || self.light_walls) && self.shape.contains(dx, dy, self.radius) {
+            map.mark_in_fov(index);
+        }
+        blocked
+    }
+
+    fn add_shallow_bump(&mut self, x: i64, y: i64, view: usize) {
+        let view = &mut self.views[view];
+        view.shallow_line.xf = x;
+        view.shallow_line.yf = y;
+        self.bumps.push(Bump {
+            x,
+            y,
+            parent: view.shallow_bump,
+        });
+        view.shallow_bump = Some(self.bumps.len() - 1);
+
+        let mut bump = view.steep_bump;
+        while let Some(index) = bump {
+            let steep = self.bumps[index];
+            if view.shallow_line.is_above(steep.x, steep.y) {
+                view.shallow_line.xi = steep.x;
+                view.shallow_line.yi = steep.y;
+            }
+            bump = steep.parent;
+        }
+    }
+
+    fn add_steep_bump(&mut self, x: i64, y: i64, view: usize) {
+        let view = &mut self.views[view];
+        view.steep_line.xf = x;
+        view.steep_line.yf = y;
+        self.bumps.push(Bump {
+            x,
+            y,
+            parent: view.steep_bump,
+        });
+        view.steep_bump = Some(self.bumps.len() - 1);
+
+        let mut bump = view.shallow_bump;
+        while let Some(index) = bump {
+            let shallow = self.bumps[index];
+            if view.steep_line.is_below(shallow.x, shallow.y) {
+                view.steep_line.xi = shallow.x;
+                view.steep_line.yi = shallow.y;
+            }
+            bump = shallow.parent;
+        }
+    }
+
+    /* Removes the view if it has been narrowed down to nothing, i.e. if its lines are colinear
+     * and pass through either extremity of the origin; returns whether it was kept */
+    fn check_view(&mut self, view: usize) -> bool {
+        let View {
+            shallow_line,
+            steep_line,
+            ..
+        } = self.views[view];
+        if shallow_line.is_line_colinear(&steep_line)
+            && (shallow_line.is_colinear(self.offset, self.limit)
+                || shallow_line.is_colinear(self.limit, self.offset))
+        {
+            self.views.remove(view);
+            false
+        } else {
+            true
+        }
+    }
+}
@@ -0,0 +1,130 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * Copyright © 2008-2019, Jice and the libtcod contributors.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::fov::{FovMap, FovShape};
+
+/* Transforms from octant-relative coordinates to map coordinates, as [xx, xy, yx, yy] */
+const TRANSFORMS: [[i64; 4]; 8] = [
+    [1, 0, 0, 1],
+    [0, 1, 1, 0],
+    [0, -1, 1, 0],
+    [-1, 0, 0, 1],
+    [-1, 0, 0, -1],
+    [0, -1, -1, 0],
+    [0, 1, -1, 0],
+    [1, 0, 0, -1],
+];
+
+pub(super) fn compute(
+    map: &mut FovMap,
+    origin: (usize, usize),
+    max_radius: usize,
+    shape: FovShape,
+    light_walls: bool,
+) {
+    let octant = Octant {
+        origin: (origin.0 as i64, origin.1 as i64),
+        radius: max_radius as i64,
+        shape,
+        light_walls,
+    };
+    for &transform in &TRANSFORMS {
+        octant.cast_light(map, 1, 1.0, 0.0, transform);
+    }
+}
+
+struct Octant {
+    origin: (i64, i64),
+    radius: i64,
+    shape: FovShape,
+    light_walls: bool,
+}
+
+impl Octant {
+    fn cast_light(
+        &self,
+        map: &mut FovMap,
+        row: i64,
+        mut start: f32,
+        end: f32,
+        [xx, xy, yx, yy]: [i64; 4],
+    ) {
+        if start < end {
+            return;
+        }
+
+        let mut new_start = 0.0;
+        for j in row..=self.radius {
+            let dy = -j;
+            let mut blocked = false;
+            for dx in -j..=0 {
+                let x = self.origin.0 + dx * xx + dy * xy;
+                let y = self.origin.1 + dx * yx + dy * yy;
+                let index = match map.index(x, y) {
+                    Some(index) => index,
+                    None => continue,
+                };
+
+                let l_slope = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+                let r_slope = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+                if start < r_slope {
+                    continue;
+                }
+                if end > l_slope {
+                    break;
+                }
+
+                let transparent = map.cells[index].transparent;
+                if self.shape.contains(dx, dy, self.radius) && (self.light_walls || transparent) {
+                    map.mark_in_fov(index);
+                }
+
+                if blocked {
+                    if transparent {
+                        blocked = false;
+                        start = new_start;
+                    } else {
+                        new_start = r_slope;
+                    }
+                } else if !transparent && j < self.radius {
+                    blocked = true;
+                    self.cast_light(map, j + 1, start, l_slope, [xx, xy, yx, yy]);
+                    new_start = r_slope;
+                }
+            }
+            if blocked {
+                break;
+            }
+        }
+    }
+}
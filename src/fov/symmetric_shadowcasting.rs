@@ -0,0 +1,187 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Albert Ford's symmetric shadowcasting, as described at
+//! <https://www.albertford.com/shadowcasting/>.
+
+use crate::fov::{FovMap, FovShape};
+
+/* Slopes are kept as exact fractions; the denominator is always positive */
+#[derive(Debug, Copy, Clone)]
+struct Slope {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Slope {
+    const fn new(numerator: i64, denominator: i64) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+
+    /* The slope from the origin to the lower edge of the tile at (depth, column) */
+    const fn of_tile(depth: i64, column: i64) -> Self {
+        Self::new(2 * column - 1, 2 * depth)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Row {
+    depth: i64,
+    start: Slope,
+    end: Slope,
+}
+
+impl Row {
+    /* floor(depth * start + 1/2) */
+    fn min_column(&self) -> i64 {
+        let Slope {
+            numerator,
+            denominator,
+        } = self.start;
+        (2 * self.depth * numerator + denominator).div_euclid(2 * denominator)
+    }
+
+    /* ceil(depth * end - 1/2) */
+    fn max_column(&self) -> i64 {
+        let Slope {
+            numerator,
+            denominator,
+        } = self.end;
+        -(denominator - 2 * self.depth * numerator).div_euclid(2 * denominator)
+    }
+
+    /* Whether the center of the tile lies within the row's slopes */
+    fn is_symmetric(&self, column: i64) -> bool {
+        column * self.start.denominator >= self.depth * self.start.numerator
+            && column * self.end.denominator <= self.depth * self.end.numerator
+    }
+
+    fn next(&self) -> Self {
+        Self {
+            depth: self.depth + 1,
+            ..*self
+        }
+    }
+}
+
+struct Quadrant {
+    origin: (i64, i64),
+    direction: usize,
+    radius: i64,
+    shape: FovShape,
+    light_walls: bool,
+}
+
+impl Quadrant {
+    fn transform(&self, depth: i64, column: i64) -> (i64, i64) {
+        let (x, y) = self.origin;
+        match self.direction {
+            0 => (x + column, y - depth),
+            1 => (x + column, y + depth),
+            2 => (x + depth, y + column),
+            _ => (x - depth, y + column),
+        }
+    }
+
+    /* Cells outside the map are treated as walls that can't be revealed */
+    fn is_wall(&self, map: &FovMap, depth: i64, column: i64) -> bool {
+        let (x, y) = self.transform(depth, column);
+        !map.index(x, y)
+            .is_some_and(|index| map.cells[index].transparent)
+    }
+
+    fn reveal(&self, map: &mut FovMap, depth: i64, column: i64) {
+        if !self.shape.contains(column, depth, self.radius) {
+            return;
+        }
+        let (x, y) = self.transform(depth, column);
+        if let Some(index) = map.index(x, y) {
+            map.mark_in_fov(index);
+        }
+    }
+
+    fn scan(&self, map: &mut FovMap, mut row: Row) {
+        if row.depth > self.radius {
+            return;
+        }
+
+        let mut previous_is_wall = None;
+        for column in row.min_column()..=row.max_column() {
+            let is_wall = self.is_wall(map, row.depth, column);
+            if (is_wall && self.light_walls) || (!is_wall && row.is_symmetric(column)) {
+                self.reveal(map, row.depth, column);
+            }
+            match previous_is_wall {
+                Some(true) if !is_wall => row.start = Slope::of_tile(row.depth, column),
+                Some(false) if is_wall => {
+                    let mut next_row = row.next();
+                    next_row.end = Slope::of_tile(row.depth, column);
+                    self.scan(map, next_row);
+                }
+                _ => {}
+            }
+            previous_is_wall = Some(is_wall);
+        }
+        if previous_is_wall == Some(false) {
+            self.scan(map, row.next());
+        }
+    }
+}
+
+pub(super) fn compute(
+    map: &mut FovMap,
+    origin: (usize, usize),
+    max_radius: usize,
+    shape: FovShape,
+    light_walls: bool,
+) {
+    for direction in 0..4 {
+        let quadrant = Quadrant {
+            origin: (origin.0 as i64, origin.1 as i64),
+            direction,
+            radius: max_radius as i64,
+            shape,
+            light_walls,
+        };
+        quadrant.scan(
+            map,
+            Row {
+                depth: 1,
+                start: Slope::new(-1, 1),
+                end: Slope::new(1, 1),
+            },
+        );
+    }
+}
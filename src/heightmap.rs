@@ -44,6 +44,9 @@ use ilyvion_util::non_nan::NonNan;
 use impl_ops::*;
 use std::ops::{self, AddAssign, MulAssign};
 
+/* The number of points sampled along the curve by `dig_bezier`, like libtcod */
+const BEZIER_STEPS: u32 = 1000;
+
 /// A struct representing a height map.
 #[derive(Clone, Debug)]
 #[cfg_attr(
@@ -125,33 +128,92 @@ impl HeightMap {
     ///
     /// If the position is outside the range of the height map.
     pub fn set_value(&mut self, position: UPosition, value: f32) {
-        self.values[position.x as usize + position.y as usize * self.width] = value;
+        *self.get_value_mut(position.x as usize, position.y as usize) = value;
+    }
+
+    /// Returns the value of the height map at the given position, or `None` if the position is
+    /// outside the range of the height map.
+    pub fn try_value(&self, position: Position) -> Option<f32> {
+        self.checked_index(position).map(|index| self.values[index])
     }
 
-    /// Interpolates the value of the height map at the given position.
+    /// Sets the value of the height map at the given position.
     ///
-    /// # Panics
+    /// # Errors
+    /// Returns an `OutOfBoundsError` if the position is outside the range of the height map, in
+    /// which case nothing is changed.
+    pub fn try_set_value(
+        &mut self,
+        position: Position,
+        value: f32,
+    ) -> Result<(), OutOfBoundsError> {
+        let index = self.checked_index(position).ok_or(OutOfBoundsError {
+            position,
+            width: self.width,
+            height: self.height,
+        })?;
+        self.values[index] = value;
+        Ok(())
+    }
+
+    fn checked_index(&self, position: Position) -> Option<usize> {
+        let (x, y) = (position.x, position.y);
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            Some(x as usize + y as usize * self.width)
+        } else {
+            None
+        }
+    }
+
+    /// Interpolates the value of the height map at the given position, using bilinear
+    /// interpolation between the four surrounding cells.
     ///
-    /// If the position is outside the range of the height map.
+    /// Positions outside the height map are clamped to its edges.
     pub fn interpolated_value(&self, position: FPosition) -> f32 {
-        let i_position = position.trunc_u();
-        if i_position.x as usize >= self.width - 1 || i_position.y as usize >= self.height - 1 {
-            self.value(i_position)
-        } else {
-            let dx = position.x - i_position.x as f32;
-            let dy = position.y - i_position.y as f32;
-            let c1 = self.value(i_position);
-            let c2 = self.value(i_position + (1, 0));
-            let c3 = self.value(i_position + (0, 1));
-            let c4 = self.value(i_position + (1, 1));
-            let top = (1.0 - dx) * c1 + dx * c2;
-            let bottom = (1.0 - dx) * c3 + dx * c4;
+        let x = position.x.clamp(0.0, (self.width - 1) as f32);
+        let y = position.y.clamp(0.0, (self.height - 1) as f32);
+        /* Interpolate towards the last column or row rather than past it */
+        let ix = (x as usize).min(self.width.saturating_sub(2));
+        let iy = (y as usize).min(self.height.saturating_sub(2));
+        let ix1 = (ix + 1).min(self.width - 1);
+        let iy1 = (iy + 1).min(self.height - 1);
+
+        let dx = x - ix as f32;
+        let dy = y - iy as f32;
+        let c1 = self.get_value(ix, iy);
+        let c2 = self.get_value(ix1, iy);
+        let c3 = self.get_value(ix, iy1);
+        let c4 = self.get_value(ix1, iy1);
+        let top = (1.0 - dx) * c1 + dx * c2;
+        let bottom = (1.0 - dx) * c3 + dx * c4;
+
+        (1.0 - dy) * top + dy * bottom
+    }
 
-            (1.0 - dy) * top + dy * bottom
+    /// Interpolates the value of the height map at the given position like
+    /// [`interpolated_value`], and returns it relative to the map's lowest and highest values,
+    /// i.e. between `0.0` and `1.0`.
+    ///
+    /// This looks through the entire height map for its lowest and highest values; if you need
+    /// many such values, [`normalize_unit`] a copy of the height map instead.
+    ///
+    /// If all the values in the height map are the same, `0.0` is returned.
+    ///
+    /// [`interpolated_value`]: #method.interpolated_value
+    /// [`normalize_unit`]: #method.normalize_unit
+    pub fn normalized_value(&self, position: FPosition) -> f32 {
+        let MinMax { min, max } = self.min_max();
+        if max - min == 0.0 {
+            0.0
+        } else {
+            (self.interpolated_value(position) - min) / (max - min)
         }
     }
 
-    /// Calculates the slope at the given position.
+    /// Calculates the slope at the given position, as the angle between `0` and `π/2` of the
+    /// steepest climb or descent to any of the cell's neighbors, in radians. Height differences
+    /// to diagonal neighbors are divided by their distance of `√2`. Only neighbors inside the
+    /// height map are considered.
     ///
     /// # Panics
     ///
@@ -160,26 +222,26 @@ impl HeightMap {
         const DIX: [i32; 8] = [-1, 0, 1, -1, 1, -1, 0, 1];
         const DIY: [i32; 8] = [-1, -1, -1, 0, 0, 1, 1, 1];
 
-        let mut min_dy = 0.0;
-        let mut max_dy = 0.0;
+        let mut steepest = 0.0_f32;
         let v = self.value(position);
         for (nx, ny) in Iterator::zip(DIX.iter(), DIY.iter())
             .map(|(&dx, &dy)| (position.x as i32 + dx, position.y as i32 + dy))
         {
-            if nx >= 0 && nx < self.width as i32 && ny >= 0 && ny <= self.height as i32 {
-                let n_slope = self.get_value(nx as usize, ny as usize) - v;
-                if n_slope > max_dy {
-                    max_dy = n_slope;
-                } else if n_slope < min_dy {
-                    min_dy = n_slope;
+            if nx >= 0 && nx < self.width as i32 && ny >= 0 && ny < self.height as i32 {
+                let mut n_slope = (self.get_value(nx as usize, ny as usize) - v).abs();
+                if nx != position.x as i32 && ny != position.y as i32 {
+                    n_slope /= std::f32::consts::SQRT_2;
                 }
+                steepest = steepest.max(n_slope);
             }
         }
 
-        (max_dy + min_dy).atan2(1.0)
+        steepest.atan2(1.0)
     }
 
-    /// Calculates the normal at the given position.
+    /// Calculates the unit normal of the surface at the given position, looking at the cells
+    /// to the right and below it. Heights below the `water_level` count as being at the water
+    /// level. On the last column and row, the normal points straight up.
     ///
     /// # Panics
     ///
@@ -219,7 +281,8 @@ impl HeightMap {
         n
     }
 
-    /// Returns the number of cells that have a height between `min` and `max`, inclusive.
+    /// Returns the number of cells that have a height between `min` and `max`, inclusive, like
+    /// libtcod's `TCOD_heightmap_count_cells`.
     pub fn count_cells(&self, min: f32, max: f32) -> usize {
         self.values
             .iter()
@@ -227,8 +290,8 @@ impl HeightMap {
             .count()
     }
 
-    /// Returns whether there is any land along the edge of the height map. A result of `false`
-    /// implies that the map is an island.
+    /// Returns whether there is any land, that is, any height above `water_level`, along the edge
+    /// of the height map. A result of `false` implies that the map is an island.
     pub fn has_land_on_border(&self, water_level: f32) -> bool {
         for x in 0..self.width {
             if self.get_value(x, 0) > water_level
@@ -251,7 +314,7 @@ impl HeightMap {
     pub fn min_max(&self) -> MinMax {
         self.values
             .iter()
-            .fold((std::f32::MAX, std::f32::MIN), |(min, max), &v| {
+            .fold((f32::MAX, f32::MIN), |(min, max), &v| {
                 (min.min(v), max.max(v))
             })
             .into()
@@ -261,22 +324,22 @@ impl HeightMap {
     ///
     /// # Panics
     ///
-    /// If `max` > `min`.
+    /// If `min` > `max`.
     pub fn clamp(&mut self, min: f32, max: f32) {
         assert!(min <= max);
 
-        self.values
-            .iter_mut()
-            .for_each(|v| *v = v.max(min).min(max))
+        self.values.iter_mut().for_each(|v| *v = v.clamp(min, max))
     }
 
     /// Normalizes the values in the height map by scaling them proportionally such that the map's
     /// current smallest value will be set to `min`, and its largest value will be set to `max`, and
     /// all values in-between will be the same as they were, relative to these new end points.
     ///
+    /// If all the values in the height map are the same, they are all set to `min`.
+    ///
     /// # Panics
     ///
-    /// If `max` > `min`.
+    /// If `min` > `max`.
     ///
     /// # Examples
     /// ```
@@ -296,30 +359,104 @@ impl HeightMap {
             max: cur_max,
         } = self.min_max();
 
-        let inv_max = if cur_max - cur_min == 0.0 {
-            0.0
-        } else {
-            (f64::from(max) - f64::from(min)) / (f64::from(cur_max) - f64::from(cur_min))
-        };
+        let cur_range = f64::from(cur_max) - f64::from(cur_min);
+        let range = f64::from(max) - f64::from(min);
 
         // normalize
         self.values.iter_mut().for_each(|v| {
-            *v = (f64::from(min) + (f64::from(*v) - f64::from(cur_min)) * inv_max) as f32
+            *v = if cur_range == 0.0 {
+                min
+            } else {
+                (f64::from(min) + (f64::from(*v) - f64::from(cur_min)) * range / cur_range) as f32
+            }
         });
     }
 
+    /// Normalizes the values in the height map to be between `0.0` and `1.0`; see [`normalize`].
+    ///
+    /// [`normalize`]: #method.normalize
+    pub fn normalize_unit(&mut self) {
+        self.normalize(0.0, 1.0);
+    }
+
     /// Resets all the values in the height map to `0.0`.
     pub fn clear(&mut self) {
+        self.set_all(0.0);
+    }
+
+    /// Sets all the values in the height map to `value`.
+    pub fn set_all(&mut self, value: f32) {
         for v in &mut self.values {
-            *v = 0.0;
+            *v = value;
         }
     }
 
+    /// Adds `value` to all the values in the height map, like libtcod's `TCOD_heightmap_add`.
+    /// This is the same as `hm += value`.
+    pub fn add_scalar(&mut self, value: f32) {
+        *self += value;
+    }
+
+    /// Multiplies all the values in the height map by `value`, like libtcod's
+    /// `TCOD_heightmap_scale`. This is the same as `hm *= value`.
+    pub fn multiply_scalar(&mut self, value: f32) {
+        *self *= value;
+    }
+
+    /// Returns the values of the height map as an 8-bit grayscale image, one byte per cell, row
+    /// by row. The values are normalized like [`normalize`] does, so the lowest value becomes
+    /// `0` and the highest becomes `255`; if all the values are the same, every byte is `0`.
+    ///
+    /// The height map itself is left unchanged.
+    ///
+    /// [`normalize`]: #method.normalize
+    ///
+    /// # Examples
+    /// ```
+    /// # use doryen_extra::heightmap::HeightMap;
+    /// let hm = HeightMap::new_with_values(3, 1, &[-1.0, 0.0, 1.0]);
+    /// assert_eq!(hm.to_luma8(), [0, 128, 255]);
+    /// ```
+    pub fn to_luma8(&self) -> Vec<u8> {
+        let MinMax { min, max } = self.min_max();
+        let range = f64::from(max) - f64::from(min);
+
+        self.values
+            .iter()
+            .map(|&v| {
+                if range == 0.0 {
+                    0
+                } else {
+                    ((f64::from(v) - f64::from(min)) * 255.0 / range).round() as u8
+                }
+            })
+            .collect()
+    }
+
+    /// Saves the height map as a grayscale PNG image at the given `path`, with the values
+    /// normalized as by [`to_luma8`].
+    ///
+    /// [`to_luma8`]: #method.to_luma8
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be created or written to.
+    #[cfg(feature = "image")]
+    pub fn save_png<P: AsRef<std::path::Path>>(&self, path: P) -> image::ImageResult<()> {
+        image::save_buffer_with_format(
+            path,
+            &self.to_luma8(),
+            self.width as u32,
+            self.height as u32,
+            image::ColorType::L8,
+            image::ImageFormat::Png,
+        )
+    }
+
     /// Linearly interpolate two height maps together.
     pub fn lerp(&self, other: &Self, coefficient: f32) -> Self {
-        assert_eq!(self.width, other.width);
-        assert_eq!(self.height, other.height);
-        assert!(coefficient >= 0.0 && coefficient <= 1.0);
+        self.assert_same_size(other);
+        assert!((0.0..=1.0).contains(&coefficient));
 
         let mut result = Self::new(self.width, self.height);
         for (v, (&sv, &ov)) in result
@@ -333,8 +470,96 @@ impl HeightMap {
         result
     }
 
-    /// Adds a hill (a half spheroid) at the given position, with a `radius` and a `height`.
-    /// If `height == radius` or `-radius`, the hill will be a half-sphere.
+    /// Adds the values of `other` to the values of this height map, cell by cell, like libtcod's
+    /// `TCOD_heightmap_add_hm`.
+    ///
+    /// # Panics
+    ///
+    /// If the height maps aren't the same size.
+    pub fn add_map(&mut self, other: &Self) {
+        self.assert_same_size(other);
+
+        for (v, &o) in self.values.iter_mut().zip(other.values.iter()) {
+            *v += o;
+        }
+    }
+
+    /// Multiplies the values of this height map by the values of `other`, cell by cell, like
+    /// libtcod's `TCOD_heightmap_multiply_hm`.
+    ///
+    /// # Panics
+    ///
+    /// If the height maps aren't the same size.
+    pub fn multiply_map(&mut self, other: &Self) {
+        self.assert_same_size(other);
+
+        for (v, &o) in self.values.iter_mut().zip(other.values.iter()) {
+            *v *= o;
+        }
+    }
+
+    /// Linearly interpolates the values of this height map towards the values of `other`, cell
+    /// by cell, like libtcod's `TCOD_heightmap_lerp_hm`. The `coefficient` is clamped to the
+    /// range \[0, 1\]; 0 keeps this height map as is, and 1 gives a copy of `other`.
+    ///
+    /// # Panics
+    ///
+    /// If the height maps aren't the same size.
+    pub fn lerp_map(&mut self, other: &Self, coefficient: f32) {
+        self.assert_same_size(other);
+        let coefficient = coefficient.clamp(0.0, 1.0);
+
+        for (v, &o) in self.values.iter_mut().zip(other.values.iter()) {
+            *v += (o - *v) * coefficient;
+        }
+    }
+
+    fn assert_same_size(&self, other: &Self) {
+        assert!(
+            self.width == other.width && self.height == other.height,
+            "height maps must be the same size, but one is {}x{} and the other is {}x{}",
+            self.width,
+            self.height,
+            other.width,
+            other.height
+        );
+    }
+
+    /// Multiplies every value by a factor that falls off from `1.0` at the center of the map to
+    /// `0.0` at its corners, which turns noisy terrain into an island surrounded by water.
+    ///
+    /// The distance from the center is normalized separately along each axis, so that the edges
+    /// of non-square maps are as far from the center as those of square ones. The factor is
+    /// `1 - d^falloff`, where `d` is that distance divided by the distance to the corners;
+    /// higher values of `falloff` keep more of the land intact, with steeper coasts.
+    ///
+    /// # Panics
+    /// If `falloff` isn't positive.
+    pub fn apply_island_mask(&mut self, falloff: f32) {
+        assert!(falloff > 0.0, "The falloff must be positive.");
+
+        /* the distance along an axis, between 0.0 at the center and 1.0 at the edges */
+        let normalized = |i: usize, size: usize| {
+            let center = (size - 1) as f32 / 2.0;
+            if center > 0.0 {
+                (i as f32 - center) / center
+            } else {
+                0.0
+            }
+        };
+
+        let (width, height) = (self.width, self.height);
+        for (i, v) in self.values.iter_mut().enumerate() {
+            let dx = normalized(i % width, width);
+            let dy = normalized(i / width, height);
+            let distance = ((dx * dx + dy * dy) / 2.0).sqrt();
+            *v *= 1.0 - distance.powf(falloff);
+        }
+    }
+
+    /// Adds a hill (a paraboloid) at the given position, with a `radius` and a `height`. Cells
+    /// at least `radius` away from `position` are left unchanged, so hills that are entirely
+    /// outside the map, or that have a radius of zero, don't do anything.
     pub fn add_hill(&mut self, position: FPosition, radius: f32, height: f32) {
         let radius2 = radius * radius;
         let coefficient = height / radius2;
@@ -358,6 +583,9 @@ impl HeightMap {
     /// Takes the highest value (if `height > 0`) or the lowest (if `height < 0`) between the map
     /// and the hill. Its main goal is to carve things into maps (like rivers) by digging hills
     /// along a curve.
+    ///
+    /// Like with [`add_hill`](#method.add_hill), only cells less than `radius` away from
+    /// `position` are affected.
     pub fn dig_hill(&mut self, position: FPosition, radius: f32, height: f32) {
         let radius2 = radius * radius;
         let coefficient = height / radius2;
@@ -378,7 +606,7 @@ impl HeightMap {
                         if *value < z {
                             *value = z;
                         }
-                    } else if *value > 0.0 {
+                    } else if *value > z {
                         *value = z;
                     }
                 }
@@ -389,6 +617,11 @@ impl HeightMap {
     /// Carves a path along a cubic Bezier curve using the `dig_hill` method. Could be used for
     /// generating roads, rivers, etc. Both radius and depth can vary linearly along the path. The
     /// four `positions` are the 4 Bezier control points.
+    ///
+    /// Each time the curve enters a new cell, a hill is dug there with the radius and depth
+    /// interpolated at that point of the curve, so cells on the curve end up at that depth (or
+    /// stay where they are, if they were already lower). Parts of the curve that leave the map
+    /// are clipped, and a curve whose control points are all the same does nothing.
     pub fn dig_bezier(
         &mut self,
         positions: [UPosition; 4],
@@ -397,31 +630,35 @@ impl HeightMap {
         end_radius: f32,
         end_depth: f32,
     ) {
-        let mut x_from = positions[0].x as usize;
-        let mut y_from = positions[0].y as usize;
+        if positions.iter().all(|&p| p == positions[0]) {
+            return;
+        }
 
-        let mut t = 0.0_f32;
-        while t <= 1.0 {
+        let mut from = None;
+        for step in 0..=BEZIER_STEPS {
+            let t = step as f32 / BEZIER_STEPS as f32;
             let it = 1.0 - t;
 
+            /* Rounded, so that the curve doesn't jitter between cells as the float error in the
+            Bernstein weights changes sign */
             let x_to = (positions[0].x as f32 * it * it * it
                 + 3.0 * positions[1].x as f32 * t * it * it
                 + 3.0 * positions[2].x as f32 * t * t * it
-                + positions[3].x as f32 * t * t * t) as usize;
+                + positions[3].x as f32 * t * t * t)
+                .round();
             let y_to = (positions[0].y as f32 * it * it * it
                 + 3.0 * positions[1].y as f32 * t * it * it
                 + 3.0 * positions[2].y as f32 * t * t * it
-                + positions[3].y as f32 * t * t * t) as usize;
+                + positions[3].y as f32 * t * t * t)
+                .round();
 
-            if x_to != x_from || y_to != y_from {
+            let to = FPosition::new(x_to, y_to);
+            if from != Some(to) {
                 let radius = start_radius + (end_radius - start_radius) * t;
                 let depth = start_depth + (end_depth - start_depth) * t;
-                self.dig_hill((x_to as f32, y_to as f32).into(), radius, depth);
-                x_from = x_to;
-                y_from = y_to;
+                self.dig_hill(to, radius, depth);
+                from = Some(to);
             }
-
-            t += 0.001;
         }
     }
 
@@ -484,9 +721,70 @@ impl HeightMap {
         }
     }
 
+    /// Generates a cave using a cellular automaton, replacing the values of the height map with
+    /// `1.0` for walls and `0.0` for floors.
+    ///
+    /// Each cell is first made a wall with the probability `fill_probability`, and then the map is
+    /// smoothed `steps` times. In each step, a floor becomes a wall if at least `birth_limit` of
+    /// its eight neighbors are walls, and a wall stays a wall if at least `death_limit` of them
+    /// are. The classic 4-5 rule has a `birth_limit` of 5 and a `death_limit` of 4. The cells on
+    /// the border of the map are always walls, so maps smaller than 3x3 are all walls.
+    ///
+    /// Since walls are `1.0` and floors `0.0`, cells with a value of at least `0.5` are walls,
+    /// e.g. for [`count_cells`](#method.count_cells).
+    pub fn cellular_automaton_cave<A: RandomAlgorithm>(
+        &mut self,
+        fill_probability: f32,
+        steps: usize,
+        birth_limit: u32,
+        death_limit: u32,
+        random: &mut Random<A>,
+    ) {
+        const WALL: f32 = 1.0;
+        const FLOOR: f32 = 0.0;
+
+        let (width, height) = (self.width, self.height);
+        for (i, value) in self.values.iter_mut().enumerate() {
+            let (x, y) = (i % width, i / width);
+            let border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+            let wall = border || random.get_bool(f64::from(fill_probability));
+            *value = if wall { WALL } else { FLOOR };
+        }
+
+        /* The border never changes, so only the inner cells need updating, and all of their
+        neighbors are inside the map; maps smaller than 3x3 have none */
+        if width < 3 || height < 3 {
+            return;
+        }
+        let mut next = self.values.clone();
+        for _ in 0..steps {
+            for y in 1..height - 1 {
+                for x in 1..width - 1 {
+                    let walls = (y - 1..=y + 1)
+                        .flat_map(|ny| (x - 1..=x + 1).map(move |nx| (nx, ny)))
+                        .filter(|&(nx, ny)| (nx, ny) != (x, y) && self.get_value(nx, ny) >= 0.5)
+                        .count() as u32;
+                    let wall = if self.get_value(x, y) >= 0.5 {
+                        walls >= death_limit
+                    } else {
+                        walls >= birth_limit
+                    };
+                    next[x + y * width] = if wall { WALL } else { FLOOR };
+                }
+            }
+            std::mem::swap(&mut self.values, &mut next);
+        }
+    }
+
     /// Apply a generic transformation on the height map, so that each resulting cell value is the
     /// weighted sum of several neighbour cells. This can be used to, e.g. smooth/sharpen the map.
     ///
+    /// Only cells whose value is between `min_level` and `max_level`, inclusive, are transformed.
+    /// Neighbor cells outside the height map are skipped, and the sum is divided by the total
+    /// weight of the neighbors that were used. Cells are transformed in place, one column at a
+    /// time, like libtcod does; cells without any neighbors inside the map, or whose neighbors'
+    /// weights sum to zero, are left unchanged.
+    ///
     /// # Examples
     /// Do simple horizontal smoothing with direct neighbor cells.
     /// ```
@@ -518,7 +816,10 @@ impl HeightMap {
                             total_weight += f64::from(cell.weight);
                         }
                     }
-                    self.values[offset] = (val / total_weight) as f32;
+                    /* Leave the cell alone if none of its neighbors contributed */
+                    if total_weight != 0.0 {
+                        self.values[offset] = (val / total_weight) as f32;
+                    }
                 }
                 offset += self.width;
             }
@@ -526,6 +827,11 @@ impl HeightMap {
     }
 
     /// Adds values from a Voronoi diagram to the height map.
+    ///
+    /// The given number of `sites` are scattered randomly across the map. Each cell then gets
+    /// the squared distance to its nearest site multiplied by the first coefficient, plus the
+    /// squared distance to its second nearest site multiplied by the second coefficient, and so
+    /// on. Any coefficients beyond the number of sites are ignored.
     pub fn add_voronoi<A: RandomAlgorithm>(
         &mut self,
         sites: usize,
@@ -538,8 +844,6 @@ impl HeightMap {
             dist: NonNan<f32>,
         }
 
-        assert!(sites >= coefficients.len());
-
         let mut points = Vec::with_capacity(sites);
         for _ in 0..sites {
             points.push(Point {
@@ -557,10 +861,10 @@ impl HeightMap {
                         + (point.y - y as i32) as f32 * (point.y - y as i32) as f32)
                         .into();
                 }
-                for coefficient in coefficients {
+                for coefficient in coefficients.iter().take(sites) {
                     let min_dist_point = points.iter_mut().min_by_key(|p| p.dist).unwrap();
                     self.values[offset] += coefficient * *min_dist_point.dist;
-                    min_dist_point.dist = std::f32::MAX.into();
+                    min_dist_point.dist = f32::MAX.into();
                 }
                 offset += self.width;
             }
@@ -570,107 +874,80 @@ impl HeightMap {
     /// Generates a height map with mid-point displacement.
     ///
     /// The mid-point displacement algorithm generates a realistic fractal height map using the
-    /// diamond-square (aka random midpoint displacement) algorithm.
+    /// diamond-square (aka random midpoint displacement) algorithm. The four corners are seeded
+    /// with random values between `0.0` and `1.0`, and each level of subdivision displaces its
+    /// new points by a random amount, whose range starts at `1.0` and is multiplied by
+    /// `roughness` for every level.
     ///
-    /// The roughness range should be comprised between `0.4` and `0.6`.
+    /// The roughness range should be comprised between `0.4` and `0.6`. The resulting values
+    /// aren't normalized; call [`normalize`] afterwards if necessary.
+    ///
+    /// [`normalize`]: #method.normalize
     ///
     /// # Panics
     ///
-    /// If the `width` or the `height` is 0.
+    /// If the height map isn't square, with a width and height that is a power of two plus one,
+    /// e.g. 129 by 129.
     pub fn mid_point_displacement<A: RandomAlgorithm>(
         &mut self,
         random: &mut Random<A>,
         roughness: f32,
     ) {
-        let mut step = 1;
+        assert!(
+            self.width == self.height && self.width > 1 && (self.width - 1).is_power_of_two(),
+            "mid_point_displacement requires a square height map with sides of 2^n + 1."
+        );
+
+        let size = self.width - 1;
+        let mut sz = size;
         let mut offset = 1.0;
-        let init_sz = self.width.min(self.height);
-        let mut sz = init_sz;
-        self.values[0] = random.get_f32(0.0, 1.0);
-        self.values[sz - 1] = random.get_f32(0.0, 1.0);
-        self.values[(sz - 1) * sz] = random.get_f32(0.0, 1.0);
-        self.values[sz * sz - 1] = random.get_f32(0.0, 1.0);
-        while sz > 0 {
+        for &(x, y) in &[(0, 0), (size, 0), (0, size), (size, size)] {
+            *self.get_value_mut(x, y) = random.get_f32(0.0, 1.0);
+        }
+        while sz > 1 {
+            let half = sz / 2;
+
             // diamond step
-            for x in 0..step {
-                for y in 0..step {
-                    let diamond_x = sz / 2 + x * sz;
-                    let diamond_y = sz / 2 + y * sz;
-
-                    let mut z = self.get_value(x * sz, y * sz);
-                    z += self.get_value((x + 1) * sz, y * sz);
-                    z += self.get_value((x + 1) * sz, (y + 1) * sz);
-                    z += self.get_value(x * sz, (y + 1) * sz);
+            for y in (half..size).step_by(sz) {
+                for x in (half..size).step_by(sz) {
+                    let mut z = self.get_value(x - half, y - half);
+                    z += self.get_value(x + half, y - half);
+                    z += self.get_value(x + half, y + half);
+                    z += self.get_value(x - half, y + half);
                     z *= 0.25;
 
-                    self.set_mpd_height(random, diamond_x, diamond_y, z, offset);
+                    self.set_mpd_height(random, x, y, z, offset);
                 }
             }
             offset *= roughness;
 
             // square step
-            for x in 0..step {
-                for y in 0..step {
-                    let diamond_x = sz / 2 + x * sz;
-                    let diamond_y = sz / 2 + y * sz;
-
-                    // north
-                    self.set_mdp_height_square(
-                        random,
-                        diamond_x,
-                        diamond_y - sz / 2,
-                        init_sz,
-                        sz / 2,
-                        offset,
-                    );
-                    // south
-                    self.set_mdp_height_square(
-                        random,
-                        diamond_x,
-                        diamond_y + sz / 2,
-                        init_sz,
-                        sz / 2,
-                        offset,
-                    );
-                    // west
-                    self.set_mdp_height_square(
-                        random,
-                        diamond_x - sz / 2,
-                        diamond_y,
-                        init_sz,
-                        sz / 2,
-                        offset,
-                    );
-                    // east
-                    self.set_mdp_height_square(
-                        random,
-                        diamond_x + sz / 2,
-                        diamond_y,
-                        init_sz,
-                        sz / 2,
-                        offset,
-                    );
+            for y in (0..=size).step_by(half) {
+                let start = if y % sz == 0 { half } else { 0 };
+                for x in (start..=size).step_by(sz) {
+                    self.set_mdp_height_square(random, x, y, half, offset);
                 }
             }
-            sz /= 2;
-            step *= 2;
+            sz = half;
         }
     }
 
     /// Add an FBM to the height map.
     ///
-    /// The noise value for map cell `(x, y)` is `(x + add_x) * mul_x / width` and
+    /// The noise coordinate for map cell `(x, y)` is `(x + add_x) * mul_x / width` and
     /// `(y + add_y) * mul_y / height`, respectively. Those values allow you to scale and translate
     /// the noise function over the height map.
     ///
+    /// The value added to the height map is `delta + noise * scale`.
+    ///
     /// # Panics
     ///
-    /// If the `noise` provided isn't 2D.
+    /// If the `noise` generator provided isn't 2D.
     pub fn add_fbm<A: NoiseAlgorithm>(
         &mut self,
-        noise: &mut Noise<A>,
-        octaves: f32,
+        noise: &Noise<A>,
         coordinates: FbmCoordinateParameters,
+        octaves: f32,
         delta: f32,
         scale: f32,
     ) {
@@ -708,7 +985,7 @@ impl HeightMap {
     /// If the `noise` generator provided isn't 2D.
     pub fn scale_fbm<A: NoiseAlgorithm>(
         &mut self,
-        noise: &mut Noise<A>,
+        noise: &Noise<A>,
         coordinates: FbmCoordinateParameters,
         octaves: f32,
         delta: f32,
@@ -756,7 +1033,6 @@ impl HeightMap {
         random: &mut Random<A>,
         x: usize,
         y: usize,
-        init_sz: usize,
         sz: usize,
         offset: f32,
     ) {
@@ -770,11 +1046,11 @@ impl HeightMap {
             z += self.get_value(x - sz, y);
             count += 1;
         }
-        if y + sz < init_sz {
+        if y + sz < self.height {
             z += self.get_value(x, y + sz);
             count += 1;
         }
-        if x + sz < init_sz {
+        if x + sz < self.width {
             z += self.get_value(x + sz, y);
             count += 1;
         }
@@ -796,8 +1072,7 @@ impl HeightMap {
 }
 
 impl_op_ex!(+ |a: &HeightMap, b: &HeightMap| -> HeightMap {
-    assert_eq!(a.width, b.width);
-    assert_eq!(a.height, b.height);
+    a.assert_same_size(b);
 
     let mut result = a.clone();
     for (r, &o) in result.values.iter_mut().zip(b.values.iter()) {
@@ -814,8 +1089,7 @@ impl AddAssign<f32> for HeightMap {
 }
 
 impl_op_ex!(*|a: &HeightMap, b: &HeightMap| -> HeightMap {
-    assert_eq!(a.width, b.width);
-    assert_eq!(a.height, b.height);
+    a.assert_same_size(b);
 
     let mut result = a.clone();
     for (r, &o) in result.values.iter_mut().zip(b.values.iter()) {
@@ -859,6 +1133,29 @@ pub struct NeighborCell {
     pub weight: f32,
 }
 
+/// The error type returned when accessing a position outside the range of a height map.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct OutOfBoundsError {
+    /// The position that was accessed.
+    pub position: Position,
+    /// The width of the height map.
+    pub width: usize,
+    /// The height of the height map.
+    pub height: usize,
+}
+
+impl std::fmt::Display for OutOfBoundsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} is outside the {}x{} height map",
+            self.position, self.width, self.height
+        )
+    }
+}
+
+impl std::error::Error for OutOfBoundsError {}
+
 /// Represents the coordinates used in the `*_fbm` methods.
 #[derive(Copy, Clone, Debug)]
 pub struct FbmCoordinateParameters {
@@ -871,3 +1168,555 @@ pub struct FbmCoordinateParameters {
     /// See the `*_fbm` methods for details on how this parameter is used.
     pub add_y: f32,
 }
+
+impl FbmCoordinateParameters {
+    /// Returns a new `FbmCoordinateParameters`, with the parameters in the order libtcod's
+    /// `TCOD_heightmap_add_fbm` and `TCOD_heightmap_scale_fbm` take them.
+    pub fn new(mul_x: f32, mul_y: f32, add_x: f32, add_y: f32) -> Self {
+        Self {
+            mul_x,
+            mul_y,
+            add_x,
+            add_y,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::heightmap::{FbmCoordinateParameters, HeightMap, NeighborCell, OutOfBoundsError};
+    use crate::noise::Noise;
+    use crate::random::Random;
+    use crate::{FPosition, Position};
+
+    #[test]
+    fn fbm() {
+        let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(3));
+        let coordinates = FbmCoordinateParameters::new(4.0, 3.0, 0.5, -2.0);
+        let mut original = HeightMap::new(8, 6);
+        original.add_hill(FPosition::new(3.0, 2.0), 4.0, 1.0);
+
+        let mut hm = original.clone();
+        hm.add_fbm(&noise, coordinates, 4.0, 0.0, 0.0);
+        hm.scale_fbm(&noise, coordinates, 4.0, 1.0, 0.0);
+        assert_eq!(original.values, hm.values);
+
+        /* the values are those of the noise itself at the cell's noise coordinates */
+        let mut added = HeightMap::new(8, 6);
+        added.add_fbm(&noise, coordinates, 4.0, 0.5, 2.0);
+        let f = [(5.0 + 0.5) * 4.0 / 8.0, (1.0 - 2.0) * 3.0 / 6.0];
+        let expected = 0.5 + noise.fbm(&f, 4.0) * 2.0;
+        assert_eq!(expected, added.value((5, 1).into()));
+        /* pinned as well, so a change to the noise itself doesn't slip through */
+        let pinned = if cfg!(feature = "libtcod-compat") {
+            -0.864_169
+        } else {
+            0.286_244_5
+        };
+        assert!((expected - pinned).abs() < 1e-6, "{}", expected);
+
+        let mut scaled = original.clone();
+        scaled.scale_fbm(&noise, coordinates, 4.0, 0.5, 2.0);
+        assert_eq!(
+            expected * original.value((5, 1).into()),
+            scaled.value((5, 1).into())
+        );
+    }
+
+    #[test]
+    fn checked_access() {
+        let mut hm = HeightMap::new(4, 3);
+        assert_eq!(Ok(()), hm.try_set_value(Position::new(2, 1), 0.5));
+        assert_eq!(Some(0.5), hm.try_value(Position::new(2, 1)));
+        assert_eq!(0.5, hm.value((2, 1).into()));
+        assert_eq!(Some(0.0), hm.try_value(Position::new(3, 2)));
+
+        for &(x, y) in &[
+            (-1, 0),
+            (0, -1),
+            (4, 0),
+            (0, 3),
+            (4, 3),
+            (i32::MIN, i32::MAX),
+        ] {
+            let position = Position::new(x, y);
+            assert_eq!(None, hm.try_value(position));
+            assert_eq!(
+                Err(OutOfBoundsError {
+                    position,
+                    width: 4,
+                    height: 3
+                }),
+                hm.try_set_value(position, 1.0)
+            );
+        }
+        assert_eq!(0.5, hm.values().iter().sum::<f32>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_value_outside_the_row_panics() {
+        let mut hm = HeightMap::new(4, 3);
+        hm.set_value((4, 0).into(), 1.0);
+    }
+
+    #[test]
+    fn counting_cells_and_finding_land_on_the_border() {
+        let values: Vec<f32> = (0..6 * 4)
+            .map(|i| if i < 12 { -1.0 } else { 1.0 })
+            .collect();
+        let hm = HeightMap::new_with_values(6, 4, &values);
+        assert_eq!(12, hm.count_cells(-1.0, 0.0));
+        assert_eq!(12, hm.count_cells(0.0, 1.0));
+        assert_eq!(24, hm.count_cells(-1.0, 1.0));
+        assert_eq!(0, hm.count_cells(-0.5, 0.5));
+        assert!(hm.has_land_on_border(0.0));
+        assert!(!hm.has_land_on_border(1.0));
+
+        let mut island = HeightMap::new(9, 7);
+        island.add_hill(FPosition::new(4.0, 3.0), 2.5, 1.0);
+        assert!(island.count_cells(0.001, 1.0) > 0);
+        assert!(!island.has_land_on_border(0.0));
+
+        island.add_hill(FPosition::new(7.0, 3.0), 2.5, 1.0);
+        assert!(island.has_land_on_border(0.0));
+    }
+
+    #[test]
+    fn scalar_operations() {
+        let values = [0.25, -1.5, 3.0, 0.0, 8.0, -0.75];
+        let mut hm = HeightMap::new_with_values(3, 2, &values);
+        hm.add_scalar(0.5);
+        assert_eq!(hm.values(), [0.75, -1.0, 3.5, 0.5, 8.5, -0.25]);
+        hm.add_scalar(-0.5);
+        assert_eq!(hm.values(), values);
+
+        hm.multiply_scalar(-2.0);
+        assert_eq!(hm.values(), [-0.5, 3.0, -6.0, 0.0, -16.0, 1.5]);
+
+        hm.set_all(4.5);
+        assert_eq!(hm.values(), [4.5; 6]);
+        hm.clear();
+        assert_eq!(hm.values(), [0.0; 6]);
+    }
+
+    #[test]
+    fn combining_maps() {
+        let other = HeightMap::new_with_values(3, 1, &[1.0, -2.0, 4.0]);
+        let mut hm = HeightMap::new_with_values(3, 1, &[0.5, 1.0, 2.0]);
+        hm.add_map(&other);
+        assert_eq!(hm.values(), [1.5, -1.0, 6.0]);
+        hm.multiply_map(&other);
+        assert_eq!(hm.values(), [1.5, 2.0, 24.0]);
+
+        hm.lerp_map(&other, 0.5);
+        assert_eq!(hm.values(), [1.25, 0.0, 14.0]);
+        hm.lerp_map(&other, 0.0);
+        assert_eq!(hm.values(), [1.25, 0.0, 14.0]);
+        hm.lerp_map(&other, 2.0);
+        assert_eq!(hm.values(), other.values());
+    }
+
+    #[test]
+    #[should_panic(expected = "one is 3x1 and the other is 1x3")]
+    fn combining_maps_of_different_sizes() {
+        let mut hm = HeightMap::new(3, 1);
+        hm.add_map(&HeightMap::new(1, 3));
+    }
+
+    #[test]
+    fn luma8_stretches_the_values() {
+        let values: Vec<f32> = (0..4 * 3)
+            .map(|i| ((i % 4 + i / 4) % 2) as f32 * 0.3 - 2.0)
+            .collect();
+        let hm = HeightMap::new_with_values(4, 3, &values);
+        assert_eq!(
+            hm.to_luma8(),
+            [0, 255, 0, 255, 255, 0, 255, 0, 0, 255, 0, 255]
+        );
+        assert_eq!(values, hm.values());
+
+        let flat = HeightMap::new_with_values(2, 2, &[7.5; 4]);
+        assert_eq!(flat.to_luma8(), [0; 4]);
+    }
+
+    fn tilted_plane(width: usize, height: usize) -> HeightMap {
+        let values: Vec<f32> = (0..width * height)
+            .map(|i| (i % width) as f32 / width as f32)
+            .collect();
+        HeightMap::new_with_values(width, height, &values)
+    }
+
+    fn correlation(a: &[f32], b: &[f32]) -> f32 {
+        let mean = |v: &[f32]| v.iter().sum::<f32>() / v.len() as f32;
+        let (mean_a, mean_b) = (mean(a), mean(b));
+        let mut covariance = 0.0;
+        let mut variance_a = 0.0;
+        let mut variance_b = 0.0;
+        for (&x, &y) in Iterator::zip(a.iter(), b.iter()) {
+            covariance += (x - mean_a) * (y - mean_b);
+            variance_a += (x - mean_a) * (x - mean_a);
+            variance_b += (y - mean_b) * (y - mean_b);
+        }
+
+        covariance / (variance_a * variance_b).sqrt()
+    }
+
+    #[test]
+    fn rain_erosion_deposits_sediment_in_low_cells() {
+        let original = tilted_plane(32, 16);
+        let mut eroded = original.clone();
+        let mut random = Random::new_mt_from_seed(17);
+        eroded.rain_erosion(32 * 16, 0.05, 0.05, &mut random);
+
+        assert_eq!(original.width(), eroded.width());
+        assert_eq!(original.height(), eroded.height());
+        assert!(eroded.values().iter().all(|v| v.is_finite()));
+
+        let depth: Vec<f32> = original.values().iter().map(|&v| -v).collect();
+        let sediment: Vec<f32> = Iterator::zip(eroded.values().iter(), original.values().iter())
+            .map(|(&e, &o)| e - o)
+            .collect();
+        assert!(correlation(&depth, &sediment) > 0.25);
+    }
+
+    #[test]
+    fn interpolated_value_is_exact_on_cells() {
+        let values: Vec<f32> = (0..12).map(|i| (i * i) as f32).collect();
+        let hm = HeightMap::new_with_values(4, 3, &values);
+        for y in 0..3 {
+            for x in 0..4 {
+                assert_eq!(
+                    hm.value((x, y).into()),
+                    hm.interpolated_value((x as f32, y as f32).into())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn interpolated_value_averages_between_cells() {
+        let hm = HeightMap::new_with_values(3, 2, &[0.0, 2.0, 6.0, 4.0, 8.0, 10.0]);
+        assert_eq!(1.0, hm.interpolated_value((0.5, 0.0).into()));
+        assert_eq!(4.0, hm.interpolated_value((1.5, 0.0).into()));
+        assert_eq!(8.0, hm.interpolated_value((2.0, 0.5).into()));
+        assert_eq!(5.0, hm.interpolated_value((1.0, 0.5).into()));
+
+        /* Clamped to the edges */
+        assert_eq!(10.0, hm.interpolated_value((5.0, 7.0).into()));
+        assert_eq!(0.0, hm.interpolated_value((-1.0, -3.0).into()));
+
+        assert_eq!(0.5, hm.normalized_value((1.0, 0.5).into()));
+    }
+
+    #[test]
+    fn slope_and_normal_of_a_tilted_plane() {
+        let hm = tilted_plane(8, 4);
+        let gradient = 1.0_f32 / 8.0;
+        for y in 0..4 {
+            for x in 0..8 {
+                let slope = hm.slope((x, y).into());
+                assert!((slope - gradient.atan()).abs() < 1.0e-6, "{}", slope);
+            }
+        }
+
+        let normal = hm.normal((3.0, 1.0).into(), -1.0);
+        let length = normal.iter().map(|n| n * n).sum::<f32>().sqrt();
+        assert!((length - 1.0).abs() < 1.0e-6);
+        /* Facing downhill, towards the lower x, and up */
+        assert!(normal[0] < 0.0);
+        assert!(normal[1].abs() < 1.0e-6);
+        assert!(normal[2] > 0.0);
+        assert_eq!([0.0, 0.0, 1.0], hm.normal((7.0, 1.0).into(), -1.0));
+    }
+
+    #[test]
+    fn slope_of_a_diagonally_tilted_plane() {
+        let values: Vec<f32> = (0..36).map(|i| (i % 6 + i / 6) as f32 / 8.0).collect();
+        let hm = HeightMap::new_with_values(6, 6, &values);
+        /* Steepest along the diagonal, which climbs by 2/8 over a distance of √2 */
+        let gradient = 2.0_f32.sqrt() / 8.0;
+        for y in 1..5 {
+            for x in 1..5 {
+                let slope = hm.slope((x, y).into());
+                assert!((slope - gradient.atan()).abs() < 1.0e-6, "{}", slope);
+            }
+        }
+    }
+
+    #[test]
+    fn slope_of_flat_maps_and_peaks() {
+        let flat = HeightMap::new(3, 3);
+        assert_eq!(0.0, flat.slope((2, 2).into()));
+
+        let mut peak = HeightMap::new(3, 3);
+        peak.set_value((1, 1).into(), 1.0);
+        assert!((peak.slope((1, 1).into()) - std::f32::consts::FRAC_PI_4).abs() < 1.0e-6);
+        let diagonal = std::f32::consts::FRAC_1_SQRT_2.atan();
+        assert!((peak.slope((0, 2).into()) - diagonal).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn normalize_constant_map() {
+        let mut hm = HeightMap::new_with_values(2, 2, &[3.0; 4]);
+        hm.normalize(-1.0, 1.0);
+        assert_eq!(hm.values(), &[-1.0; 4]);
+    }
+
+    #[test]
+    fn normalize_negative_map() {
+        let mut hm = HeightMap::new_with_values(4, 1, &[-8.0, -6.0, -4.0, -2.0]);
+        hm.normalize(10.0, 16.0);
+        assert_eq!(hm.values(), &[10.0, 12.0, 14.0, 16.0]);
+
+        let min_max = hm.min_max();
+        assert_eq!((10.0, 16.0), (min_max.min, min_max.max));
+    }
+
+    #[test]
+    fn normalize_unit_hits_the_extremes_exactly() {
+        let values: Vec<f32> = (0..100).map(|i| (i as f32 * 0.731).sin() * 3.7).collect();
+        let mut hm = HeightMap::new_with_values(10, 10, &values);
+        hm.normalize_unit();
+
+        let min_max = hm.min_max();
+        assert_eq!(0.0, min_max.min);
+        assert_eq!(1.0, min_max.max);
+    }
+
+    #[test]
+    fn clamp_limits_values() {
+        let mut hm = HeightMap::new_with_values(4, 1, &[-2.0, 0.5, 1.5, 3.0]);
+        hm.clamp(0.0, 2.0);
+        assert_eq!(hm.values(), &[0.0, 0.5, 1.5, 2.0]);
+    }
+
+    fn cell(x: i32, y: i32, weight: f32) -> NeighborCell {
+        NeighborCell {
+            relative_position: Position::new(x, y),
+            weight,
+        }
+    }
+
+    #[test]
+    fn kernel_transform_clips_kernels_at_the_edges() {
+        let mut hm = HeightMap::new_with_values(5, 1, &[0.0, 0.0, 9.0, 0.0, 0.0]);
+        let blur = [cell(-1, 0, 1.0), cell(0, 0, 1.0), cell(1, 0, 1.0)];
+        hm.kernel_transform(&blur, 0.0, 100.0);
+
+        let expected = [0.0, 3.0, 4.0, 4.0 / 3.0, 2.0 / 3.0];
+        for (&value, &e) in Iterator::zip(hm.values().iter(), expected.iter()) {
+            assert!((value - e).abs() < 1.0e-6, "{:?}", hm.values());
+        }
+    }
+
+    #[test]
+    fn kernel_transform_respects_levels() {
+        let mut hm = HeightMap::new_with_values(3, 1, &[1.0, 10.0, 1.0]);
+        let blur = [cell(-1, 0, 1.0), cell(0, 0, 1.0), cell(1, 0, 1.0)];
+        hm.kernel_transform(&blur, 5.0, 100.0);
+
+        assert_eq!(hm.values(), &[1.0, 4.0, 1.0]);
+    }
+
+    #[test]
+    fn kernel_transform_without_neighbors_leaves_cells_unchanged() {
+        let mut hm = HeightMap::new_with_values(2, 2, &[1.0, 2.0, 3.0, 4.0]);
+        hm.kernel_transform(&[cell(5, 5, 1.0)], 0.0, 100.0);
+        assert_eq!(hm.values(), &[1.0, 2.0, 3.0, 4.0]);
+
+        hm.kernel_transform(&[cell(0, 0, 1.0), cell(1, 0, -1.0)], 0.0, 100.0);
+        assert!(hm.values().iter().all(|v| v.is_finite()));
+    }
+
+    fn variance(values: &[f32]) -> f32 {
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / values.len() as f32
+    }
+
+    #[test]
+    fn mid_point_displacement_is_deterministic() {
+        let mut first = HeightMap::new(129, 129);
+        first.mid_point_displacement(&mut Random::new_mt_from_seed(3), 0.5);
+        let mut second = HeightMap::new(129, 129);
+        second.mid_point_displacement(&mut Random::new_mt_from_seed(3), 0.5);
+
+        assert_eq!(first.values(), second.values());
+        assert!(first.values().iter().all(|v| v.is_finite()));
+    }
+
+    #[test]
+    fn mid_point_displacement_roughness_controls_variance() {
+        for seed in 0..5 {
+            let mut smooth = HeightMap::new(129, 129);
+            smooth.mid_point_displacement(&mut Random::new_mt_from_seed(seed), 0.3);
+            let mut rough = HeightMap::new(129, 129);
+            rough.mid_point_displacement(&mut Random::new_mt_from_seed(seed), 0.7);
+
+            assert!(variance(smooth.values()) < variance(rough.values()));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "sides of 2^n + 1")]
+    fn mid_point_displacement_requires_power_of_two_plus_one() {
+        let mut hm = HeightMap::new(128, 128);
+        hm.mid_point_displacement(&mut Random::new_mt_from_seed(3), 0.5);
+    }
+
+    #[test]
+    fn rain_erosion_leaves_flat_maps_unchanged() {
+        let original = HeightMap::new_with_values(8, 8, &[0.5; 64]);
+        let mut eroded = original.clone();
+        let mut random = Random::new_mt_from_seed(17);
+        eroded.rain_erosion(1000, 0.05, 0.05, &mut random);
+
+        assert_eq!(original.values(), eroded.values());
+    }
+
+    #[test]
+    fn island_mask_fades_towards_the_corners() {
+        let mut hm = HeightMap::new_with_values(9, 5, &[2.0; 45]);
+        hm.apply_island_mask(2.0);
+        assert_eq!(2.0, hm.value((4, 2).into()));
+        for &corner in &[(0, 0), (8, 0), (0, 4), (8, 4)] {
+            assert!(hm.value(corner.into()).abs() < 1.0e-6);
+        }
+        /* the middle of each edge is equally far from the center, despite the map not being
+         * square */
+        assert!((hm.value((0, 2).into()) - hm.value((4, 0).into())).abs() < 1.0e-6);
+        assert!(hm.value((2, 2).into()) > hm.value((1, 1).into()));
+
+        let once = hm.clone();
+        hm.apply_island_mask(2.0);
+        assert_eq!(2.0, hm.value((4, 2).into()));
+        assert!(hm.value((2, 1).into()) < once.value((2, 1).into()));
+
+        let mut steep = HeightMap::new_with_values(9, 5, &[2.0; 45]);
+        steep.apply_island_mask(8.0);
+        assert!(steep.value((2, 1).into()) > once.value((2, 1).into()));
+    }
+
+    #[test]
+    fn hills_peak_at_their_center() {
+        let mut hm = HeightMap::new(11, 11);
+        hm.add_hill(FPosition::new(5.0, 5.0), 3.0, 2.0);
+        assert_eq!(2.0, hm.value((5, 5).into()));
+        assert!(hm.value((5, 6).into()) < 2.0 && hm.value((5, 6).into()) > hm.value((5, 7).into()));
+        assert_eq!(0.0, hm.value((5, 8).into()));
+        assert_eq!(0.0, hm.value((7, 8).into()));
+        assert!(hm.value((7, 7).into()) > 0.0);
+        let raised = hm.count_cells(0.001, 10.0);
+
+        hm.add_hill(FPosition::new(-20.0, 5.0), 3.0, 2.0);
+        hm.add_hill(FPosition::new(5.0, 50.0), 3.0, 2.0);
+        hm.add_hill(FPosition::new(2.0, 2.0), 0.0, 2.0);
+        assert_eq!(raised, hm.count_cells(0.001, 10.0));
+    }
+
+    #[test]
+    fn cellular_automaton_cave() {
+        let cave = |fill_probability: f32, seed: u32| {
+            let mut hm = HeightMap::new(40, 30);
+            let mut random = Random::new_mt_from_seed(seed);
+            hm.cellular_automaton_cave(fill_probability, 4, 5, 4, &mut random);
+            hm
+        };
+
+        let sparse = cave(0.4, 1234);
+        assert_eq!(sparse.values(), cave(0.4, 1234).values());
+        assert!(sparse.values().iter().all(|&v| v == 0.0 || v == 1.0));
+        for x in 0..40 {
+            assert_eq!(1.0, sparse.value((x, 0).into()));
+            assert_eq!(1.0, sparse.value((x, 29).into()));
+        }
+        for y in 0..30 {
+            assert_eq!(1.0, sparse.value((0, y).into()));
+            assert_eq!(1.0, sparse.value((39, y).into()));
+        }
+
+        let dense = cave(0.55, 1234);
+        assert!(dense.count_cells(0.5, 1.0) > sparse.count_cells(0.5, 1.0));
+        assert!(sparse.count_cells(0.0, 0.5) > 0);
+
+        for &(width, height) in &[(1, 1), (2, 5), (5, 2)] {
+            let mut small = HeightMap::new(width, height);
+            small.cellular_automaton_cave(0.4, 4, 5, 4, &mut Random::new_mt_from_seed(1));
+            assert!(small.values().iter().all(|&v| v == 1.0));
+        }
+    }
+
+    #[test]
+    fn dig_bezier_carves_along_the_curve() {
+        let mut hm = HeightMap::new(20, 10);
+        let line = [(2, 5).into(), (7, 5).into(), (12, 5).into(), (17, 5).into()];
+        hm.dig_bezier(line, 2.0, -1.0, 2.0, -3.0);
+
+        /* Evenly spaced, collinear control points make the curve parameter linear in x, and the
+        curve enters cell x halfway from the previous one */
+        for x in 3..=17 {
+            let t = (x as f32 - 2.5) / 15.0;
+            let depth = -1.0 - 2.0 * t;
+            assert!((hm.value((x, 5).into()) - depth).abs() < 0.01);
+        }
+        for x in 0..20 {
+            for &y in &[0, 1, 2, 3, 7, 8, 9] {
+                assert_eq!(0.0, hm.value((x, y).into()));
+            }
+        }
+    }
+
+    #[test]
+    fn dig_bezier_clips_and_ignores_empty_curves() {
+        let mut hm = HeightMap::new(20, 10);
+        let point = [(4, 4).into(); 4];
+        hm.dig_bezier(point, 3.0, -1.0, 3.0, -1.0);
+        assert_eq!(0, hm.count_cells(-10.0, -0.001));
+
+        let off_map = [
+            (5, 5).into(),
+            (40, 5).into(),
+            (5, 30).into(),
+            (60, 40).into(),
+        ];
+        hm.dig_bezier(off_map, 2.0, -1.0, 2.0, -1.0);
+        assert!(hm.value((5, 5).into()) < 0.0);
+    }
+
+    #[test]
+    fn dig_hill_only_ever_lowers_or_raises() {
+        let mut hm = HeightMap::new_with_values(11, 11, &[-1.0; 121]);
+        hm.dig_hill(FPosition::new(5.0, 5.0), 4.0, -2.0);
+        assert_eq!(-2.0, hm.value((5, 5).into()));
+        /* The dug hill doesn't reach below the terrain here, so it's left alone */
+        assert_eq!(-1.0, hm.value((5, 8).into()));
+
+        hm.dig_hill(FPosition::new(5.0, 5.0), 4.0, 1.5);
+        assert_eq!(1.5, hm.value((5, 5).into()));
+        assert!(hm.value((5, 8).into()) > 0.0);
+        assert_eq!(-1.0, hm.value((5, 9).into()));
+    }
+
+    #[test]
+    fn voronoi_with_one_site_grows_with_the_distance() {
+        let mut hm = HeightMap::new(16, 12);
+        hm.add_voronoi(1, &[1.0, -3.0], &mut Random::new_mt_from_seed(6));
+
+        let site = (0..16 * 12)
+            .map(|i| Position::new(i % 16, i / 16))
+            .find(|&p| hm.value((p.x as u32, p.y as u32).into()) == 0.0)
+            .unwrap();
+        let distance = |p: Position| (p.x - site.x).pow(2) + (p.y - site.y).pow(2);
+        for a in 0..16 * 12 {
+            for b in 0..16 * 12 {
+                let (pa, pb) = (Position::new(a % 16, a / 16), Position::new(b % 16, b / 16));
+                if distance(pa) < distance(pb) {
+                    assert!(
+                        hm.value((pa.x as u32, pa.y as u32).into())
+                            < hm.value((pb.x as u32, pb.y as u32).into())
+                    );
+                }
+            }
+        }
+    }
+}
@@ -57,20 +57,44 @@
 //! you want to use this library without bringing in [`doryen-rs`] as a dependency, just put
 //! ```toml
 //! [dependencies]
-//! doryen-extra = { version = "...", default-features = false }
+//! doryen-extra = { version = "...", default-features = false, features = ["std"] }
 //! ```
 //! in your `Cargo.toml` file, which removes the default `doryen` feature.
 //!
+//! ## `std`
+//!
+//! Everything but the random number generator algorithms needs the standard library, which is
+//! behind the default `std` feature. Without it, the library is `no_std`, and only the
+//! `random::algorithms` module is available; the algorithms and `Bits` only use `core` and never
+//! allocate, so they can be used on embedded targets. `entropy_seed` and the `std::error::Error`
+//! implementations are also left out.
+//!
+//! Before version 0.3, there was no `std` feature, and everything was always available. If you
+//! used to turn off the default features, you now need to enable `std` to keep what you had, as
+//! shown above.
+//!
+//! ## `entropy`
+//!
+//! With this feature enabled, the `new_seeded` constructors of the RNG algorithms seed themselves
+//! from the operating system's random number source, using the [`getrandom`] crate. Without it,
+//! they fall back to seeding from the current time.
+//!
+//! ## `image`
+//!
+//! With this feature enabled, `HeightMap::save_png` saves a height map as a grayscale PNG image,
+//! using the [`image`] crate.
+//!
 //! ## `libtcod-compat`
 //!
 //! This feature restores (on a best-effort basis) the functionality of the original
-//! `libtcod` library, where it has been modified. At the time of writing, the only change that
-//! will happen is to the float generation of the `ComplementaryMultiplyWithCarry` RNG algorithm.
+//! `libtcod` library, where it has been modified. At the time of writing, the changes that
+//! will happen are to the floating point number generation and the seeding of the RNG algorithms.
 //!
 //! ## `rng_support`
 //!
-//! With this feature enabled, the [`Random`] struct implements [`rand_core::RngCore`] and
-//! [`rand_core::SeedableRng`], which lets it be used in any place that accepts the `rand` crate RNGs.
+//! With this feature enabled, the [`Random`] struct, as well as the `MersenneTwister` and
+//! `ComplementaryMultiplyWithCarry` algorithms, implement [`rand_core::RngCore`] and
+//! [`rand_core::SeedableRng`], which lets them be used in any place that accepts the `rand` crate RNGs.
 //!
 //! ## `serialization`
 //!
@@ -81,11 +105,8 @@
 //! # Missing Features / Toolkits
 //!
 //! The following toolkits from [`libtcod`] have not yet been converted, with possible reason given in parenthesis:
-//! * `bsp` toolkit: 2D Binary Space Partition
-//! * `fov` toolkit: Easily calculate the potential visible set of map cells from the player position
 //! * `image` toolkit: Some image manipulation utilities (undecided on whether to convert this one; other crates may already serve this purpose)
 //! * `list` toolkit: A fast, lightweight and generic container, that provides array, list and stack paradigms (use `Vec` instead)
-//! * `namegen` toolkit: Allows one to generate random names out of custom made syllable sets (parts requires `parse` toolkit)
 //! * `parse` toolkit: An easy way to parse complex text configuration files
 //!
 //! [`libtcod`]: https://github.com/libtcod/libtcod
@@ -93,11 +114,14 @@
 //! [`tcod`]: https://crates.io/crates/tcod
 //!
 //! [`Random`]: ./random/struct.Random.html
+//! [`getrandom`]: ../getrandom/index.html
+//! [`image`]: ../image/index.html
 //! [`rand_core::RngCore`]: ../rand_core/trait.RngCore.html
 //! [`rand_core::SeedableRng`]: ../rand_core/trait.SeedableRng.html
 //! [`serde::ser::Serialize`]: ../serde/ser/trait.Serialize.html
 //! [`serde::de::Deserialize`]: ../serde/de/trait.Deserialize.html
 
+#![cfg_attr(not(feature = "std"), no_std)]
 // Coding conventions
 //
 // Deny (don't do this)
@@ -128,14 +152,14 @@
 // Deny (don't do this)
 #![deny(clippy::cast_lossless)]
 #![deny(clippy::default_trait_access)]
-#![deny(clippy::empty_enum)]
+#![deny(clippy::empty_enums)]
 #![deny(clippy::enum_glob_use)]
 #![deny(clippy::expl_impl_clone_on_copy)]
 #![deny(clippy::explicit_into_iter_loop)]
 #![deny(clippy::explicit_iter_loop)]
-#![deny(clippy::filter_map)]
+#![deny(clippy::manual_filter_map)]
 #![deny(clippy::filter_map_next)]
-#![deny(clippy::find_map)]
+#![deny(clippy::manual_find_map)]
 #![deny(clippy::if_not_else)]
 #![deny(clippy::invalid_upcast_comparisons)]
 #![deny(clippy::items_after_statements)]
@@ -158,15 +182,17 @@
 // Warn (try not to do this)
 //#![warn(clippy::must_use_candidate)]
 #![deny(clippy::new_without_default)]
-#![warn(clippy::pub_enum_variant_names)]
 #![warn(clippy::shadow_unrelated)]
 #![warn(clippy::similar_names)]
 #![warn(clippy::too_many_lines)]
 
+#[cfg(feature = "std")]
 #[macro_use]
 mod util;
 
+#[cfg(feature = "std")]
 mod base;
+#[cfg(feature = "std")]
 pub use base::*;
 
 #[cfg(feature = "doryen")]
@@ -174,9 +200,30 @@ pub mod extenders;
 #[cfg(feature = "doryen")]
 pub mod extensions;
 
+#[cfg(feature = "std")]
 pub mod bresenham;
+#[cfg(feature = "std")]
+pub mod bsp;
+#[cfg(feature = "std")]
 pub mod color;
+#[cfg(feature = "std")]
+pub mod fov;
 
+#[cfg(feature = "std")]
 pub mod heightmap;
+#[cfg(feature = "std")]
+pub mod namegen;
+#[cfg(feature = "std")]
 pub mod noise;
+#[cfg(feature = "std")]
+pub mod path;
+#[cfg(feature = "std")]
 pub mod random;
+
+/// # Random number generator.
+///
+/// Without the `std` feature, only the random number generator algorithms are available.
+#[cfg(not(feature = "std"))]
+pub mod random {
+    pub mod algorithms;
+}
@@ -0,0 +1,695 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * Copyright © 2008-2019, Jice and the libtcod contributors.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Name generation.
+//!
+//! This module generates random names out of syllable sets, following rules that say which
+//! kinds of syllables make up a name. The syllable sets are loaded from definitions in the same
+//! format as the `.cfg` files used by the `libtcod` name generator:
+//!
+//! ```text
+//! name "dwarf" {
+//!     syllablesStart = "Dur, Thr, Bal"
+//!     syllablesEnd = "in, ain, or"
+//!     rules = "$s$e, %25$s$e_$s$e"
+//!     illegal = "rr"
+//! }
+//! ```
+//!
+//! Every property takes a comma separated list. The syllable lists are `syllablesPre`,
+//! `syllablesStart`, `syllablesMiddle`, `syllablesEnd` and `syllablesPost`, and the phoneme lists
+//! are `phonemesVocals` and `phonemesConsonants`. An underscore in a syllable or a rule stands
+//! for a space.
+//!
+//! Each rule is a template, where `$P`, `$s`, `$m`, `$e` and `$p` are replaced by a random
+//! syllable from the pre, start, middle, end and post lists respectively, `$v` and `$c` by a
+//! random vocal or consonant, and `$?` by either. A number between the `$` and the letter is the
+//! percentage chance of the part being included at all, e.g. `$50m`. A rule starting with a
+//! percentage, e.g. `%25$s$e`, is only used that often when picked. Generated names containing
+//! any of the `illegal` strings, or the same letter three times in a row, are thrown away.
+//!
+//! Alternatively, the [`MarkovNameGenerator`](struct.MarkovNameGenerator.html) learns which
+//! letters tend to follow each other from a list of existing names, and generates new names
+//! that look like them.
+
+use crate::random::Rng;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/* How many times generation is attempted before giving up on finding a legal name */
+const MAX_ATTEMPTS: usize = 1000;
+
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+struct NameSet {
+    name: String,
+    pre: Vec<String>,
+    start: Vec<String>,
+    middle: Vec<String>,
+    end: Vec<String>,
+    post: Vec<String>,
+    vocals: Vec<String>,
+    consonants: Vec<String>,
+    rules: Vec<String>,
+    illegal: Vec<String>,
+}
+
+impl NameSet {
+    fn generate<R: Rng>(&self, rng: &mut R) -> String {
+        /* Pick a rule, giving rules with a percentage that chance of being used */
+        let rule = loop {
+            let rule = &self.rules[rng.get_i32(0, self.rules.len() as i32 - 1) as usize];
+            let (chance, rule) = match rule.strip_prefix('%') {
+                Some(rest) => split_number(rest),
+                None => (100, rule.as_str()),
+            };
+            if rng.get_i32(0, 100) <= chance {
+                break rule;
+            }
+        };
+
+        let mut name = String::new();
+        let mut chars = rule.chars();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                name.push(c);
+                continue;
+            }
+
+            let (chance, rest) = split_number(chars.as_str());
+            chars = rest.chars();
+            let list = match chars.next() {
+                Some('P') => &self.pre,
+                Some('s') => &self.start,
+                Some('m') => &self.middle,
+                Some('e') => &self.end,
+                Some('p') => &self.post,
+                Some('v') => &self.vocals,
+                Some('c') => &self.consonants,
+                Some('?') => {
+                    if rng.get_i32(0, 1) == 0 {
+                        &self.vocals
+                    } else {
+                        &self.consonants
+                    }
+                }
+                _ => continue,
+            };
+            if rng.get_i32(0, 100) <= chance && !list.is_empty() {
+                name.push_str(&list[rng.get_i32(0, list.len() as i32 - 1) as usize]);
+            }
+        }
+
+        name.replace('_', " ").trim().to_owned()
+    }
+
+    fn is_legal(&self, name: &str) -> bool {
+        let lowercase = name.to_lowercase();
+        let chars: Vec<char> = lowercase.chars().collect();
+        let has_triples = chars.windows(3).any(|w| w[0] == w[1] && w[1] == w[2]);
+        let has_illegal = self
+            .illegal
+            .iter()
+            .any(|illegal| lowercase.contains(&illegal.to_lowercase()));
+        !name.is_empty() && !has_triples && !has_illegal
+    }
+}
+
+/* Splits a leading percentage off a string, defaulting to 100 when there is none */
+fn split_number(s: &str) -> (i32, &str) {
+    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    match s[..digits].parse() {
+        Ok(number) => (number, &s[digits..]),
+        Err(_) => (100, s),
+    }
+}
+
+/// A name generator, holding any number of named syllable sets.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct NameGenerator {
+    sets: Vec<NameSet>,
+}
+
+impl NameGenerator {
+    /// Returns a new name generator without any syllable sets.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the syllable set definitions in the file at the given `path`. Sets with the same
+    /// name as an already loaded set replace it.
+    ///
+    /// Nothing is loaded if the file can't be read or contains errors.
+    pub fn parse_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), NameGenError> {
+        let data = std::fs::read_to_string(path).map_err(NameGenError::Io)?;
+        self.parse_str(&data).map_err(NameGenError::Parse)
+    }
+
+    /// Loads the syllable set definitions in `data`. Sets with the same name as an already loaded
+    /// set replace it.
+    ///
+    /// Nothing is loaded if `data` contains errors.
+    pub fn parse_str(&mut self, data: &str) -> Result<(), NameGenParseError> {
+        let sets = Parser::new(data).parse()?;
+        for set in sets {
+            match self.sets.iter_mut().find(|s| s.name == set.name) {
+                Some(existing) => *existing = set,
+                None => self.sets.push(set),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the names of the loaded syllable sets, in the order they were first loaded.
+    pub fn set_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.sets.iter().map(|set| set.name.as_str())
+    }
+
+    /// Generates a random name using the syllable set with the given name.
+    ///
+    /// Returns `None` if there is no syllable set with that name, or if no legal name could be
+    /// generated after a thousand attempts.
+    pub fn generate<R: Rng>(&self, set_name: &str, rng: &mut R) -> Option<String> {
+        let set = self.sets.iter().find(|set| set.name == set_name)?;
+        (0..MAX_ATTEMPTS)
+            .map(|_| set.generate(rng))
+            .find(|name| set.is_legal(name))
+    }
+}
+
+/// A name generator based on a Markov chain of the letters in a list of training names.
+///
+/// Each letter of a generated name is picked based on how often it followed the previous
+/// `order` letters in the training names, so a higher order gives names closer to the training
+/// names, and a lower order gives more varied names.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct MarkovNameGenerator {
+    order: usize,
+    /* The letters seen following each context of `order` letters, with `None` being the end of
+     * the name. Contexts at the start of a name are padded with `START`. */
+    transitions: BTreeMap<String, Vec<Option<char>>>,
+    names: BTreeSet<String>,
+    dedupe: bool,
+}
+
+impl MarkovNameGenerator {
+    /* Pads contexts at the start of names; it can't appear in the names themselves */
+    const START: char = '\u{0}';
+
+    /// Returns a new, untrained, Markov name generator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Learns from the given `names`, looking at the `order` letters preceding each letter.
+    /// Training again with the same order adds to what was learned before, while training with
+    /// a different order starts over.
+    ///
+    /// # Panics
+    ///
+    /// If `order` is 0.
+    pub fn train(&mut self, names: &[&str], order: usize) {
+        assert!(order > 0, "order must be at least 1");
+        if order != self.order {
+            self.order = order;
+            self.transitions.clear();
+            self.names.clear();
+        }
+
+        for name in names {
+            let name = name.replace(Self::START, "");
+            let mut context: String = std::iter::repeat_n(Self::START, order).collect();
+            for c in name.chars().map(Some).chain(std::iter::once(None)) {
+                self.transitions.entry(context.clone()).or_default().push(c);
+                if let Some(c) = c {
+                    context.remove(0);
+                    context.push(c);
+                }
+            }
+            self.names.insert(name);
+        }
+    }
+
+    /// Sets whether names identical to one of the training names are thrown away. It is off by
+    /// default.
+    pub fn set_dedupe(&mut self, dedupe: bool) {
+        self.dedupe = dedupe;
+    }
+
+    /// Generates a random name with between `min_len` and `max_len` letters, inclusive.
+    ///
+    /// Returns `None` if the generator hasn't been trained, or if no name fitting the
+    /// requirements could be generated after a thousand attempts.
+    pub fn generate<R: Rng>(&self, rng: &mut R, min_len: usize, max_len: usize) -> Option<String> {
+        if self.transitions.is_empty() {
+            return None;
+        }
+
+        (0..MAX_ATTEMPTS)
+            .filter_map(|_| self.generate_once(rng, max_len))
+            .find(|name| {
+                let len = name.chars().count();
+                len >= min_len && !(self.dedupe && self.names.contains(name))
+            })
+    }
+
+    /* Generates a name, giving up once it gets longer than `max_len` */
+    fn generate_once<R: Rng>(&self, rng: &mut R, max_len: usize) -> Option<String> {
+        let mut name = String::new();
+        let mut context: String = std::iter::repeat_n(Self::START, self.order).collect();
+        for _ in 0..=max_len {
+            let next = &self.transitions[&context];
+            match next[rng.get_i32(0, next.len() as i32 - 1) as usize] {
+                Some(c) => {
+                    name.push(c);
+                    context.remove(0);
+                    context.push(c);
+                }
+                None => return Some(name),
+            }
+        }
+        None
+    }
+}
+
+/// The error type returned when loading a name generator file fails.
+#[derive(Debug)]
+pub enum NameGenError {
+    /// The file couldn't be read.
+    Io(std::io::Error),
+    /// The file's contents couldn't be parsed.
+    Parse(NameGenParseError),
+}
+
+impl std::fmt::Display for NameGenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not read name generator file: {}", error),
+            Self::Parse(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for NameGenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Parse(error) => Some(error),
+        }
+    }
+}
+
+/// The error type returned when parsing name generator definitions fails.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NameGenParseError {
+    /// The line, starting from 1, where the error was found.
+    pub line: usize,
+    /// The kind of error that was found.
+    pub kind: NameGenParseErrorKind,
+}
+
+/// The kinds of errors that can occur when parsing name generator definitions.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum NameGenParseErrorKind {
+    /// Something other than what was expected was found, e.g. a missing `{`.
+    Expected(&'static str),
+    /// A string was not closed before the end of its line.
+    UnterminatedString,
+    /// A comment was not closed before the end of the definitions.
+    UnterminatedComment,
+    /// A property that doesn't exist was set.
+    UnknownProperty(String),
+    /// A syllable set without any rules was defined.
+    MissingRules(String),
+}
+
+impl std::fmt::Display for NameGenParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid name generator definition on line {}: ",
+            self.line
+        )?;
+        match &self.kind {
+            NameGenParseErrorKind::Expected(expected) => write!(f, "expected {}", expected),
+            NameGenParseErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            NameGenParseErrorKind::UnterminatedComment => write!(f, "unterminated comment"),
+            NameGenParseErrorKind::UnknownProperty(property) => {
+                write!(f, "unknown property {:?}", property)
+            }
+            NameGenParseErrorKind::MissingRules(name) => write!(f, "set {:?} has no rules", name),
+        }
+    }
+}
+
+impl std::error::Error for NameGenParseError {}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Identifier(&'a str),
+    String(&'a str),
+    Symbol(char),
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Self {
+        Self { s, line: 1 }
+    }
+
+    fn parse(mut self) -> Result<Vec<NameSet>, NameGenParseError> {
+        let mut sets = Vec::new();
+        while let Some(token) = self.next_token()? {
+            if token != Token::Identifier("name") {
+                return Err(self.error(NameGenParseErrorKind::Expected("\"name\"")));
+            }
+            let name = match self.next_token()? {
+                Some(Token::String(name)) => name,
+                _ => return Err(self.error(NameGenParseErrorKind::Expected("a set name"))),
+            };
+            self.expect_symbol('{')?;
+            sets.push(self.parse_set(name)?);
+        }
+        Ok(sets)
+    }
+
+    fn parse_set(&mut self, name: &str) -> Result<NameSet, NameGenParseError> {
+        let mut set = NameSet {
+            name: name.to_owned(),
+            ..NameSet::default()
+        };
+        loop {
+            let property = match self.next_token()? {
+                Some(Token::Symbol('}')) => break,
+                Some(Token::Identifier(property)) => property,
+                _ => return Err(self.error(NameGenParseErrorKind::Expected("a property or '}'"))),
+            };
+            let list = match property {
+                "syllablesPre" => &mut set.pre,
+                "syllablesStart" => &mut set.start,
+                "syllablesMiddle" => &mut set.middle,
+                "syllablesEnd" => &mut set.end,
+                "syllablesPost" => &mut set.post,
+                "phonemesVocals" => &mut set.vocals,
+                "phonemesConsonants" => &mut set.consonants,
+                "rules" => &mut set.rules,
+                "illegal" => &mut set.illegal,
+                _ => {
+                    let kind = NameGenParseErrorKind::UnknownProperty(property.to_owned());
+                    return Err(self.error(kind));
+                }
+            };
+            self.expect_symbol('=')?;
+            let value = match self.next_token()? {
+                Some(Token::String(value)) => value,
+                _ => return Err(self.error(NameGenParseErrorKind::Expected("a string"))),
+            };
+            *list = value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_owned)
+                .collect();
+        }
+
+        if set.rules.is_empty() {
+            return Err(self.error(NameGenParseErrorKind::MissingRules(set.name)));
+        }
+        Ok(set)
+    }
+
+    fn expect_symbol(&mut self, symbol: char) -> Result<(), NameGenParseError> {
+        match self.next_token()? {
+            Some(Token::Symbol(c)) if c == symbol => Ok(()),
+            _ => Err(self.error(NameGenParseErrorKind::Expected(match symbol {
+                '{' => "'{'",
+                _ => "'='",
+            }))),
+        }
+    }
+
+    fn error(&self, kind: NameGenParseErrorKind) -> NameGenParseError {
+        NameGenParseError {
+            line: self.line,
+            kind,
+        }
+    }
+
+    /* Skips whitespace and comments, keeping track of the line */
+    fn skip_ignored(&mut self) -> Result<(), NameGenParseError> {
+        loop {
+            let trimmed = self.s.trim_start();
+            self.line += self.s[..self.s.len() - trimmed.len()].matches('\n').count();
+            self.s = trimmed;
+
+            if self.s.starts_with("//") {
+                let end = self.s.find('\n').unwrap_or(self.s.len());
+                self.s = &self.s[end..];
+            } else if let Some(rest) = self.s.strip_prefix("/*") {
+                let end = rest
+                    .find("*/")
+                    .ok_or_else(|| self.error(NameGenParseErrorKind::UnterminatedComment))?;
+                self.line += rest[..end].matches('\n').count();
+                self.s = &rest[end + 2..];
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'a>>, NameGenParseError> {
+        self.skip_ignored()?;
+        let c = match self.s.chars().next() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+
+        if c == '"' {
+            let rest = &self.s[1..];
+            let end = rest
+                .find(['"', '\n'])
+                .filter(|&end| rest[end..].starts_with('"'))
+                .ok_or_else(|| self.error(NameGenParseErrorKind::UnterminatedString))?;
+            self.s = &rest[end + 1..];
+            Ok(Some(Token::String(&rest[..end])))
+        } else if c.is_alphabetic() {
+            let end = self
+                .s
+                .find(|other: char| !other.is_alphanumeric() && other != '_')
+                .unwrap_or(self.s.len());
+            let identifier = &self.s[..end];
+            self.s = &self.s[end..];
+            Ok(Some(Token::Identifier(identifier)))
+        } else {
+            self.s = &self.s[c.len_utf8()..];
+            Ok(Some(Token::Symbol(c)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::namegen::{
+        MarkovNameGenerator, NameGenParseError, NameGenParseErrorKind, NameGenerator,
+    };
+    use crate::random::Random;
+
+    const DWARVES: &str = r#"
+        // Some dwarven names
+        name "dwarf" {
+            syllablesStart = "Dur, Thr, Bal, Gim"
+            syllablesMiddle = "a, o"
+            syllablesEnd = "in, ain, or, li"
+            syllablesPost = "the_Bold, Ironfoot"
+            /* Middle syllables are rare,
+               and titles even rarer */
+            rules = "$s$e, $s$25m$e, %10$s$e_$p"
+            illegal = "rr, ao"
+        }
+
+        name "short" {
+            phonemesVocals = "a"
+            phonemesConsonants = "k"
+            rules = "$c$v"
+        }
+    "#;
+
+    #[test]
+    fn parse_and_generate() {
+        let mut generator = NameGenerator::new();
+        generator.parse_str(DWARVES).unwrap();
+        assert_eq!(
+            vec!["dwarf", "short"],
+            generator.set_names().collect::<Vec<_>>()
+        );
+
+        let mut random = Random::new_mt_from_seed(42);
+        let mut same_seed = Random::new_mt_from_seed(42);
+        /* libtcod-compat seeds the twister and maps numbers onto ranges its own way */
+        let expected = if cfg!(feature = "libtcod-compat") {
+            "Balli"
+        } else {
+            "Balor"
+        };
+        assert_eq!(
+            Some(expected.to_owned()),
+            generator.generate("dwarf", &mut Random::new_mt_from_seed(42))
+        );
+        let mut titled = 0;
+        for _ in 0..50 {
+            let name = generator.generate("dwarf", &mut random).unwrap();
+            assert_eq!(
+                Some(&name),
+                generator.generate("dwarf", &mut same_seed).as_ref()
+            );
+            assert!(["Dur", "Thr", "Bal", "Gim"]
+                .iter()
+                .any(|s| name.starts_with(s)));
+            assert!(!name.contains("rr") && !name.contains("ao") && !name.contains('_'));
+            if name.contains(' ') {
+                assert!(name.ends_with(" the Bold") || name.ends_with(" Ironfoot"));
+                titled += 1;
+            }
+        }
+        assert!(titled > 0);
+        assert_eq!(
+            Some("ka".to_owned()),
+            generator.generate("short", &mut random)
+        );
+        assert_eq!(None, generator.generate("elf", &mut random));
+    }
+
+    #[test]
+    fn redefined_sets_replace_old_ones() {
+        let mut generator = NameGenerator::new();
+        generator.parse_str(DWARVES).unwrap();
+        generator
+            .parse_str("name \"short\" { phonemesVocals = \"o\" phonemesConsonants = \"t\" rules = \"$v$c\" }")
+            .unwrap();
+        assert_eq!(2, generator.set_names().count());
+        let mut random = Random::new_mt_from_seed(1);
+        assert_eq!(
+            Some("ot".to_owned()),
+            generator.generate("short", &mut random)
+        );
+    }
+
+    #[test]
+    fn parse_errors_report_lines() {
+        let error = |data: &str| NameGenerator::new().parse_str(data).unwrap_err();
+        assert_eq!(
+            NameGenParseError {
+                line: 3,
+                kind: NameGenParseErrorKind::UnknownProperty("syllablesFirst".to_owned()),
+            },
+            error("name \"a\" {\n  rules = \"$s\"\n  syllablesFirst = \"x\"\n}")
+        );
+        assert_eq!(
+            NameGenParseError {
+                line: 2,
+                kind: NameGenParseErrorKind::UnterminatedString,
+            },
+            error("name \"a\" {\n  rules = \"$s\n}")
+        );
+        assert_eq!(
+            NameGenParseErrorKind::MissingRules("a".to_owned()),
+            error("name \"a\" { syllablesStart = \"x\" }").kind
+        );
+        assert_eq!(
+            NameGenParseErrorKind::Expected("'='"),
+            error("/* a\n comment */ name \"a\" { rules \"x\" }").kind
+        );
+        assert_eq!(
+            2,
+            error("/* a\n comment */ name \"a\" { rules \"x\" }").line
+        );
+        assert_eq!(
+            NameGenParseErrorKind::UnterminatedComment,
+            error("name \"a\" { /* rules = \"x\" }").kind
+        );
+    }
+
+    #[test]
+    fn markov_names() {
+        let towns = [
+            "Ashford", "Bradford", "Oxford", "Stafford", "Ashby", "Derby", "Whitby", "Selby",
+            "Ashton", "Brighton", "Preston", "Boston",
+        ];
+        let mut generator = MarkovNameGenerator::new();
+        assert_eq!(
+            None,
+            generator.generate(&mut Random::new_mt_from_seed(3), 1, 10)
+        );
+        generator.train(&towns, 1);
+        generator.set_dedupe(true);
+
+        let mut random = Random::new_mt_from_seed(3);
+        let mut same_seed = Random::new_mt_from_seed(3);
+        for _ in 0..100 {
+            let name = generator.generate(&mut random, 5, 8).unwrap();
+            assert!((5..=8).contains(&name.chars().count()), "{}", name);
+            assert!(!towns.contains(&name.as_str()), "{}", name);
+            assert_eq!(Some(name), generator.generate(&mut same_seed, 5, 8));
+        }
+
+        /* Every name of a single letter generator is the letter repeated */
+        let mut repeated = MarkovNameGenerator::new();
+        repeated.train(&["aaaa"], 1);
+        let name = repeated.generate(&mut random, 2, 4).unwrap();
+        assert!(name.chars().all(|c| c == 'a'));
+        assert!((2..=4).contains(&name.len()));
+        assert_eq!(None, repeated.generate(&mut random, 5, 4));
+
+        /* With a higher order, only the training names themselves can be generated here */
+        generator.train(&towns, 3);
+        assert_eq!(None, generator.generate(&mut random, 1, 10));
+        generator.set_dedupe(false);
+        let town = generator.generate(&mut random, 1, 10).unwrap();
+        assert!(towns.contains(&town.as_str()));
+    }
+}
@@ -49,14 +49,20 @@
 //! ## Choosing a noise type
 //! The default choice should be Simplex. It's much faster than Perlin, especially in 4 dimensions.
 //! It has a better contrast too.
+//!
+//! Cellular (Worley) noise is a different beast, with values computed from the distances to
+//! randomly placed feature points, and is well suited for caves, cracked ground or stone tiles.
 
 pub mod algorithms;
 
+use crate::color::Color;
 use crate::noise::algorithms::Algorithm;
 use crate::noise::algorithms::AlgorithmInitializer;
+use crate::noise::algorithms::DerivativeAlgorithm;
 use crate::noise::algorithms::Perlin;
 use crate::noise::algorithms::Simplex;
 use crate::noise::algorithms::Wavelet;
+use crate::noise::algorithms::{Cellular, CellularFunction, DistanceMetric};
 use crate::random::algorithms::Algorithm as RandomAlgorithm;
 use crate::random::Random;
 use derivative::Derivative;
@@ -65,12 +71,110 @@ use derivative::Derivative;
 pub const MAX_OCTAVES: usize = 128;
 /// The maximum number of dimensions supported.
 pub const MAX_DIMENSIONS: usize = 4;
-//pub const DEFAULT_HURST: f32 = 0.5;
+/// The default Hurst exponent, which makes each octave's amplitude the inverse of its frequency.
+///
+/// Note that libtcod's default is 0.5; use [`set_hurst`](struct.Noise.html#method.set_hurst) to
+/// match it.
+pub const DEFAULT_HURST: f32 = 1.0;
 /// The default lacunarity value.
 pub const DEFAULT_LACUNARITY: f32 = 2.0;
 
 const DELTA: f32 = 1.0e-6;
 
+/* Offsets the warp noise is sampled at for each axis, far enough apart that the
+displacements along the different axes are uncorrelated. */
+const WARP_OFFSETS: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [5.2, 1.3, 7.1], [1.7, 9.2, 3.4]];
+
+/// What is done to each octave's noise value before it's added to the fractal by
+/// [`fbm_with`](struct.Noise.html#method.fbm_with).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum OctaveTransform {
+    /// The value is added as is, which gives Fractal Brownian Motion.
+    Identity,
+    /// The absolute value is added, which folds the noise at zero and gives turbulence.
+    Abs,
+}
+
+impl OctaveTransform {
+    fn apply(self, value: f32) -> f32 {
+        match self {
+            Self::Identity => value,
+            Self::Abs => value.abs(),
+        }
+    }
+}
+
+/// The parameters of the [`get_fbm_ridged`] and [`get_fbm_billow`] fractals.
+///
+/// [`get_fbm_ridged`]: struct.Noise.html#method.get_fbm_ridged
+/// [`get_fbm_billow`]: struct.Noise.html#method.get_fbm_billow
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct FbmParams {
+    /// The factor the frequency is multiplied with for each octave.
+    pub lacunarity: f32,
+    /// The factor the amplitude is multiplied with for each octave.
+    pub gain: f32,
+}
+
+impl FbmParams {
+    /// Returns parameters with the given lacunarity and gain.
+    pub fn new(lacunarity: f32, gain: f32) -> Self {
+        Self { lacunarity, gain }
+    }
+
+    /// Returns parameters with the given lacunarity, and the gain given by the Hurst exponent
+    /// `hurst`, i.e. `lacunarity^-hurst`.
+    pub fn from_hurst(lacunarity: f32, hurst: f32) -> Self {
+        Self::new(lacunarity, lacunarity.powf(-hurst))
+    }
+}
+
+impl Default for FbmParams {
+    /// Returns parameters with a lacunarity of 2.0 and a gain of 0.5.
+    fn default() -> Self {
+        Self::new(DEFAULT_LACUNARITY, 0.5)
+    }
+}
+
+/// The fractal [`render_to_colors`](struct.Noise.html#method.render_to_colors) samples.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct RenderParams {
+    /// The number of octaves; see [`fbm_with`](struct.Noise.html#method.fbm_with).
+    pub octaves: f32,
+    /// What is done to each octave's noise value.
+    pub transform: OctaveTransform,
+}
+
+impl RenderParams {
+    /// Returns parameters for rendering Fractal Brownian Motion with the given octaves.
+    pub fn fbm(octaves: f32) -> Self {
+        Self {
+            octaves,
+            transform: OctaveTransform::Identity,
+        }
+    }
+
+    /// Returns parameters for rendering turbulence with the given octaves.
+    pub fn turbulence(octaves: f32) -> Self {
+        Self {
+            octaves,
+            transform: OctaveTransform::Abs,
+        }
+    }
+}
+
 /// A struct representing a noise generator algorithm and its parameters.
 
 #[derive(Derivative)]
@@ -80,7 +184,10 @@ pub struct Noise<A: Algorithm> {
     algorithm: A,
     #[derivative(Debug = "ignore")]
     exponent: [f32; MAX_OCTAVES],
+    hurst: f32,
     lacunarity: f32,
+    /* the frequency multiplier of each axis */
+    scale: [f32; MAX_DIMENSIONS],
 }
 
 impl<A: Algorithm> Noise<A> {
@@ -96,31 +203,58 @@ impl<A: Algorithm> Noise<A> {
             "Number of coordinates given in 'f' must match the dimensions."
         );
 
-        self.algorithm.generate(f)
+        self.algorithm.generate(&self.scaled(f)[0..self.dimensions])
     }
 
     /// Returns the Fractal Brownian Motion function value between -1.0 and 1.0 at the given
-    /// coordinates, using the lacunarity defined when the noise generator was created.
+    /// coordinates, using the noise generator's Hurst exponent and lacunarity.
     /// The same array of coordinates will always return the same value.
     ///
     /// The octaves decide the number of iterations. Must be < `MAX_OCTAVES`, i.e. 128.
     ///
     /// # Panics
     /// If the `f` slice's length isn't equal to the `Noise`'s dimensions.
-    pub fn fbm(&self, f: &[f32], mut octaves: f32) -> f32 {
+    pub fn fbm(&self, f: &[f32], octaves: f32) -> f32 {
+        self.fbm_with(f, octaves, OctaveTransform::Identity)
+    }
+
+    /// Returns the turbulence function value between -1.0 and 1.0 at the given
+    /// coordinates, using the noise generator's Hurst exponent and lacunarity.
+    /// The same array of coordinates will always return the same value.
+    ///
+    /// The octaves decide the number of iterations. Must be < `MAX_OCTAVES`, i.e. 128.
+    ///
+    /// # Panics
+    /// If the `f` slice's length isn't equal to the `Noise`'s dimensions.
+    pub fn turbulence(&self, f: &[f32], octaves: f32) -> f32 {
+        self.fbm_with(f, octaves, OctaveTransform::Abs)
+    }
+
+    /// Returns the Fractal Brownian Motion function value between -1.0 and 1.0 at the given
+    /// coordinates, with each octave's noise value passed through the `transform` before it's
+    /// added. [`fbm`](#method.fbm) and [`turbulence`](#method.turbulence) are this with
+    /// [`OctaveTransform::Identity`] and [`OctaveTransform::Abs`], respectively.
+    ///
+    /// The octaves decide the number of iterations. Must be < `MAX_OCTAVES`, i.e. 128.
+    ///
+    /// [`OctaveTransform::Identity`]: enum.OctaveTransform.html#variant.Identity
+    /// [`OctaveTransform::Abs`]: enum.OctaveTransform.html#variant.Abs
+    ///
+    /// # Panics
+    /// If the `f` slice's length isn't equal to the `Noise`'s dimensions.
+    pub fn fbm_with(&self, f: &[f32], mut octaves: f32, transform: OctaveTransform) -> f32 {
         assert_eq!(
             self.dimensions,
             f.len(),
             "Number of coordinates given in 'f' must match the dimensions."
         );
 
-        let mut tf = [0.0_f32; MAX_DIMENSIONS];
-        tf[0..self.dimensions].copy_from_slice(f);
+        let mut tf = self.scaled(f);
 
         let mut value: f64 = 0.0;
         /* Inner loop of spectral construction, where the fractal is built */
         for &e in self.exponent.iter().take(octaves.trunc() as usize) {
-            value += f64::from(self.algorithm.generate(&tf)) * f64::from(e);
+            value += f64::from(transform.apply(self.algorithm.generate(&tf))) * f64::from(e);
             for tfe in tf.iter_mut().take(f.len()) {
                 *tfe *= self.lacunarity;
             }
@@ -130,57 +264,349 @@ impl<A: Algorithm> Noise<A> {
         let exp_i = octaves.trunc() as usize;
         octaves -= octaves.trunc();
         if octaves > DELTA {
-            value +=
-                f64::from(octaves * self.algorithm.generate(&tf)) * f64::from(self.exponent[exp_i]);
+            value += f64::from(octaves * transform.apply(self.algorithm.generate(&tf)))
+                * f64::from(self.exponent[exp_i]);
         }
 
-        value.max(-0.99999).min(0.99999) as f32
+        value.clamp(-0.99999, 0.99999) as f32
     }
 
-    /// Returns the turbulence function value between -1.0 and 1.0 at the given
-    /// coordinates, using the lacunarity defined when the noise generator was created.
+    /// Returns the ridged multifractal function value between 0.0 and 1.0 at the given
+    /// coordinates. The same array of coordinates will always return the same value.
+    ///
+    /// Each octave contributes `(1 - |n|)²`, weighted by the contribution of the previous
+    /// octave, which gives sharp ridges that are smooth in the valleys, like mountain ranges.
+    ///
+    /// The octaves decide the number of iterations; at least one octave is always computed.
+    ///
+    /// # Panics
+    /// If the `coords` slice's length isn't equal to the `Noise`'s dimensions.
+    pub fn get_fbm_ridged(&self, coords: &[f32], octaves: usize, params: FbmParams) -> f32 {
+        assert_eq!(
+            self.dimensions,
+            coords.len(),
+            "Number of coordinates given in 'coords' must match the dimensions."
+        );
+
+        let mut tf = self.scaled(coords);
+
+        let mut value = 0.0;
+        let mut total_amplitude = 0.0;
+        let mut amplitude = 1.0;
+        let mut weight = 1.0;
+        for _ in 0..octaves.max(1) {
+            let signal = 1.0 - self.algorithm.generate(&tf).abs();
+            let signal = signal * signal * weight;
+            /* Ridges are only kept where the previous octave had ridges too */
+            weight = signal.clamp(0.0, 1.0);
+
+            value += signal * amplitude;
+            total_amplitude += amplitude;
+            amplitude *= params.gain;
+            for tfe in tf.iter_mut().take(self.dimensions) {
+                *tfe *= params.lacunarity;
+            }
+        }
+
+        (value / total_amplitude).clamp(0.0, 1.0)
+    }
+
+    /// Returns the billow function value between -1.0 and 1.0 at the given coordinates.
     /// The same array of coordinates will always return the same value.
     ///
-    /// The octaves decide the number of iterations. Must be < `MAX_OCTAVES`, i.e. 128.
+    /// Each octave contributes `2|n| - 1`, which gives puffy, rounded shapes, like clouds.
+    ///
+    /// The octaves decide the number of iterations; at least one octave is always computed.
     ///
     /// # Panics
-    /// If the `f` slice's length isn't equal to the `Noise`'s dimensions.
-    pub fn turbulence(&self, f: &[f32], mut octaves: f32) -> f32 {
+    /// If the `coords` slice's length isn't equal to the `Noise`'s dimensions.
+    pub fn get_fbm_billow(&self, coords: &[f32], octaves: usize, params: FbmParams) -> f32 {
         assert_eq!(
             self.dimensions,
-            f.len(),
-            "Number of coordinates given in 'f' must match the dimensions."
+            coords.len(),
+            "Number of coordinates given in 'coords' must match the dimensions."
         );
 
-        let mut tf = [0.0_f32; MAX_DIMENSIONS];
-        tf[0..self.dimensions].copy_from_slice(f);
+        let mut tf = self.scaled(coords);
 
-        let mut value: f64 = 0.0;
-        /* Inner loop of spectral construction, where the fractal is built */
-        for &e in self.exponent.iter().take(octaves.trunc() as usize) {
-            value += f64::from(self.algorithm.generate(&tf).abs()) * f64::from(e);
-            for tfe in tf.iter_mut().take(f.len()) {
-                *tfe *= self.lacunarity;
+        let mut value = 0.0;
+        let mut total_amplitude = 0.0;
+        let mut amplitude = 1.0;
+        for _ in 0..octaves.max(1) {
+            let signal = 2.0 * self.algorithm.generate(&tf).abs() - 1.0;
+
+            value += signal * amplitude;
+            total_amplitude += amplitude;
+            amplitude *= params.gain;
+            for tfe in tf.iter_mut().take(self.dimensions) {
+                *tfe *= params.lacunarity;
             }
         }
 
-        /* Take care of remainder in octaves */
-        let exp_i = octaves.trunc() as usize;
-        octaves -= octaves.trunc();
-        if octaves > DELTA {
-            value += f64::from(octaves * self.algorithm.generate(&tf).abs())
-                * f64::from(self.exponent[exp_i]);
+        (value / total_amplitude).clamp(-1.0, 1.0)
+    }
+
+    /// Returns the noise function value between -1.0 and 1.0 at the given coordinates after
+    /// displacing them by the `warp` noise, which gives the noise a swirly, organic look.
+    ///
+    /// Each coordinate is moved by `strength` times the value of `warp` sampled at a position
+    /// offset differently for each axis, so the displacements along the axes are independent.
+    /// A `strength` of 0.0 gives the same value as [`flat`](#method.flat).
+    ///
+    /// # Panics
+    /// * If the `Noise` isn't 2D or 3D.
+    /// * If the `coords` slice's length isn't equal to the `Noise`'s dimensions.
+    /// * If the `warp` noise doesn't have the same dimensions as this `Noise`.
+    pub fn get_warped<B: Algorithm>(&self, coords: &[f32], warp: &Noise<B>, strength: f32) -> f32 {
+        assert!(
+            self.dimensions == 2 || self.dimensions == 3,
+            "Domain warping only supports 2 or 3 dimensions."
+        );
+        assert_eq!(
+            self.dimensions,
+            coords.len(),
+            "Number of coordinates given in 'coords' must match the dimensions."
+        );
+        assert_eq!(
+            self.dimensions, warp.dimensions,
+            "The 'warp' noise must have the same dimensions as this noise."
+        );
+
+        let coords = self.scaled(coords);
+        let mut warped = [0.0_f32; MAX_DIMENSIONS];
+        let mut shifted = [0.0_f32; MAX_DIMENSIONS];
+        for (i, offsets) in WARP_OFFSETS.iter().enumerate().take(self.dimensions) {
+            for ((s, &c), &o) in shifted.iter_mut().zip(&coords).zip(offsets) {
+                *s = c + o;
+            }
+            warped[i] = coords[i] + strength * warp.algorithm.generate(&shifted);
         }
 
-        value.max(-0.99999).min(0.99999) as f32
+        self.algorithm.generate(&warped[0..self.dimensions])
     }
 
-    fn new<R: RandomAlgorithm>(
-        mut dimensions: usize,
-        //hurst: f32,
-        lacunarity: f32,
-        random: Random<R>,
-    ) -> Self {
+    /// Fills `out` with the noise function values of a 2D grid of `size.0` by `size.1`
+    /// points, in row-major order.
+    ///
+    /// The value at `out[y * size.0 + x]` is the same as
+    /// `flat(&[origin.0 + x as f32 * step.0, origin.1 + y as f32 * step.1])`, but the checks are
+    /// done once for the whole grid rather than once per point.
+    ///
+    /// # Panics
+    /// * If the `Noise` isn't 2D.
+    /// * If `out.len()` isn't equal to `size.0 * size.1`.
+    pub fn get_grid_2d(
+        &self,
+        origin: (f32, f32),
+        step: (f32, f32),
+        size: (usize, usize),
+        out: &mut [f32],
+    ) {
+        assert_eq!(
+            self.dimensions, 2,
+            "get_grid_2d requires a 2D noise generator."
+        );
+        let (width, height) = size;
+        assert_eq!(
+            width * height,
+            out.len(),
+            "The length of 'out' must be equal to the width times the height of the grid."
+        );
+        if width == 0 {
+            return;
+        }
+
+        let (scale_x, scale_y) = (self.scale[0], self.scale[1]);
+        for (y, row) in out.chunks_exact_mut(width).enumerate() {
+            let fy = (origin.1 + y as f32 * step.1) * scale_y;
+            for (x, value) in row.iter_mut().enumerate() {
+                let fx = (origin.0 + x as f32 * step.0) * scale_x;
+                *value = self.algorithm.generate(&[fx, fy]);
+            }
+        }
+    }
+
+    /// Fills `out` with colors showing the fractal given by `params` over a 2D grid of `size.0`
+    /// by `size.1` points, in row-major order, which is handy for seeing what the noise
+    /// parameters look like.
+    ///
+    /// The points are the same as for [`get_grid_2d`](#method.get_grid_2d). The values are
+    /// normalized so that the lowest one in the grid becomes 0.0 and the highest 1.0, and each
+    /// value is then looked up in the `gradient` by linear interpolation, so that 0.0 gives its
+    /// first color and 1.0 its last. The `gradient` can be a handful of key colors, or one
+    /// generated by e.g. [`Color::generate_gradient_rgb`]. If every value is the same, the whole
+    /// grid gets the first color.
+    ///
+    /// [`Color::generate_gradient_rgb`]: ../color/struct.Color.html#method.generate_gradient_rgb
+    ///
+    /// # Panics
+    /// * If the `Noise` isn't 2D.
+    /// * If `out.len()` isn't equal to `size.0 * size.1`.
+    /// * If the `gradient` is empty.
+    pub fn render_to_colors(
+        &self,
+        origin: (f32, f32),
+        step: (f32, f32),
+        size: (usize, usize),
+        params: RenderParams,
+        gradient: &[Color],
+        out: &mut [Color],
+    ) {
+        assert_eq!(
+            self.dimensions, 2,
+            "render_to_colors requires a 2D noise generator."
+        );
+        let (width, height) = size;
+        assert_eq!(
+            width * height,
+            out.len(),
+            "The length of 'out' must be equal to the width times the height of the grid."
+        );
+        assert!(
+            !gradient.is_empty(),
+            "The gradient must have at least one color."
+        );
+
+        let values: Vec<f32> = (0..width * height)
+            .map(|i| {
+                let x = origin.0 + (i % width) as f32 * step.0;
+                let y = origin.1 + (i / width) as f32 * step.1;
+                self.fbm_with(&[x, y], params.octaves, params.transform)
+            })
+            .collect();
+        let (min, max) = values
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &v| {
+                (min.min(v), max.max(v))
+            });
+
+        let last = (gradient.len() - 1) as f32;
+        for (color, &value) in out.iter_mut().zip(&values) {
+            let normalized = if max > min {
+                (value - min) / (max - min)
+            } else {
+                0.0
+            };
+            let position = normalized * last;
+            let index = (position as usize).min(gradient.len() - 1);
+            *color = match gradient.get(index + 1) {
+                Some(&next) => gradient[index].lerp_rgb(next, position - index as f32),
+                None => gradient[index],
+            };
+        }
+    }
+
+    /// Returns a noise function value between -1.0 and 1.0 that tiles seamlessly, repeating
+    /// itself every `periods[i]` units along coordinate `i`.
+    ///
+    /// Each coordinate is mapped onto a circle whose circumference is its period, so the noise
+    /// is evaluated on a torus with twice as many dimensions as there are coordinates. The
+    /// `Noise` must therefore have been created with twice the number of dimensions in `coords`,
+    /// e.g. a 4D noise generator for 2D seamless noise.
+    ///
+    /// The [`scale`](#method.set_scale) isn't applied, since the periods already decide the
+    /// size of the features.
+    ///
+    /// # Panics
+    /// * If the `coords` and `periods` slices don't have the same length.
+    /// * If the `Noise`'s dimensions aren't twice the length of `coords`.
+    /// * If any of the `periods` isn't positive.
+    pub fn get_seamless(&self, coords: &[f32], periods: &[f32]) -> f32 {
+        assert_eq!(
+            coords.len(),
+            periods.len(),
+            "Number of periods given in 'periods' must match the number of coordinates."
+        );
+        assert_eq!(
+            self.dimensions,
+            2 * coords.len(),
+            "Seamless noise requires twice as many dimensions as coordinates given in 'coords'."
+        );
+        assert!(
+            periods.iter().all(|&p| p > 0.0),
+            "All the periods given in 'periods' must be positive."
+        );
+
+        let mut tf = [0.0_f32; MAX_DIMENSIONS];
+        for (i, (&c, &p)) in Iterator::zip(coords.iter(), periods.iter()).enumerate() {
+            let angle = f64::from(c) / f64::from(p) * 2.0 * std::f64::consts::PI;
+            let radius = f64::from(p) / (2.0 * std::f64::consts::PI);
+            tf[2 * i] = (radius * angle.cos()) as f32;
+            tf[2 * i + 1] = (radius * angle.sin()) as f32;
+        }
+
+        self.algorithm.generate(&tf[0..self.dimensions])
+    }
+
+    /// Returns the number of dimensions of the noise, which is the number of coordinates every
+    /// method expects to be given.
+    pub fn dimensions(&self) -> u32 {
+        /* at most MAX_DIMENSIONS, so this never truncates */
+        self.dimensions as u32
+    }
+
+    /// Returns the frequency multiplier of each axis; see [`set_scale`](#method.set_scale).
+    pub fn scale(&self) -> &[f32] {
+        &self.scale[0..self.dimensions]
+    }
+
+    /// Sets the frequency multiplier of each axis, which the coordinates given to every method
+    /// but [`get_seamless`](#method.get_seamless) are multiplied by before the noise is
+    /// evaluated. A larger scale along an axis makes the features smaller along it; the default
+    /// scale of 1.0 along every axis leaves the coordinates as they are.
+    ///
+    /// # Panics
+    /// If the `scale` slice's length isn't equal to the `Noise`'s dimensions.
+    pub fn set_scale(&mut self, scale: &[f32]) {
+        assert_eq!(
+            self.dimensions,
+            scale.len(),
+            "Number of factors given in 'scale' must match the dimensions."
+        );
+
+        self.scale[0..self.dimensions].copy_from_slice(scale);
+    }
+
+    /* the coordinates multiplied by the scale, padded with zeroes */
+    fn scaled(&self, coords: &[f32]) -> [f32; MAX_DIMENSIONS] {
+        let mut scaled = [0.0_f32; MAX_DIMENSIONS];
+        for ((s, &c), &factor) in scaled.iter_mut().zip(coords).zip(&self.scale) {
+            *s = c * factor;
+        }
+
+        scaled
+    }
+
+    /// Returns the Hurst exponent used by [`fbm`](#method.fbm) and
+    /// [`turbulence`](#method.turbulence).
+    pub fn hurst(&self) -> f32 {
+        self.hurst
+    }
+
+    /// Sets the Hurst exponent used by [`fbm`](#method.fbm) and
+    /// [`turbulence`](#method.turbulence). Each octave's amplitude is its frequency raised to
+    /// the power of `-hurst`, so higher values give smoother noise. Defaults to
+    /// [`DEFAULT_HURST`](constant.DEFAULT_HURST.html).
+    pub fn set_hurst(&mut self, hurst: f32) {
+        self.hurst = hurst;
+        self.exponent = Self::exponent(hurst, self.lacunarity);
+    }
+
+    /// Returns the lacunarity used by [`fbm`](#method.fbm) and
+    /// [`turbulence`](#method.turbulence).
+    pub fn lacunarity(&self) -> f32 {
+        self.lacunarity
+    }
+
+    /// Sets the lacunarity used by [`fbm`](#method.fbm) and
+    /// [`turbulence`](#method.turbulence), i.e. the factor the frequency is multiplied with for
+    /// each octave.
+    pub fn set_lacunarity(&mut self, lacunarity: f32) {
+        self.lacunarity = lacunarity;
+        self.exponent = Self::exponent(self.hurst, lacunarity);
+    }
+
+    fn new<R: RandomAlgorithm>(mut dimensions: usize, lacunarity: f32, random: Random<R>) -> Self {
         dimensions = dimensions.min(4);
 
         let initializer = AlgorithmInitializer::new(random);
@@ -188,16 +614,18 @@ impl<A: Algorithm> Noise<A> {
         Self {
             dimensions,
             algorithm: A::new(dimensions, initializer),
-            exponent: Self::exponent(lacunarity),
+            exponent: Self::exponent(DEFAULT_HURST, lacunarity),
+            hurst: DEFAULT_HURST,
             lacunarity,
+            scale: [1.0; MAX_DIMENSIONS],
         }
     }
 
-    fn exponent(lacunarity: f32) -> [f32; MAX_OCTAVES] {
+    fn exponent(hurst: f32, lacunarity: f32) -> [f32; MAX_OCTAVES] {
         let mut exponent = [0.0; MAX_OCTAVES];
-        let mut f = 1.0;
-        for e in exponent.iter_mut() {
-            *e = 1.0 / f;
+        let mut f = 1.0_f32;
+        for e in &mut exponent {
+            *e = f.powf(-hurst);
             f *= lacunarity;
         }
 
@@ -205,6 +633,32 @@ impl<A: Algorithm> Noise<A> {
     }
 }
 
+impl<A: DerivativeAlgorithm> Noise<A> {
+    /// Returns the noise function value between -1.0 and 1.0 at the given coordinates, like
+    /// [`flat`](#method.flat), together with its partial derivatives with respect to each of the
+    /// coordinates. The returned `Vec` has as many elements as there are dimensions.
+    ///
+    /// # Panics
+    /// If the `coords` slice's length isn't equal to the `Noise`'s dimensions.
+    pub fn get_with_derivative(&self, coords: &[f32]) -> (f32, Vec<f32>) {
+        assert_eq!(
+            self.dimensions,
+            coords.len(),
+            "Number of coordinates given in 'coords' must match the dimensions."
+        );
+
+        let (value, mut derivative) = self
+            .algorithm
+            .generate_with_derivative(&self.scaled(coords)[0..self.dimensions]);
+        /* The chain rule brings the scale back out of the derivative */
+        for (d, &factor) in derivative.iter_mut().zip(&self.scale) {
+            *d *= factor;
+        }
+
+        (value, derivative)
+    }
+}
+
 impl Noise<Perlin> {
     /// Initializes a Perlin noise generator with the given number of dimensions (from 1 to 4),
     /// the lacunarity parameter and a random number generator.
@@ -230,13 +684,498 @@ impl Noise<Simplex> {
 }
 
 impl Noise<Wavelet> {
-    /// Initializes a Wavelet noise generator with the given number of dimensions (from 1 to 4),
+    /// Initializes a Wavelet noise generator with the given number of dimensions (from 1 to 3),
     /// the lacunarity parameter and a random number generator.
+    ///
+    /// # Panics
+    /// If `dimensions` isn't between 1 and 3. Use [`try_new_wavelet`](#method.try_new_wavelet)
+    /// to handle this case without panicking.
     pub fn new_wavelet<R: RandomAlgorithm>(
         dimensions: usize,
         lacunarity: f32,
         random: Random<R>,
     ) -> Self {
-        Self::new(dimensions, lacunarity, random)
+        match Self::try_new_wavelet(dimensions, lacunarity, random) {
+            Ok(noise) => noise,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Initializes a Wavelet noise generator like [`new_wavelet`](#method.new_wavelet), but
+    /// returns an error instead of panicking if `dimensions` isn't between 1 and 3.
+    pub fn try_new_wavelet<R: RandomAlgorithm>(
+        dimensions: usize,
+        lacunarity: f32,
+        random: Random<R>,
+    ) -> Result<Self, UnsupportedDimensionsError> {
+        if (1..=3).contains(&dimensions) {
+            Ok(Self::new(dimensions, lacunarity, random))
+        } else {
+            Err(UnsupportedDimensionsError {
+                dimensions,
+                supported: 1..=3,
+            })
+        }
+    }
+}
+
+/// The error type returned when a noise algorithm doesn't support the requested number of
+/// dimensions.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnsupportedDimensionsError {
+    /// The number of dimensions requested.
+    pub dimensions: usize,
+    /// The numbers of dimensions the algorithm supports.
+    pub supported: std::ops::RangeInclusive<usize>,
+}
+
+impl std::fmt::Display for UnsupportedDimensionsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} dimensions are not supported; this noise supports {} to {} dimensions",
+            self.dimensions,
+            self.supported.start(),
+            self.supported.end()
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedDimensionsError {}
+
+/// A cellular noise generator.
+pub type CellularNoise = Noise<Cellular>;
+
+impl Noise<Cellular> {
+    /// Initializes a cellular noise generator with the given number of dimensions (2 or 3),
+    /// the lacunarity parameter, the distance function and metric to use and a random number
+    /// generator.
+    ///
+    /// # Panics
+    /// If `dimensions` is neither 2 nor 3.
+    pub fn new_cellular<R: RandomAlgorithm>(
+        dimensions: usize,
+        lacunarity: f32,
+        function: CellularFunction,
+        metric: DistanceMetric,
+        random: Random<R>,
+    ) -> Self {
+        assert!(
+            dimensions == 2 || dimensions == 3,
+            "Cellular noise only supports 2 or 3 dimensions."
+        );
+
+        let mut noise = Self::new(dimensions, lacunarity, random);
+        noise.algorithm.function = function;
+        noise.algorithm.metric = metric;
+        noise
+    }
+
+    /// Returns the cellular noise function value between 0.0 and 1.0 at the given coordinates.
+    /// The same array of coordinates will always return the same value.
+    ///
+    /// # Panics
+    /// If the `coords` slice's length isn't equal to the `Noise`'s dimensions.
+    pub fn get(&self, coords: &[f32]) -> f32 {
+        self.flat(coords)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::noise::algorithms::DerivativeAlgorithm;
+    use crate::noise::{
+        FbmParams, Noise, OctaveTransform, RenderParams, UnsupportedDimensionsError,
+    };
+    use crate::random::{Random, Rng};
+
+    fn check_derivative<A: DerivativeAlgorithm>(noise: &Noise<A>, dimensions: usize) {
+        const H: f32 = 1.0e-3;
+        const EPSILON: f32 = 1.0e-2;
+
+        let mut random = Random::new_mt_from_seed(42);
+        for _ in 0..200 {
+            /* Stay clear of the lattice cell boundaries, which libtcod's 4D Perlin noise
+            isn't continuous across. */
+            let coords: Vec<f32> = (0..dimensions)
+                .map(|_| random.get_i32(-50, 50) as f32 + random.get_f32(0.1, 0.9))
+                .collect();
+            let (value, derivative) = noise.get_with_derivative(&coords);
+            assert_eq!(noise.flat(&coords), value);
+            assert_eq!(dimensions, derivative.len());
+
+            for (i, &d) in derivative.iter().enumerate() {
+                let mut above = coords.clone();
+                above[i] += H;
+                let mut below = coords.clone();
+                below[i] -= H;
+                let estimate = (noise.flat(&above) - noise.flat(&below)) / (above[i] - below[i]);
+                assert!(
+                    (estimate - d).abs() < EPSILON,
+                    "derivative {} at {:?} was {}, estimated {}",
+                    i,
+                    coords,
+                    d,
+                    estimate
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn render_to_colors_follows_the_gradient() {
+        let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(7));
+        let params = RenderParams::fbm(3.0);
+        let (origin, step, size) = ((0.5, -1.0), (0.3, 0.2), (8, 6));
+        let gradient = [Color::BLACK, Color::WHITE];
+
+        let mut colors = [Color::RED; 48];
+        noise.render_to_colors(origin, step, size, params, &gradient, &mut colors);
+
+        let values: Vec<f32> = (0..48)
+            .map(|i| {
+                let x = origin.0 + (i % 8) as f32 * step.0;
+                let y = origin.1 + (i / 8) as f32 * step.1;
+                noise.fbm(&[x, y], 3.0)
+            })
+            .collect();
+        let lowest = (0..48)
+            .min_by(|&a, &b| values[a].total_cmp(&values[b]))
+            .unwrap();
+        let highest = (0..48)
+            .max_by(|&a, &b| values[a].total_cmp(&values[b]))
+            .unwrap();
+        assert_eq!(Color::BLACK, colors[lowest]);
+        assert_eq!(Color::WHITE, colors[highest]);
+        for a in 0..48 {
+            for b in 0..48 {
+                if values[a] < values[b] {
+                    assert!(colors[a].r <= colors[b].r);
+                }
+            }
+        }
+
+        let mut turbulent = [Color::RED; 48];
+        let single = [Color::BLUE];
+        let turbulence = RenderParams::turbulence(3.0);
+        noise.render_to_colors(origin, step, size, turbulence, &single, &mut turbulent);
+        assert!(turbulent.iter().all(|&c| c == Color::BLUE));
+    }
+
+    #[test]
+    fn perlin_derivative_matches_central_difference() {
+        for dimensions in 1..=4 {
+            let noise = Noise::new_perlin(dimensions, 2.0, Random::new_mt_from_seed(1));
+            check_derivative(&noise, dimensions);
+        }
+    }
+
+    #[test]
+    fn simplex_derivative_matches_central_difference() {
+        for dimensions in 1..=4 {
+            let noise = Noise::new_simplex(dimensions, 2.0, Random::new_mt_from_seed(1));
+            check_derivative(&noise, dimensions);
+        }
+    }
+
+    #[test]
+    fn ridged_and_billow_stay_in_range() {
+        let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(1));
+        let params = FbmParams::from_hurst(2.0, 0.8);
+        let mut ridged = Vec::new();
+        for i in 0..500 {
+            let coords = [i as f32 * 0.173, i as f32 * -0.311];
+            let ridged_value = noise.get_fbm_ridged(&coords, 6, params);
+            assert!((0.0..=1.0).contains(&ridged_value), "{}", ridged_value);
+            ridged.push(ridged_value);
+
+            let billow_value = noise.get_fbm_billow(&coords, 6, params);
+            assert!((-1.0..=1.0).contains(&billow_value), "{}", billow_value);
+        }
+        assert!(ridged.iter().any(|&v| (v - ridged[0]).abs() > 0.01));
+    }
+
+    #[test]
+    fn zero_octaves_gives_the_single_octave_value() {
+        let noise = Noise::new_perlin(3, 2.0, Random::new_mt_from_seed(1));
+        let params = FbmParams::default();
+        for i in 0..50 {
+            let coords = [i as f32 * 0.37, 1.5, i as f32 * -0.21];
+            let n = noise.flat(&coords);
+
+            let ridged = noise.get_fbm_ridged(&coords, 0, params);
+            assert_eq!(noise.get_fbm_ridged(&coords, 1, params), ridged);
+            assert!((ridged - (1.0 - n.abs()) * (1.0 - n.abs())).abs() < 1.0e-6);
+
+            let billow = noise.get_fbm_billow(&coords, 0, params);
+            assert_eq!(noise.get_fbm_billow(&coords, 1, params), billow);
+            assert!((billow - (2.0 * n.abs() - 1.0)).abs() < 1.0e-6);
+        }
+    }
+
+    #[test]
+    fn warping_changes_the_noise_only_with_strength() {
+        let noise = Noise::new_simplex(3, 2.0, Random::new_mt_from_seed(1));
+        let warp = Noise::new_perlin(3, 2.0, Random::new_mt_from_seed(2));
+        let mut changed = false;
+        for i in 0..50 {
+            let coords = [i as f32 * 0.37, 1.5, i as f32 * -0.21];
+            let value = noise.flat(&coords);
+            assert_eq!(value, noise.get_warped(&coords, &warp, 0.0));
+            changed |= (value - noise.get_warped(&coords, &warp, 4.0)).abs() > 1.0e-3;
+        }
+        assert!(changed);
+    }
+
+    #[test]
+    fn fbm_and_turbulence_values_are_unchanged() {
+        let perlin = Noise::new_perlin(2, 2.0, Random::new_mt_from_seed(1234));
+        let perlin_coords = [1.3, 2.7];
+        let simplex = Noise::new_simplex(3, 2.0, Random::new_mt_from_seed(99));
+        let simplex_coords = [2.9, -1.4, 0.6];
+        let expected = if cfg!(feature = "libtcod-compat") {
+            [0.223_502_03, 0.458_841_26, -0.595_004_5, 0.595_004_5]
+        } else {
+            [0.084_721_72, 0.188_027_75, -0.044_953_622, 0.750_972]
+        };
+        assert_eq!(
+            expected,
+            [
+                perlin.fbm(&perlin_coords, 4.5),
+                perlin.turbulence(&perlin_coords, 4.5),
+                simplex.fbm(&simplex_coords, 3.0),
+                simplex.turbulence(&simplex_coords, 3.0),
+            ]
+        );
+
+        assert_eq!(
+            simplex.fbm(&simplex_coords, 3.0),
+            simplex.fbm_with(&simplex_coords, 3.0, OctaveTransform::Identity)
+        );
+        assert_eq!(
+            simplex.turbulence(&simplex_coords, 3.0),
+            simplex.fbm_with(&simplex_coords, 3.0, OctaveTransform::Abs)
+        );
+    }
+
+    #[test]
+    fn scale_changes_the_feature_size_per_axis() {
+        let unscaled = Noise::new_perlin(2, 2.0, Random::new_mt_from_seed(5));
+        let mut ones = Noise::new_perlin(2, 2.0, Random::new_mt_from_seed(5));
+        ones.set_scale(&[1.0, 1.0]);
+        let mut scaled = Noise::new_perlin(2, 2.0, Random::new_mt_from_seed(5));
+        scaled.set_scale(&[2.0, 1.0]);
+        assert_eq!([2.0, 1.0], scaled.scale());
+
+        let mut random = Random::new_mt_from_seed(42);
+        for _ in 0..100 {
+            let (x, y) = (random.get_f32(-10.0, 10.0), random.get_f32(-10.0, 10.0));
+            assert_eq!(unscaled.flat(&[x, y]), ones.flat(&[x, y]));
+            assert_eq!(unscaled.fbm(&[x, y], 3.0), ones.fbm(&[x, y], 3.0));
+
+            /* Features repeat twice as often along x, and just as often along y */
+            assert_eq!(unscaled.flat(&[2.0 * x, y]), scaled.flat(&[x, y]));
+            assert_eq!(
+                unscaled.turbulence(&[2.0 * x, y], 3.0),
+                scaled.turbulence(&[x, y], 3.0)
+            );
+            assert_eq!(unscaled.flat(&[0.0, y]), scaled.flat(&[0.0, y]));
+        }
+
+        let mut grid = [0.0; 6];
+        scaled.get_grid_2d((0.5, 0.25), (0.5, 0.5), (3, 2), &mut grid);
+        assert_eq!(scaled.flat(&[1.5, 0.75]), grid[5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "must match the dimensions")]
+    fn scale_must_match_the_dimensions() {
+        let mut noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(1));
+        noise.set_scale(&[1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn coordinates_must_match_the_dimensions() {
+        let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(1));
+        assert_eq!(2, noise.dimensions());
+        assert_eq!(
+            3,
+            Noise::new_perlin(3, 2.0, Random::new_mt_from_seed(1)).dimensions()
+        );
+
+        let coords = [0.5, 1.5];
+        assert!((-1.0..=1.0).contains(&noise.flat(&coords)));
+        assert!((-1.0..=1.0).contains(&noise.fbm(&coords, 4.0)));
+        assert!((-1.0..=1.0).contains(&noise.turbulence(&coords, 4.0)));
+
+        for result in &[
+            std::panic::catch_unwind(|| noise.flat(&[0.5, 1.5, 2.5])),
+            std::panic::catch_unwind(|| noise.fbm(&[0.5, 1.5, 2.5], 4.0)),
+            std::panic::catch_unwind(|| noise.turbulence(&[0.5], 4.0)),
+        ] {
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "must match the dimensions")]
+    fn extra_coordinates_are_rejected() {
+        let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(1));
+        let _ = noise.flat(&[0.5, 1.5, 2.5]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn warping_requires_matching_dimensions() {
+        let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(1));
+        let warp = Noise::new_simplex(3, 2.0, Random::new_mt_from_seed(2));
+        let _ = noise.get_warped(&[1.0, 2.0], &warp, 1.0);
+    }
+
+    #[test]
+    fn grid_matches_individual_samples() {
+        const SIZE: usize = 16;
+
+        let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(1));
+        let (origin, step) = ((-3.5, 10.25), (0.3, -0.7));
+        let mut grid = vec![0.0; SIZE * SIZE];
+        noise.get_grid_2d(origin, step, (SIZE, SIZE), &mut grid);
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let coords = [origin.0 + x as f32 * step.0, origin.1 + y as f32 * step.1];
+                assert_eq!(noise.flat(&coords), grid[y * SIZE + x]);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The length of 'out' must be equal")]
+    fn grid_requires_matching_output_length() {
+        let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(1));
+        let mut grid = vec![0.0; 10];
+        noise.get_grid_2d((0.0, 0.0), (1.0, 1.0), (4, 4), &mut grid);
+    }
+
+    #[test]
+    fn seamless_noise_repeats_with_its_period() {
+        const PERIOD: f32 = 16.0;
+
+        let noise = Noise::new_simplex(4, 2.0, Random::new_mt_from_seed(1));
+        for &y in &[0.0, 1.5, 3.25] {
+            let start = noise.get_seamless(&[0.0, y], &[PERIOD, 8.0]);
+            let end = noise.get_seamless(&[PERIOD, y], &[PERIOD, 8.0]);
+            assert!((start - end).abs() < 1.0e-4, "{} != {}", start, end);
+
+            let values: Vec<f32> = (0..64)
+                .map(|i| noise.get_seamless(&[i as f32 * PERIOD / 64.0, y], &[PERIOD, 8.0]))
+                .collect();
+            assert!(values.iter().any(|&v| (v - start).abs() > 0.01));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn seamless_requires_matching_periods() {
+        let noise = Noise::new_perlin(4, 2.0, Random::new_mt_from_seed(1));
+        let _ = noise.get_seamless(&[1.0, 2.0], &[4.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn seamless_requires_positive_periods() {
+        let noise = Noise::new_perlin(2, 2.0, Random::new_mt_from_seed(1));
+        let _ = noise.get_seamless(&[1.0], &[0.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn derivative_requires_matching_dimensions() {
+        let noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(1));
+        let _ = noise.get_with_derivative(&[1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn wavelet_rejects_unsupported_dimensions() {
+        for &dimensions in &[0, 4] {
+            let error = Noise::try_new_wavelet(dimensions, 2.0, Random::new_mt_from_seed(1));
+            assert_eq!(
+                Some(UnsupportedDimensionsError {
+                    dimensions,
+                    supported: 1..=3
+                }),
+                error.err()
+            );
+        }
+        assert!(Noise::try_new_wavelet(1, 2.0, Random::new_mt_from_seed(1)).is_ok());
+    }
+
+    #[test]
+    fn wavelet_3d_stays_finite_and_in_range() {
+        let noise = Noise::new_wavelet(3, 2.0, Random::new_mt_from_seed(9));
+        let mut max = 0.0_f32;
+        for z in 0..40 {
+            for y in 0..40 {
+                for x in 0..40 {
+                    let coords = [x as f32 * 0.43, y as f32 * 0.43, z as f32 * 0.43 - 8.0];
+                    let value = noise.flat(&coords);
+                    assert!(value.is_finite() && (-1.0..=1.0).contains(&value));
+                    max = max.max(value.abs());
+                }
+            }
+        }
+        assert!(max > 0.1);
+
+        for &far in &[1.0e6, -3.0e7, 1.0e30] {
+            let value = noise.flat(&[far, far, far]);
+            assert!(value.is_finite() && (-1.0..=1.0).contains(&value));
+        }
+    }
+
+    #[test]
+    fn wavelet_is_continuous_across_tile_edges() {
+        /* the 32 cell tiles are scaled down by 2 */
+        const EDGE: f32 = 16.0;
+        const EPSILON: f32 = 1.0e-3;
+
+        let noise = Noise::new_wavelet(3, 2.0, Random::new_mt_from_seed(4));
+        for &(y, z) in &[(0.3, 5.1), (EDGE, 2.2), (-EDGE, EDGE)] {
+            for &edge in &[0.0, EDGE, -EDGE, 2.0 * EDGE] {
+                let below = noise.flat(&[edge - EPSILON, y, z]);
+                let above = noise.flat(&[edge + EPSILON, y, z]);
+                assert!((below - above).abs() < 0.01, "{} vs {}", below, above);
+            }
+            assert!((noise.flat(&[0.7, y, z]) - noise.flat(&[0.7 + EDGE, y, z])).abs() < 1.0e-5);
+        }
+    }
+
+    #[test]
+    fn fbm_uses_the_hurst_exponent_and_lacunarity() {
+        let coords = [1.3, 4.2];
+        let mut noise = Noise::new_simplex(2, 2.0, Random::new_mt_from_seed(8));
+
+        /* the default Hurst exponent of 1 weighs each octave by the inverse of its frequency */
+        let by_hand: f32 = (0..4)
+            .map(|i| {
+                let f = 2.0_f32.powi(i);
+                noise.flat(&[coords[0] * f, coords[1] * f]) / f
+            })
+            .sum();
+        let fbm = noise.fbm(&coords, 4.0);
+        assert!((by_hand.clamp(-0.99999, 0.99999) - fbm).abs() < 1.0e-5);
+
+        noise.set_lacunarity(3.0);
+        assert_eq!(3.0, noise.lacunarity());
+        let other = Noise::new_simplex(2, 3.0, Random::new_mt_from_seed(8));
+        assert_eq!(other.fbm(&coords, 4.0), noise.fbm(&coords, 4.0));
+        assert_ne!(fbm, noise.fbm(&coords, 4.0));
+
+        noise.set_lacunarity(2.0);
+        assert_eq!(fbm, noise.fbm(&coords, 4.0));
+        let turbulence = noise.turbulence(&coords, 2.0);
+        noise.set_hurst(0.5);
+        assert_eq!(0.5, noise.hurst());
+        assert_ne!(fbm, noise.fbm(&coords, 4.0));
+        assert_ne!(turbulence, noise.turbulence(&coords, 2.0));
     }
 }
@@ -33,10 +33,12 @@
 
 //! Noise generator algorithms.
 
+mod cellular;
 mod perlin;
 mod simplex;
 mod wavelet;
 
+pub use cellular::{Cellular, CellularFunction, DistanceMetric};
 pub use perlin::Perlin;
 pub use simplex::Simplex;
 pub use wavelet::Wavelet;
@@ -55,6 +57,13 @@ pub trait Algorithm {
     fn generate(&self, f: &[f32]) -> f32;
 }
 
+/// Noise algorithm trait for algorithms that can compute their analytic partial derivatives.
+pub trait DerivativeAlgorithm: Algorithm {
+    /// Generates the noise value at the given coordinates, along with its partial derivative
+    /// with respect to each of the coordinates.
+    fn generate_with_derivative(&self, f: &[f32]) -> (f32, Vec<f32>);
+}
+
 /// Noise algorithm initializer.
 #[derive(Debug)]
 pub struct AlgorithmInitializer<R: RandomAlgorithm> {
@@ -0,0 +1,346 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::noise::algorithms::AlgorithmInitializer;
+use crate::noise::{Algorithm, MAX_DIMENSIONS};
+use crate::random::algorithms::Algorithm as RandomAlgorithm;
+use crate::random::Rng;
+use derivative::Derivative;
+use ilyvion_util::multi_dimensional::Window2D;
+
+/// The value cellular noise computes from the distances to the nearest feature points.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum CellularFunction {
+    /// The distance to the nearest feature point.
+    F1,
+    /// The distance to the second nearest feature point.
+    F2,
+    /// The difference between the distances to the second nearest and the nearest feature
+    /// points, which gives the noise a cracked, cell wall-like look.
+    F2MinusF1,
+}
+
+/// The metric used to measure the distance to feature points in cellular noise.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum DistanceMetric {
+    /// The straight line distance, giving round cells.
+    Euclidean,
+    /// The sum of the distances along each axis, giving diamond shaped cells.
+    Manhattan,
+    /// The largest of the distances along each axis, giving square cells.
+    Chebyshev,
+}
+
+impl DistanceMetric {
+    fn distance(self, a: &[f32], b: &[f32]) -> f32 {
+        let deltas = Iterator::zip(a.iter(), b.iter()).map(|(a, b)| (a - b).abs());
+        match self {
+            Self::Euclidean => deltas.map(|d| d * d).sum::<f32>().sqrt(),
+            Self::Manhattan => deltas.sum(),
+            Self::Chebyshev => deltas.fold(0.0, f32::max),
+        }
+    }
+
+    /* The largest distance possible between a point and the feature point of its own cell,
+    which is what bounds F1, and is used to bring the noise values into [0, 1]. */
+    fn max_distance(self, dimensions: usize) -> f32 {
+        match self {
+            Self::Euclidean => (dimensions as f32).sqrt(),
+            Self::Manhattan => dimensions as f32,
+            Self::Chebyshev => 1.0,
+        }
+    }
+}
+
+/// Cellular (Worley) noise algorithm.
+///
+/// Every unit cell of the noise space has a single feature point placed randomly within it,
+/// and the noise value depends on the distances to the feature points nearest to the given
+/// coordinates. Unlike the other algorithms, the values are between 0.0 and 1.0, and only 2 and 3
+/// dimensions are supported.
+#[derive(Clone, Copy, Derivative)]
+#[derivative(Debug)]
+pub struct Cellular {
+    dimensions: usize,
+    pub(crate) function: CellularFunction,
+    pub(crate) metric: DistanceMetric,
+    /** Randomized map of indexes into offsets */
+    #[derivative(Debug = "ignore")]
+    map: [u8; 256],
+    /** Random 256 x ndim buffer of feature point offsets within their cells */
+    #[derivative(Debug = "ignore")]
+    offsets: [f32; MAX_DIMENSIONS * 256],
+}
+
+impl Cellular {
+    fn feature_point(&self, cell: [i32; 3]) -> [f32; 3] {
+        let mut n_index = 0;
+        for &ni in cell.iter().take(self.dimensions) {
+            n_index = i32::from(self.map[((n_index + ni) & 0xFF) as usize]);
+        }
+        let offsets_window = Window2D::new_ref_unchecked(&self.offsets, 256, MAX_DIMENSIONS);
+        let offsets = &offsets_window[n_index as usize];
+
+        let mut point = [0.0; 3];
+        for i in 0..self.dimensions {
+            point[i] = cell[i] as f32 + offsets[i];
+        }
+        point
+    }
+
+    /* The shortest distance from the point to anywhere within the cell */
+    fn distance_to_cell(&self, point: &[f32; 3], cell: [i32; 3]) -> f32 {
+        let mut nearest = [0.0; 3];
+        for i in 0..self.dimensions {
+            nearest[i] = point[i].clamp(cell[i] as f32, (cell[i] + 1) as f32);
+        }
+        self.metric
+            .distance(&point[..self.dimensions], &nearest[..self.dimensions])
+    }
+}
+
+impl Algorithm for Cellular {
+    fn new<R: RandomAlgorithm>(
+        dimensions: usize,
+        mut initializer: AlgorithmInitializer<R>,
+    ) -> Self {
+        let map = initializer.map();
+        let mut offsets = [0.0; MAX_DIMENSIONS * 256];
+        for offset in &mut offsets {
+            *offset = initializer.random.get_f32(0.0, 1.0);
+        }
+
+        Self {
+            dimensions,
+            function: CellularFunction::F1,
+            metric: DistanceMetric::Euclidean,
+            map,
+            offsets,
+        }
+    }
+
+    fn generate(&self, f: &[f32]) -> f32 {
+        if self.dimensions < 2 || self.dimensions > 3 {
+            panic!("Cellular noise only supports 2 or 3 dimensions");
+        }
+
+        let mut point = [0.0; 3];
+        point[..self.dimensions].copy_from_slice(&f[..self.dimensions]);
+        let mut cell = [0; 3];
+        for i in 0..self.dimensions {
+            cell[i] = point[i].floor() as i32;
+        }
+
+        /* A feature point two or more cells away can be nearer than those of the neighboring
+        cells, so the cells are searched in rings of growing distance around the point's own
+        cell, skipping the cells that are too far away to hold anything nearer than F2, until
+        the next ring is too far away as a whole. Every cell in ring `r` is at least `r - 1`
+        cells beyond the nearest face of the point's own cell along some axis, which no metric
+        makes shorter. */
+        let nearest_face = (0..self.dimensions)
+            .map(|i| {
+                let fraction = point[i] - cell[i] as f32;
+                fraction.min(1.0 - fraction)
+            })
+            .fold(f32::MAX, f32::min);
+        let mut f1 = f32::MAX;
+        let mut f2 = f32::MAX;
+        let mut ring = 0_i32;
+        while nearest_face + ((ring - 1) as f32) < f2 {
+            let z_ring = if self.dimensions == 3 { ring } else { 0 };
+            for dz in -z_ring..=z_ring {
+                for dy in -ring..=ring {
+                    for dx in -ring..=ring {
+                        if dx.abs().max(dy.abs()).max(dz.abs()) != ring {
+                            continue;
+                        }
+                        let neighbor = [cell[0] + dx, cell[1] + dy, cell[2] + dz];
+                        if self.distance_to_cell(&point, neighbor) >= f2 {
+                            continue;
+                        }
+
+                        let feature_point = self.feature_point(neighbor);
+                        let distance = self
+                            .metric
+                            .distance(&point[..self.dimensions], &feature_point[..self.dimensions]);
+                        if distance < f1 {
+                            f2 = f1;
+                            f1 = distance;
+                        } else if distance < f2 {
+                            f2 = distance;
+                        }
+                    }
+                }
+            }
+            ring += 1;
+        }
+
+        let value = match self.function {
+            CellularFunction::F1 => f1,
+            CellularFunction::F2 => f2,
+            CellularFunction::F2MinusF1 => f2 - f1,
+        };
+
+        (value / self.metric.max_distance(self.dimensions)).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::noise::algorithms::{
+        AlgorithmInitializer, Cellular, CellularFunction, DistanceMetric,
+    };
+    use crate::noise::{Algorithm, Noise};
+    use crate::random::Random;
+
+    #[test]
+    fn f1_is_zero_at_feature_points() {
+        for &metric in &[
+            DistanceMetric::Euclidean,
+            DistanceMetric::Manhattan,
+            DistanceMetric::Chebyshev,
+        ] {
+            let noise = Noise::new_cellular(
+                3,
+                2.0,
+                CellularFunction::F1,
+                metric,
+                Random::new_mt_from_seed(7),
+            );
+            for cell in &[[0, 0, 0], [3, -5, 2], [-12, 40, -7]] {
+                let point = noise.algorithm.feature_point(*cell);
+                assert!(noise.get(&point) < 1.0e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn output_is_deterministic_for_a_seed() {
+        for &function in &[
+            CellularFunction::F1,
+            CellularFunction::F2,
+            CellularFunction::F2MinusF1,
+        ] {
+            let first = Noise::new_cellular(
+                2,
+                2.0,
+                function,
+                DistanceMetric::Euclidean,
+                Random::new_mt_from_seed(7),
+            );
+            let second = Noise::new_cellular(
+                2,
+                2.0,
+                function,
+                DistanceMetric::Euclidean,
+                Random::new_mt_from_seed(7),
+            );
+            for i in 0..100 {
+                let coords = [i as f32 * 0.37 - 20.0, i as f32 * -0.53 + 7.0];
+                let value = first.get(&coords);
+                assert_eq!(value, second.get(&coords));
+                assert!((0.0..=1.0).contains(&value));
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_feature_points_match_an_exhaustive_search() {
+        for &dimensions in &[2, 3] {
+            for &metric in &[
+                DistanceMetric::Euclidean,
+                DistanceMetric::Manhattan,
+                DistanceMetric::Chebyshev,
+            ] {
+                let random = Random::new_mt_from_seed(11);
+                let mut cellular = Cellular::new(dimensions, AlgorithmInitializer::new(random));
+                cellular.metric = metric;
+                for i in 0..300 {
+                    let coords = [
+                        i as f32 * 0.377 - 40.0,
+                        i as f32 * -0.291 + 9.0,
+                        i as f32 * 0.113,
+                    ];
+                    let cell: Vec<_> = coords.iter().map(|c| c.floor() as i32).collect();
+
+                    /* every cell within four of the point's own */
+                    let mut distances = vec![];
+                    let z_range = if dimensions == 3 { -4..=4 } else { 0..=0 };
+                    for dz in z_range {
+                        for dy in -4..=4 {
+                            for dx in -4..=4 {
+                                let feature_point = cellular.feature_point([
+                                    cell[0] + dx,
+                                    cell[1] + dy,
+                                    cell[2] + dz,
+                                ]);
+                                distances.push(
+                                    metric.distance(
+                                        &coords[..dimensions],
+                                        &feature_point[..dimensions],
+                                    ),
+                                );
+                            }
+                        }
+                    }
+                    distances.sort_by(f32::total_cmp);
+                    let normalize = |d: f32| (d / metric.max_distance(dimensions)).clamp(0.0, 1.0);
+
+                    cellular.function = CellularFunction::F1;
+                    assert_eq!(normalize(distances[0]), cellular.generate(&coords));
+                    cellular.function = CellularFunction::F2;
+                    assert_eq!(normalize(distances[1]), cellular.generate(&coords));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn f2_is_never_nearer_than_f1() {
+        let random = Random::new_mt_from_seed(7);
+        let f1 = Cellular::new(2, AlgorithmInitializer::new(random));
+        let mut f2 = f1;
+        f2.function = CellularFunction::F2;
+        for i in 0..100 {
+            let coords = [i as f32 * 0.71, i as f32 * 0.29];
+            assert!(f1.generate(&coords) <= f2.generate(&coords));
+        }
+    }
+}
@@ -31,7 +31,7 @@
  * POSSIBILITY OF SUCH DAMAGE.
  */
 
-use crate::noise::algorithms::AlgorithmInitializer;
+use crate::noise::algorithms::{AlgorithmInitializer, DerivativeAlgorithm};
 use crate::noise::{Algorithm, MAX_DIMENSIONS};
 use crate::random::algorithms::Algorithm as RandomAlgorithm;
 use derivative::Derivative;
@@ -63,18 +63,24 @@ impl Perlin {
         iw: i32,
         fw: f32,
     ) -> f32 {
-        let n: [i32; 4] = [ix, iy, iz, iw];
         let f: [f32; 4] = [fx, fy, fz, fw];
+
+        Iterator::zip(self.lattice_gradient([ix, iy, iz, iw]).iter(), f.iter())
+            .take(self.dimensions)
+            .map(|(b, f)| b * f)
+            .sum()
+    }
+
+    fn lattice_gradient(&self, n: [i32; MAX_DIMENSIONS]) -> [f32; MAX_DIMENSIONS] {
         let mut n_index = 0;
         for &ni in n.iter().take(self.dimensions) {
             n_index = i32::from(self.map[((n_index + ni) & 0xFF) as usize]);
         }
         let buffer_window = Window2D::new_ref_unchecked(&self.buffer, 256, MAX_DIMENSIONS);
 
-        Iterator::zip(buffer_window[n_index as usize].iter(), f.iter())
-            .take(self.dimensions)
-            .map(|(b, f)| b * f)
-            .sum()
+        let mut gradient = [0.0; MAX_DIMENSIONS];
+        gradient.copy_from_slice(&buffer_window[n_index as usize]);
+        gradient
     }
 
     fn perlin_1d(
@@ -356,6 +362,10 @@ impl Perlin {
     fn cubic_f32(a: f32) -> f32 {
         a * a * (3.0 - 2.0 * a)
     }
+
+    fn cubic_derivative_f32(a: f32) -> f32 {
+        6.0 * a * (1.0 - a)
+    }
 }
 
 impl Algorithm for Perlin {
@@ -388,6 +398,74 @@ impl Algorithm for Perlin {
             _ => unreachable!(),
         };
 
-        value.max(-0.99999).min(0.99999)
+        value.clamp(-0.99999, 0.99999)
+    }
+}
+
+impl DerivativeAlgorithm for Perlin {
+    fn generate_with_derivative(&self, f: &[f32]) -> (f32, Vec<f32>) {
+        const CORNERS: usize = 1 << MAX_DIMENSIONS;
+
+        let mut n: [i32; MAX_DIMENSIONS] = [0; MAX_DIMENSIONS];
+        let mut r: [f32; MAX_DIMENSIONS] = [0.0; MAX_DIMENSIONS];
+        let mut w: [f32; MAX_DIMENSIONS] = [0.0; MAX_DIMENSIONS];
+        let mut dw: [f32; MAX_DIMENSIONS] = [0.0; MAX_DIMENSIONS];
+        for i in 0..self.dimensions {
+            n[i] = f[i].floor() as i32;
+            r[i] = f[i] - n[i] as f32;
+            w[i] = Self::cubic_f32(r[i]);
+            dw[i] = Self::cubic_derivative_f32(r[i]);
+        }
+
+        /* Lattice values and their gradients at every corner of the cell; bit i of the corner
+        index tells whether the corner lies on the upper side of dimension i. */
+        let mut values = [0.0; CORNERS];
+        let mut derivatives = [[0.0; MAX_DIMENSIONS]; CORNERS];
+        let corners = 1 << self.dimensions;
+        for corner in 0..corners {
+            let mut cn = n;
+            let mut cr = r;
+            for i in 0..self.dimensions {
+                if corner & (1 << i) != 0 {
+                    cn[i] += 1;
+                    cr[i] -= 1.0;
+                }
+            }
+            /* The 4D lattice of libtcod ignores the fourth dimension at these two corners,
+            and so must we in order to produce the same values as perlin_4d. */
+            let ignore_w = self.dimensions == 4 && corner & 0b0111 == 0b0110;
+            if ignore_w {
+                cn[3] = 0;
+                cr[3] = 0.0;
+            }
+
+            values[corner] = self.lattice(cn[0], cr[0], cn[1], cr[1], cn[2], cr[2], cn[3], cr[3]);
+            let gradient = self.lattice_gradient(cn);
+            derivatives[corner][..self.dimensions].copy_from_slice(&gradient[..self.dimensions]);
+            if ignore_w {
+                derivatives[corner][3] = 0.0;
+            }
+        }
+
+        /* Interpolate along one dimension at a time, in the same order as the perlin_Nd
+        functions, applying the product rule to the interpolation weight. */
+        let mut remaining = corners;
+        for i in 0..self.dimensions {
+            remaining /= 2;
+            for k in 0..remaining {
+                let (a, b) = (values[2 * k], values[2 * k + 1]);
+                let (da, db) = (derivatives[2 * k], derivatives[2 * k + 1]);
+                for j in 0..self.dimensions {
+                    derivatives[k][j] = lerp!(da[j], db[j], w[i]);
+                }
+                derivatives[k][i] += (b - a) * dw[i];
+                values[k] = lerp!(a, b, w[i]);
+            }
+        }
+
+        (
+            values[0].clamp(-0.99999, 0.99999),
+            derivatives[0][..self.dimensions].to_vec(),
+        )
     }
 }
@@ -31,8 +31,8 @@
  * POSSIBILITY OF SUCH DAMAGE.
  */
 
-use crate::noise::algorithms::AlgorithmInitializer;
-use crate::noise::Algorithm;
+use crate::noise::algorithms::{AlgorithmInitializer, DerivativeAlgorithm};
+use crate::noise::{Algorithm, MAX_DIMENSIONS};
 use crate::random::algorithms::Algorithm as RandomAlgorithm;
 use crate::util::FloorRem;
 
@@ -62,15 +62,33 @@ impl Algorithm for Simplex {
         assert!(f.len() >= self.dimensions);
 
         match self.dimensions {
-            1 => self.simplex_1d(f[0]),
-            2 => self.simplex_2d(f[0], f[1]),
-            3 => self.simplex_3d(f[0], f[1], f[2]),
-            4 => self.simplex_4d(f[0], f[1], f[2], f[3]),
+            1 => self.simplex_1d(f[0], None),
+            2 => self.simplex_2d(f[0], f[1], None),
+            3 => self.simplex_3d(f[0], f[1], f[2], None),
+            4 => self.simplex_4d(f[0], f[1], f[2], f[3], None),
             _ => unreachable!(),
         }
     }
 }
 
+impl DerivativeAlgorithm for Simplex {
+    fn generate_with_derivative(&self, f: &[f32]) -> (f32, Vec<f32>) {
+        assert!(f.len() >= self.dimensions);
+
+        let mut derivative = [0.0; MAX_DIMENSIONS];
+        let d = Some(&mut derivative);
+        let value = match self.dimensions {
+            1 => self.simplex_1d(f[0], d),
+            2 => self.simplex_2d(f[0], f[1], d),
+            3 => self.simplex_3d(f[0], f[1], f[2], d),
+            4 => self.simplex_4d(f[0], f[1], f[2], f[3], d),
+            _ => unreachable!(),
+        };
+
+        (value, derivative[..self.dimensions].to_vec())
+    }
+}
+
 impl Simplex {
     const SIMPLEX_SCALE: f32 = 0.5;
     const SIMPLEX: [[f32; 4]; 64] = [
@@ -140,27 +158,39 @@ impl Simplex {
         [3.0, 2.0, 1.0, 0.0],
     ];
 
-    fn simplex_1d(&self, f0: f32) -> f32 {
+    fn simplex_1d(&self, f0: f32, derivative: Option<&mut [f32; MAX_DIMENSIONS]>) -> f32 {
         let i0 = (f0 * Self::SIMPLEX_SCALE).floor() as i32;
         let i1 = i0 + 1;
         let x0 = f0 * Self::SIMPLEX_SCALE - i0 as f32;
         let x1 = x0 - 1.0;
         let t0 = 1.0 - x0 * x0;
         let t1 = 1.0 - x1 * x1;
+        let i0 = i32::from(self.map[(i0 & 0xFF) as usize]);
+        let g0 = Self::simplex_gradient_1d(i0);
+        let i1 = i32::from(self.map[(i1 & 0xFF) as usize]);
+        let g1 = Self::simplex_gradient_1d(i1);
+        if let Some(derivative) = derivative {
+            Self::add_corner_derivative(t0, &[g0], &[x0], g0 * x0, derivative);
+            Self::add_corner_derivative(t1, &[g1], &[x1], g1 * x1, derivative);
+            Self::scale_derivative(derivative, 0.25 * Self::SIMPLEX_SCALE);
+        }
         let t0 = t0 * t0;
         let t1 = t1 * t1;
-        let i0 = i32::from(self.map[(i0 & 0xFF) as usize]);
-        let mut n0 = Self::simplex_gradient_1d(i0, x0);
+        let mut n0 = g0 * x0;
         n0 *= t0 * t0;
-        let i1 = i32::from(self.map[(i1 & 0xFF) as usize]);
-        let mut n1 = Self::simplex_gradient_1d(i1, x1);
+        let mut n1 = g1 * x1;
         n1 *= t1 * t1;
 
         0.25 * (n0 + n1)
     }
 
     #[allow(clippy::many_single_char_names)]
-    fn simplex_2d(&self, f0: f32, f1: f32) -> f32 {
+    fn simplex_2d(
+        &self,
+        f0: f32,
+        f1: f32,
+        mut derivative: Option<&mut [f32; MAX_DIMENSIONS]>,
+    ) -> f32 {
         const F2: f64 = 0.366_025_403;
         const G2: f64 = 0.211_324_865;
 
@@ -181,41 +211,63 @@ impl Simplex {
         let y1 = y0 - j1 as f32 + G2 as f32;
         let x2 = x0 - 1.0 + (2.0 * G2) as f32;
         let y2 = y0 - 1.0 + (2.0 * G2) as f32;
-        let mut t0 = 0.5 - x0 * x0 - y0 * y0;
+        let t0 = 0.5 - x0 * x0 - y0 * y0;
 
         let n0 = if t0 < 0.0 {
             0.0
         } else {
             let mut idx = (ii + i32::from(self.map[jj as usize])) & 0xFF;
-            t0 *= t0;
             idx = i32::from(self.map[idx as usize]);
-            Self::simplex_gradient_2d(idx, x0, y0) * t0 * t0
+            Self::corner(
+                t0,
+                &Self::simplex_gradient_2d(idx),
+                &[x0, y0],
+                derivative.as_deref_mut(),
+            )
         };
-        let mut t1 = 0.5 - x1 * x1 - y1 * y1;
+        let t1 = 0.5 - x1 * x1 - y1 * y1;
         let n1 = if t1 < 0.0 {
             0.0
         } else {
             let mut idx = (ii + i1 + i32::from(self.map[((jj + j1) & 0xFF) as usize])) & 0xFF;
-            t1 *= t1;
             idx = i32::from(self.map[idx as usize]);
-            Self::simplex_gradient_2d(idx, x1, y1) * t1 * t1
+            Self::corner(
+                t1,
+                &Self::simplex_gradient_2d(idx),
+                &[x1, y1],
+                derivative.as_deref_mut(),
+            )
         };
-        let mut t2 = 0.5 - x2 * x2 - y2 * y2;
+        let t2 = 0.5 - x2 * x2 - y2 * y2;
         let n2 = if t2 < 0.0 {
             0.0
         } else {
             let mut idx = (ii + 1 + i32::from(self.map[((jj + 1) & 0xFF) as usize])) & 0xFF;
-            t2 *= t2;
             idx = i32::from(self.map[idx as usize]);
-            Self::simplex_gradient_2d(idx, x2, y2) * t2 * t2
+            Self::corner(
+                t2,
+                &Self::simplex_gradient_2d(idx),
+                &[x2, y2],
+                derivative.as_deref_mut(),
+            )
         };
 
+        if let Some(derivative) = derivative {
+            Self::scale_derivative(derivative, 40.0 * Self::SIMPLEX_SCALE);
+        }
+
         40.0 * (n0 + n1 + n2)
     }
 
     #[allow(clippy::too_many_lines)]
     #[allow(clippy::many_single_char_names)]
-    fn simplex_3d(&self, f0: f32, f1: f32, f2: f32) -> f32 {
+    fn simplex_3d(
+        &self,
+        f0: f32,
+        f1: f32,
+        f2: f32,
+        mut derivative: Option<&mut [f32; MAX_DIMENSIONS]>,
+    ) -> f32 {
         const F3: f64 = 0.333_333_333;
         const G3: f64 = 0.166_666_667;
 
@@ -263,7 +315,7 @@ impl Simplex {
         let jj = j.floor_modulo(256);
         let kk = k.floor_modulo(256);
 
-        let mut t0 = 0.6 - x0 * x0 - y0 * y0 - z0 * z0;
+        let t0 = 0.6 - x0 * x0 - y0 * y0 - z0 * z0;
         let n0 = if t0 < 0.0 {
             0.0
         } else {
@@ -274,11 +326,15 @@ impl Simplex {
                     ))
                     & 0xFF) as usize],
             );
-            t0 *= t0;
-            Self::simplex_gradient_3d(idx, x0, y0, z0) * t0 * t0
+            Self::corner(
+                t0,
+                &Self::simplex_gradient_3d(idx),
+                &[x0, y0, z0],
+                derivative.as_deref_mut(),
+            )
         };
 
-        let mut t1 = 0.6 - x1 * x1 - y1 * y1 - z1 * z1;
+        let t1 = 0.6 - x1 * x1 - y1 * y1 - z1 * z1;
         let n1 = if t1 < 0.0 {
             0.0
         } else {
@@ -291,11 +347,15 @@ impl Simplex {
                     ))
                     & 0xFF) as usize],
             );
-            t1 *= t1;
-            Self::simplex_gradient_3d(idx, x1, y1, z1) * t1 * t1
+            Self::corner(
+                t1,
+                &Self::simplex_gradient_3d(idx),
+                &[x1, y1, z1],
+                derivative.as_deref_mut(),
+            )
         };
 
-        let mut t2 = 0.6 - x2 * x2 - y2 * y2 - z2 * z2;
+        let t2 = 0.6 - x2 * x2 - y2 * y2 - z2 * z2;
         let n2 = if t2 < 0.0 {
             0.0
         } else {
@@ -308,11 +368,15 @@ impl Simplex {
                     ))
                     & 0xFF) as usize],
             );
-            t2 *= t2;
-            Self::simplex_gradient_3d(idx, x2, y2, z2) * t2 * t2
+            Self::corner(
+                t2,
+                &Self::simplex_gradient_3d(idx),
+                &[x2, y2, z2],
+                derivative.as_deref_mut(),
+            )
         };
 
-        let mut t3 = 0.6 - x3 * x3 - y3 * y3 - z3 * z3;
+        let t3 = 0.6 - x3 * x3 - y3 * y3 - z3 * z3;
         let n3 = if t3 < 0.0 {
             0.0
         } else {
@@ -325,16 +389,31 @@ impl Simplex {
                     ))
                     & 0xFF) as usize],
             );
-            t3 *= t3;
-            Self::simplex_gradient_3d(idx, x3, y3, z3) * t3 * t3
+            Self::corner(
+                t3,
+                &Self::simplex_gradient_3d(idx),
+                &[x3, y3, z3],
+                derivative.as_deref_mut(),
+            )
         };
 
+        if let Some(derivative) = derivative {
+            Self::scale_derivative(derivative, 32.0 * Self::SIMPLEX_SCALE);
+        }
+
         32.0 * (n0 + n1 + n2 + n3)
     }
 
     #[allow(clippy::too_many_lines)]
     #[allow(clippy::many_single_char_names)]
-    fn simplex_4d(&self, f0: f32, f1: f32, f2: f32, f3: f32) -> f32 {
+    fn simplex_4d(
+        &self,
+        f0: f32,
+        f1: f32,
+        f2: f32,
+        f3: f32,
+        mut derivative: Option<&mut [f32; MAX_DIMENSIONS]>,
+    ) -> f32 {
         const F4: f64 = 0.309_016_994;
         const G4: f64 = 0.138_196_601;
 
@@ -405,7 +484,7 @@ impl Simplex {
         let kk = k.floor_modulo(256);
         let ll = l.floor_modulo(256);
 
-        let mut t0 = 0.6 - x0 * x0 - y0 * y0 - z0 * z0 - w0 * w0;
+        let t0 = 0.6 - x0 * x0 - y0 * y0 - z0 * z0 - w0 * w0;
         let n0 = if t0 < 0.0 {
             0.0
         } else {
@@ -420,11 +499,15 @@ impl Simplex {
                     ))
                     & 0xFF) as usize],
             );
-            t0 *= t0;
-            Self::simplex_gradient_4d(idx, x0, y0, z0, w0) * t0 * t0
+            Self::corner(
+                t0,
+                &Self::simplex_gradient_4d(idx),
+                &[x0, y0, z0, w0],
+                derivative.as_deref_mut(),
+            )
         };
 
-        let mut t1 = 0.6 - x1 * x1 - y1 * y1 - z1 * z1 - w1 * w1;
+        let t1 = 0.6 - x1 * x1 - y1 * y1 - z1 * z1 - w1 * w1;
         let n1 = if t1 < 0.0 {
             0.0
         } else {
@@ -444,11 +527,15 @@ impl Simplex {
                     ))
                     & 0xFF) as usize],
             );
-            t1 *= t1;
-            Self::simplex_gradient_4d(idx, x1, y1, z1, w1) * t1 * t1
+            Self::corner(
+                t1,
+                &Self::simplex_gradient_4d(idx),
+                &[x1, y1, z1, w1],
+                derivative.as_deref_mut(),
+            )
         };
 
-        let mut t2 = 0.6 - x2 * x2 - y2 * y2 - z2 * z2 - w2 * w2;
+        let t2 = 0.6 - x2 * x2 - y2 * y2 - z2 * z2 - w2 * w2;
         let n2 = if t2 < 0.0 {
             0.0
         } else {
@@ -468,11 +555,15 @@ impl Simplex {
                     ))
                     & 0xFF) as usize],
             );
-            t2 *= t2;
-            Self::simplex_gradient_4d(idx, x2, y2, z2, w2) * t2 * t2
+            Self::corner(
+                t2,
+                &Self::simplex_gradient_4d(idx),
+                &[x2, y2, z2, w2],
+                derivative.as_deref_mut(),
+            )
         };
 
-        let mut t3 = 0.6 - x3 * x3 - y3 * y3 - z3 * z3 - w3 * w3;
+        let t3 = 0.6 - x3 * x3 - y3 * y3 - z3 * z3 - w3 * w3;
         let n3 = if t3 < 0.0 {
             0.0
         } else {
@@ -492,11 +583,15 @@ impl Simplex {
                     ))
                     & 0xFF) as usize],
             );
-            t3 *= t3;
-            Self::simplex_gradient_4d(idx, x3, y3, z3, w3) * t3 * t3
+            Self::corner(
+                t3,
+                &Self::simplex_gradient_4d(idx),
+                &[x3, y3, z3, w3],
+                derivative.as_deref_mut(),
+            )
         };
 
-        let mut t4 = 0.6 - x4 * x4 - y4 * y4 - z4 * z4 - w4 * w4;
+        let t4 = 0.6 - x4 * x4 - y4 * y4 - z4 * z4 - w4 * w4;
         let n4 = if t4 < 0.0 {
             0.0
         } else {
@@ -516,55 +611,105 @@ impl Simplex {
                     ))
                     & 0xFF) as usize],
             );
-            t4 *= t4;
-            Self::simplex_gradient_4d(idx, x4, y4, z4, w4) * t4 * t4
+            Self::corner(
+                t4,
+                &Self::simplex_gradient_4d(idx),
+                &[x4, y4, z4, w4],
+                derivative.as_deref_mut(),
+            )
         };
 
+        if let Some(derivative) = derivative {
+            Self::scale_derivative(derivative, 27.0 * Self::SIMPLEX_SCALE);
+        }
+
         27.0 * (n0 + n1 + n2 + n3 + n4)
     }
 
-    fn simplex_gradient_1d(mut h: i32, x: f32) -> f32 {
+    /* The contribution t⁴ (g · x) of a single corner to the noise value, where t is the
+    corner's remaining falloff, g its gradient and x the offset from it. */
+    fn corner(t: f32, g: &[f32], x: &[f32], derivative: Option<&mut [f32; MAX_DIMENSIONS]>) -> f32 {
+        let dot: f32 = Iterator::zip(g.iter(), x.iter()).map(|(g, x)| g * x).sum();
+        if let Some(derivative) = derivative {
+            Self::add_corner_derivative(t, g, x, dot, derivative);
+        }
+        let t2 = t * t;
+
+        dot * t2 * t2
+    }
+
+    /* Adds the partial derivatives of t⁴ (g · x) with respect to x, using that
+    t = r - |x|², i.e. d/dx = t⁴ g - 8 t³ (g · x) x. */
+    fn add_corner_derivative(
+        t: f32,
+        g: &[f32],
+        x: &[f32],
+        dot: f32,
+        derivative: &mut [f32; MAX_DIMENSIONS],
+    ) {
+        let t2 = t * t;
+        let t3 = t2 * t;
+        let t4 = t2 * t2;
+        for ((d, &gi), &xi) in derivative.iter_mut().zip(g.iter()).zip(x.iter()) {
+            *d += t4 * gi - 8.0 * t3 * dot * xi;
+        }
+    }
+
+    /* The corner offsets are the coordinates scaled by SIMPLEX_SCALE, and the sum of the
+    contributions is scaled once more before it is returned. */
+    fn scale_derivative(derivative: &mut [f32; MAX_DIMENSIONS], scale: f32) {
+        for d in derivative.iter_mut() {
+            *d *= scale;
+        }
+    }
+
+    fn simplex_gradient_1d(mut h: i32) -> f32 {
         h &= 0xF;
         let mut grad = 1.0 + (h & 7) as f32;
         if h & 8 == 8 {
             grad = -grad;
         }
-        grad * x
+        grad
     }
 
-    #[allow(clippy::many_single_char_names)]
-    fn simplex_gradient_2d(mut h: i32, x: f32, y: f32) -> f32 {
+    fn simplex_gradient_2d(mut h: i32) -> [f32; 2] {
         h &= 0x7;
-        let (u, v) = if h < 4 { (x, 2.0 * y) } else { (y, 2.0 * x) };
-        (if h & 1 == 1 { -u } else { u }) + (if h & 2 == 2 { -v } else { v })
+        let u = if h & 1 == 1 { -1.0 } else { 1.0 };
+        let v = if h & 2 == 2 { -2.0 } else { 2.0 };
+        if h < 4 {
+            [u, v]
+        } else {
+            [v, u]
+        }
     }
 
-    #[allow(clippy::many_single_char_names)]
-    #[allow(unused_parens)] // Bug in stable/nightly at the time of writing.
-    fn simplex_gradient_3d(mut h: i32, x: f32, y: f32, z: f32) -> f32 {
+    fn simplex_gradient_3d(mut h: i32) -> [f32; 3] {
         h &= 0xF;
-        let u = if h < 8 { x } else { y };
+        let u = if h < 8 { 0 } else { 1 };
         let v = if h < 4 {
-            y
+            1
         } else if h == 12 || h == 14 {
-            x
+            0
         } else {
-            z
+            2
         };
 
-        (if h & 1 == 1 { -u } else { u } + if h & 2 == 2 { -v } else { v })
+        let mut gradient = [0.0; 3];
+        gradient[u] = if h & 1 == 1 { -1.0 } else { 1.0 };
+        gradient[v] = if h & 2 == 2 { -1.0 } else { 1.0 };
+        gradient
     }
 
-    #[allow(clippy::many_single_char_names)]
-    #[allow(unused_parens)] // Bug in stable/nightly at the time of writing.
-    fn simplex_gradient_4d(mut h: i32, x: f32, y: f32, z: f32, t: f32) -> f32 {
+    fn simplex_gradient_4d(mut h: i32) -> [f32; 4] {
         h &= 0x1F;
-        let u = if h < 24 { x } else { y };
-        let v = if h < 16 { y } else { z };
-        let w = if h < 8 { z } else { t };
-
-        (if h & 1 == 1 { -u } else { u }
-            + if h & 2 == 2 { -v } else { v }
-            + if h & 4 == 4 { -w } else { w })
+        let u = if h < 24 { 0 } else { 1 };
+        let v = if h < 16 { 1 } else { 2 };
+        let w = if h < 8 { 2 } else { 3 };
+
+        let mut gradient = [0.0; 4];
+        gradient[u] = if h & 1 == 1 { -1.0 } else { 1.0 };
+        gradient[v] = if h & 2 == 2 { -1.0 } else { 1.0 };
+        gradient[w] = if h & 4 == 4 { -1.0 } else { 1.0 };
+        gradient
     }
 }
@@ -71,15 +71,17 @@ impl Algorithm for Wavelet {
     #[allow(clippy::many_single_char_names)]
     fn generate(&self, f: &[f32]) -> f32 {
         if self.dimensions > 3 {
-            panic!("Wavelet noise only supports up to 3 dimensions");
+            panic!("Wavelet noise only supports 1 to 3 dimensions");
         }
 
+        /* The noise repeats every tile, so wrapping the coordinates into the first tile doesn't
+        change it, but keeps the weights below from blowing up for far away coordinates. */
         let mut pf = [0.0; 3];
         for (pfe, &fe) in Iterator::zip(
             pf.iter_mut().take(self.dimensions),
             f.iter().take(self.dimensions),
         ) {
-            *pfe = fe * WAVELET_SCALE;
+            *pfe = (fe * WAVELET_SCALE).floor_modulo(WAVELET_TILE_SIZE as f32);
         }
 
         let mut mid = [0; 3];
@@ -119,7 +121,7 @@ impl Algorithm for Wavelet {
             }
         }
 
-        result.max(-1.0).min(1.0)
+        result.clamp(-1.0, 1.0)
     }
 }
 
@@ -217,7 +219,7 @@ impl WaveletTileData {
 
         for i in 0..WAVELET_TILE_SIZE as isize {
             to[i as usize * stride] = 0.0;
-            for k in i / 2..=i / 2 {
+            for k in i / 2..=i / 2 + 1 {
                 to[i as usize * stride] += P_COEFFICIENTS[(2 + i - 2 * k) as usize]
                     * from[k.floor_modulo(WAVELET_TILE_SIZE as isize / 2) as usize * stride];
             }
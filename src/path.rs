@@ -0,0 +1,1277 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * Copyright © 2008-2019, Jice and the libtcod contributors.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! # Path finding.
+//!
+//! This module provides the A* algorithm for finding the shortest path between two cells of a map,
+//! Jump Point Search for doing the same faster on maps where every move costs the same, and
+//! Dijkstra's algorithm for finding the distance from one cell to every other cell.
+
+mod jump_point_search;
+pub use jump_point_search::JumpPointSearch;
+
+use crate::fov::FovMap;
+use crate::Position;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+const ORTHOGONAL_DIRECTIONS: [(i32, i32); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+const DIAGONAL_DIRECTIONS: [(i32, i32); 4] = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+
+/// The signature of the callbacks that give the cost of moving from the cell at
+/// `(from_x, from_y)` to the adjacent cell at `(to_x, to_y)`. A cost of `0.0` or less means the
+/// move is impossible.
+pub type CostCallback = dyn FnMut(i32, i32, i32, i32) -> f32;
+
+/// Whether a diagonal step may cut the corners of the two cells it passes between, that is,
+/// the two cells orthogonally adjacent to both the cell it starts on and the one it ends on.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub enum CornerCutting {
+    /// Diagonal steps are allowed even between two blocked cells. This is the default.
+    #[default]
+    Allowed,
+    /// Diagonal steps are only allowed when at least one of the two cells can be moved to, so
+    /// that they never squeeze between two blocked cells.
+    NeedsOneOpen,
+    /// Diagonal steps are only allowed when both of the two cells can be moved to, so that they
+    /// never touch the corner of a blocked cell.
+    NeedsBothOpen,
+}
+
+/* Where the cost of moving between cells comes from */
+enum Costs {
+    Walkable(Vec<bool>),
+    Callback(Box<CostCallback>),
+}
+
+impl std::fmt::Debug for Costs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Walkable(walkable) => f.debug_tuple("Walkable").field(walkable).finish(),
+            Self::Callback(_) => f.write_str("Callback(..)"),
+        }
+    }
+}
+
+/* The movement costs shared by the path finding algorithms */
+#[derive(Debug)]
+struct Grid {
+    width: usize,
+    height: usize,
+    costs: Costs,
+    diagonal_cost: f32,
+    corner_cutting: CornerCutting,
+    /* The state of the last search, reused between searches to avoid reallocating */
+    distances: Vec<f32>,
+    previous: Vec<Option<usize>>,
+    closed: Vec<bool>,
+    open: BinaryHeap<Node>,
+    neighbors: Vec<(usize, f32)>,
+    /* How much work the last computation took: how many cells it expanded, and the most cells
+     * the open set held at once */
+    expanded: usize,
+    max_open: usize,
+}
+
+impl Grid {
+    fn from_map(map: &FovMap, diagonal_cost: f32) -> Self {
+        let (width, height) = (map.width(), map.height());
+        let walkable = (0..height as u32)
+            .flat_map(|y| (0..width as u32).map(move |x| (x, y)))
+            .map(|position| map.is_walkable(position.into()))
+            .collect();
+
+        Self::new(width, height, Costs::Walkable(walkable), diagonal_cost)
+    }
+
+    fn new(width: usize, height: usize, costs: Costs, diagonal_cost: f32) -> Self {
+        assert!(width > 0 && height > 0);
+        assert!(diagonal_cost >= 0.0, "diagonal_cost must not be negative");
+
+        Self {
+            width,
+            height,
+            costs,
+            diagonal_cost,
+            corner_cutting: CornerCutting::default(),
+            distances: vec![f32::INFINITY; width * height],
+            previous: vec![None; width * height],
+            closed: vec![false; width * height],
+            open: BinaryHeap::new(),
+            neighbors: Vec::with_capacity(8),
+            expanded: 0,
+            max_open: 0,
+        }
+    }
+
+    fn index(&self, position: Position) -> Option<usize> {
+        let (x, y) = (position.x, position.y);
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            Some(x as usize + y as usize * self.width)
+        } else {
+            None
+        }
+    }
+
+    fn position(&self, index: usize) -> Position {
+        Position::new((index % self.width) as i32, (index / self.width) as i32)
+    }
+
+    fn is_walkable(&self, position: Position) -> bool {
+        self.index(position)
+            .is_some_and(|index| !self.is_blocked(index))
+    }
+
+    /* Whether a cell is known to be impossible to move to, without asking a callback */
+    fn is_blocked(&self, index: usize) -> bool {
+        match &self.costs {
+            Costs::Walkable(walkable) => !walkable[index],
+            Costs::Callback(_) => false,
+        }
+    }
+
+    /* Fills `self.neighbors` with the cells that can be moved to from a cell, along with the
+     * cost of moving there */
+    fn find_neighbors(&mut self, index: usize) {
+        let position = self.position(index);
+        let diagonal_cost = self.diagonal_cost;
+        let diagonals: &[(i32, i32)] = if diagonal_cost > 0.0 {
+            &DIAGONAL_DIRECTIONS
+        } else {
+            &[]
+        };
+
+        self.neighbors.clear();
+        let directions = ORTHOGONAL_DIRECTIONS
+            .iter()
+            .map(|&d| (d, 1.0))
+            .chain(diagonals.iter().map(|&d| (d, diagonal_cost)));
+        for ((dx, dy), multiplier) in directions {
+            let to = Position::new(position.x + dx, position.y + dy);
+            let neighbor = match self.index(to) {
+                Some(neighbor) => neighbor,
+                None => continue,
+            };
+            if dx != 0 && dy != 0 && !self.allows_diagonal(position, dx, dy) {
+                continue;
+            }
+            let cost = self.step_cost(position, to, neighbor);
+            if cost > 0.0 {
+                self.neighbors.push((neighbor, cost * multiplier));
+            }
+        }
+    }
+
+    /* The cost of moving from a cell to the adjacent cell at `to`, whose index is `index`; 0.0 or
+     * less if the move is impossible */
+    fn step_cost(&mut self, from: Position, to: Position, index: usize) -> f32 {
+        match &mut self.costs {
+            Costs::Walkable(walkable) if walkable[index] => 1.0,
+            Costs::Walkable(_) => 0.0,
+            Costs::Callback(callback) => callback(from.x, from.y, to.x, to.y),
+        }
+    }
+
+    /* Whether the corner cutting rule allows the diagonal step from `from` in the direction
+     * `(dx, dy)`, given which of the two cells it passes between can be moved to from `from` */
+    fn allows_diagonal(&mut self, from: Position, dx: i32, dy: i32) -> bool {
+        let is_open = |grid: &mut Self, to: Position| match grid.index(to) {
+            Some(index) => grid.step_cost(from, to, index) > 0.0,
+            None => false,
+        };
+        let horizontal = Position::new(from.x + dx, from.y);
+        let vertical = Position::new(from.x, from.y + dy);
+        match self.corner_cutting {
+            CornerCutting::Allowed => true,
+            CornerCutting::NeedsOneOpen => is_open(self, horizontal) || is_open(self, vertical),
+            CornerCutting::NeedsBothOpen => is_open(self, horizontal) && is_open(self, vertical),
+        }
+    }
+
+    /* The positions of the cells that can be moved to from a position, in the order the search
+     * considers them */
+    fn neighbor_positions(&mut self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        match self.index(position) {
+            Some(index) => self.find_neighbors(index),
+            None => self.neighbors.clear(),
+        }
+        let width = self.width;
+        self.neighbors.iter().map(move |&(neighbor, _)| {
+            Position::new((neighbor % width) as i32, (neighbor / width) as i32)
+        })
+    }
+
+    /* A lower bound on the cost of going from one cell to another, as long as moves cost at
+     * least 1.0 */
+    fn heuristic(&self, from: usize, to: usize) -> f32 {
+        self.estimate(self.position(from), self.position(to))
+    }
+
+    /* The heuristic between positions, which may lie outside the grid */
+    fn estimate(&self, from: Position, to: Position) -> f32 {
+        let dx = (from.x - to.x).abs() as f32;
+        let dy = (from.y - to.y).abs() as f32;
+        if self.diagonal_cost > 0.0 && self.diagonal_cost < 1.0 {
+            /* Zig-zagging diagonals beat orthogonal steps, making every step along the longer
+             * axis cost as little as a diagonal one */
+            self.diagonal_cost * dx.max(dy)
+        } else if self.diagonal_cost > 0.0 {
+            /* A diagonal step is never worth more than the two orthogonal steps it replaces */
+            let diagonal_cost = self.diagonal_cost.min(2.0);
+            dx.max(dy) - dx.min(dy) + diagonal_cost * dx.min(dy)
+        } else {
+            dx + dy
+        }
+    }
+
+    /* Forgets the state of the last search */
+    fn reset(&mut self) {
+        for distance in &mut self.distances {
+            *distance = f32::INFINITY;
+        }
+        for previous in &mut self.previous {
+            *previous = None;
+        }
+        for closed in &mut self.closed {
+            *closed = false;
+        }
+        self.open.clear();
+    }
+
+    /* Forgets how much work the last computation took */
+    fn reset_stats(&mut self) {
+        self.expanded = 0;
+        self.max_open = 0;
+    }
+
+    fn push_open(&mut self, node: Node) {
+        self.open.push(node);
+        self.max_open = self.max_open.max(self.open.len());
+    }
+
+    /* Runs a best-first search from the `starts`, filling in the cheapest known distance to each
+     * cell from any of them and the cell it is reached from. With a `goal`, this is A* and stops
+     * once the goal is reached; without one, it is Dijkstra's algorithm and visits every
+     * reachable cell. */
+    fn search(&mut self, starts: &[usize], goal: Option<usize>) {
+        self.reset();
+
+        for &start in starts {
+            self.distances[start] = 0.0;
+            self.push_open(Node {
+                estimate: goal.map_or(0.0, |goal| self.heuristic(start, goal)),
+                index: start,
+            });
+        }
+        while let Some(Node { index, .. }) = self.open.pop() {
+            if Some(index) == goal {
+                break;
+            }
+            /* Skip outdated entries left behind when a cheaper route was found */
+            if self.closed[index] {
+                continue;
+            }
+            self.closed[index] = true;
+            self.expanded += 1;
+
+            let distance = self.distances[index];
+            self.find_neighbors(index);
+            let neighbors = std::mem::take(&mut self.neighbors);
+            for &(neighbor, step_cost) in &neighbors {
+                let neighbor_distance = distance + step_cost;
+                if neighbor_distance < self.distances[neighbor] {
+                    self.distances[neighbor] = neighbor_distance;
+                    self.previous[neighbor] = Some(index);
+                    self.push_open(Node {
+                        estimate: neighbor_distance
+                            + goal.map_or(0.0, |goal| self.heuristic(neighbor, goal)),
+                        index: neighbor,
+                    });
+                }
+            }
+            self.neighbors = neighbors;
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Node {
+    estimate: f32,
+    index: usize,
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Node {}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Node {
+    /* Reversed, so that the binary heap pops the lowest estimate first */
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+/// The A* path finding algorithm, working on the walkable cells of a map, or on the movement
+/// costs given by a callback.
+#[derive(Debug)]
+pub struct AStar {
+    grid: Grid,
+    origin: Position,
+    destination: Position,
+    path: Vec<Position>,
+    /* The cost of getting from the first origin to each step of `path` */
+    path_costs: Vec<f32>,
+    /* The index into `path` of the next step to walk */
+    next: usize,
+}
+
+impl AStar {
+    /// Returns a new path finder for the walkable cells of the given map. Later changes to the
+    /// map are not seen by the path finder.
+    ///
+    /// The `diagonal_cost` is the cost of a diagonal step, relative to an orthogonal one; see
+    /// [`set_diagonal_cost`](#method.set_diagonal_cost).
+    ///
+    /// # Panics
+    ///
+    /// If the `diagonal_cost` is negative.
+    pub fn new(map: &FovMap, diagonal_cost: f32) -> Self {
+        Self::from_grid(Grid::from_map(map, diagonal_cost))
+    }
+
+    /// Returns a new path finder for a map of the given size, where the cost of moving from one
+    /// cell to an adjacent one is given by the `callback`, called with the coordinates of both
+    /// cells as `(from_x, from_y, to_x, to_y)`. A cost of `0.0` or less means the move is
+    /// impossible. The cost of diagonal moves is multiplied by the `diagonal_cost`.
+    ///
+    /// The shortest path is only guaranteed to be found if no move costs less than `1.0`.
+    ///
+    /// # Panics
+    ///
+    /// * If the `width` or the `height` is 0.
+    /// * If the `diagonal_cost` is negative.
+    pub fn new_with_callback<F>(
+        width: usize,
+        height: usize,
+        callback: F,
+        diagonal_cost: f32,
+    ) -> Self
+    where
+        F: FnMut(i32, i32, i32, i32) -> f32 + 'static,
+    {
+        let costs = Costs::Callback(Box::new(callback));
+        Self::from_grid(Grid::new(width, height, costs, diagonal_cost))
+    }
+
+    fn from_grid(grid: Grid) -> Self {
+        Self {
+            grid,
+            origin: Position::ORIGIN,
+            destination: Position::ORIGIN,
+            path: Vec::new(),
+            path_costs: Vec::new(),
+            next: 0,
+        }
+    }
+
+    /// Returns the cost of a diagonal step, relative to an orthogonal one.
+    pub fn diagonal_cost(&self) -> f32 {
+        self.grid.diagonal_cost
+    }
+
+    /// Sets the cost of a diagonal step, relative to an orthogonal one. A cost of `0.0` disables
+    /// diagonal steps entirely, `1.0` makes them as cheap as orthogonal steps (Chebyshev
+    /// distance) and `1.41` makes them about as expensive as their actual length (octile
+    /// distance). Takes effect on the next computation.
+    ///
+    /// # Panics
+    ///
+    /// If the `cost` is negative.
+    pub fn set_diagonal_cost(&mut self, cost: f32) {
+        assert!(cost >= 0.0, "diagonal_cost must not be negative");
+        self.grid.diagonal_cost = cost;
+    }
+
+    /// Returns whether diagonal steps may cut corners.
+    pub fn corner_cutting(&self) -> CornerCutting {
+        self.grid.corner_cutting
+    }
+
+    /// Sets whether diagonal steps may cut corners; see [`CornerCutting`]. Has no effect if the
+    /// diagonal cost is `0.0`. Takes effect on the next computation.
+    ///
+    /// [`CornerCutting`]: enum.CornerCutting.html
+    pub fn set_corner_cutting(&mut self, corner_cutting: CornerCutting) {
+        self.grid.corner_cutting = corner_cutting;
+    }
+
+    /// Allows diagonal steps to cut corners or not; a shorthand for
+    /// [`set_corner_cutting`](#method.set_corner_cutting) with `CornerCutting::Allowed` or
+    /// `CornerCutting::NeedsOneOpen`.
+    pub fn set_allow_corner_cutting(&mut self, allow: bool) {
+        self.set_corner_cutting(if allow {
+            CornerCutting::Allowed
+        } else {
+            CornerCutting::NeedsOneOpen
+        });
+    }
+
+    /// Returns the cells that can be moved to in one step from `position`: the adjacent cells
+    /// inside the map that are walkable, or that the callback gives a positive cost, including
+    /// the diagonal ones unless the diagonal cost is `0.0` or the
+    /// [corner cutting](#method.set_corner_cutting) rule forbids them. Useful for writing other
+    /// searches over the same cells. Nothing is returned for a `position` outside the map.
+    ///
+    /// This takes `&mut self` since the callback may need to be called.
+    pub fn neighbors(&mut self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        self.grid.neighbor_positions(position)
+    }
+
+    /// Computes the shortest path between `from` and `to`, replacing the previously computed
+    /// one. Returns whether a path was found.
+    ///
+    /// The path excludes `from` but includes `to`. The `from` cell does not need to be walkable,
+    /// but `to` does.
+    ///
+    /// The buffers used by the computation are kept and reused by the next one, so computing
+    /// paths repeatedly with the same path finder doesn't allocate.
+    pub fn compute(&mut self, from: Position, to: Position) -> bool {
+        self.restart(from, to);
+        self.append_path(from, to)
+    }
+
+    /// Computes the shortest path between `from` and `to` like [`compute`](#method.compute), but
+    /// if `to` can't be reached, computes the shortest path to the reachable cell closest to `to`
+    /// instead. Returns whether a path all the way to `to` was found.
+    ///
+    /// Closeness is measured by the same heuristic the search uses, so it takes the diagonal cost
+    /// into account; ties are broken by picking the cell that is cheapest to reach. When no
+    /// other cell is reachable, the path is empty. The [`destination`](#method.destination) is
+    /// the cell the path actually leads to.
+    pub fn compute_to_closest(&mut self, from: Position, to: Position) -> bool {
+        self.restart(from, to);
+        let start = match self.grid.index(from) {
+            Some(start) => start,
+            None => return false,
+        };
+
+        let goal = self
+            .grid
+            .index(to)
+            .filter(|&goal| !self.grid.is_blocked(goal));
+        self.grid.search(&[start], goal);
+        if let Some(goal) = goal {
+            if goal == start || self.grid.previous[goal].is_some() {
+                self.push_segment(start, goal);
+                return true;
+            }
+        }
+
+        /* The goal wasn't reached, so the search has visited every reachable cell */
+        let grid = &self.grid;
+        let closest = (0..grid.closed.len())
+            .filter(|&index| grid.closed[index])
+            .min_by(|&a, &b| {
+                let (a_estimate, b_estimate) = (
+                    grid.estimate(grid.position(a), to),
+                    grid.estimate(grid.position(b), to),
+                );
+                a_estimate
+                    .total_cmp(&b_estimate)
+                    .then_with(|| grid.distances[a].total_cmp(&grid.distances[b]))
+            })
+            .unwrap_or(start);
+        self.destination = self.grid.position(closest);
+        self.push_segment(start, closest);
+        false
+    }
+
+    /// Computes the shortest path that goes through each of the `points` in order, starting from
+    /// the first one and ending at the last one, replacing the previously computed path. Returns
+    /// whether every leg of the route could be found; if not, the path is left empty.
+    ///
+    /// The path excludes the first point but includes all the others. Only the first point may be
+    /// unwalkable.
+    ///
+    /// # Panics
+    ///
+    /// If `points` is empty.
+    pub fn compute_through(&mut self, points: &[Position]) -> bool {
+        assert!(!points.is_empty(), "there must be at least one point");
+
+        self.restart(points[0], points[points.len() - 1]);
+        let found = if points.len() == 1 {
+            self.append_path(points[0], points[0])
+        } else {
+            points
+                .windows(2)
+                .all(|leg| self.append_path(leg[0], leg[1]))
+        };
+        if !found {
+            self.path.clear();
+            self.path_costs.clear();
+        }
+        found
+    }
+
+    /// Returns how many cells the last computation expanded, that is, how many cells it found
+    /// the shortest route to and looked at the neighbors of. For a route through several
+    /// points, this is the total over all of its legs.
+    pub fn nodes_expanded(&self) -> usize {
+        self.grid.expanded
+    }
+
+    /// Returns the largest number of cells the last computation had waiting to be expanded at
+    /// once, which is a measure of how much memory it needed. For a route through several
+    /// points, this is the largest over all of its legs.
+    pub fn max_open_set_size(&self) -> usize {
+        self.grid.max_open
+    }
+
+    fn restart(&mut self, from: Position, to: Position) {
+        self.origin = from;
+        self.destination = to;
+        self.path.clear();
+        self.path_costs.clear();
+        self.next = 0;
+        self.grid.reset_stats();
+    }
+
+    /* Searches for the shortest path between two positions, appending it to `self.path` if found */
+    fn append_path(&mut self, from: Position, to: Position) -> bool {
+        let (start, goal) = match (self.grid.index(from), self.grid.index(to)) {
+            (Some(start), Some(goal)) if !self.grid.is_blocked(goal) => (start, goal),
+            _ => return false,
+        };
+
+        self.grid.search(&[start], Some(goal));
+        if start != goal && self.grid.previous[goal].is_none() {
+            return false;
+        }
+        self.push_segment(start, goal);
+        true
+    }
+
+    /* Appends the path to `end` found by the last search from `start`, excluding `start` */
+    fn push_segment(&mut self, start: usize, end: usize) {
+        let segment_start = self.path.len();
+        let cost_so_far = self.path_costs.last().copied().unwrap_or(0.0);
+        let mut current = end;
+        while current != start {
+            self.path.push(self.grid.position(current));
+            self.path_costs
+                .push(cost_so_far + self.grid.distances[current]);
+            current =
+                self.grid.previous[current].expect("every cell on the path has a predecessor");
+        }
+        self.path[segment_start..].reverse();
+        self.path_costs[segment_start..].reverse();
+    }
+
+    /// Returns the steps of the path that haven't been walked yet, in order. The slice is only
+    /// valid until the next computation.
+    pub fn steps(&self) -> &[Position] {
+        &self.path[self.next..]
+    }
+
+    /// Returns the number of steps of the path that haven't been walked yet.
+    pub fn len(&self) -> usize {
+        self.path.len() - self.next
+    }
+
+    /// Returns whether there are no steps of the path left to walk, which is also the case when
+    /// no path was found.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the step of the path at the given index, counting from the next step to walk,
+    /// or `None` if the index is past the end of the path.
+    pub fn get(&self, index: usize) -> Option<Position> {
+        self.steps().get(index).copied()
+    }
+
+    /// Returns the total cost of the steps of the path that haven't been walked yet, which
+    /// includes the costs given by the callback and the diagonal cost. If the path is empty, the
+    /// cost is `0.0`.
+    pub fn cost(&self) -> f32 {
+        let total = self.path_costs.last().copied().unwrap_or(0.0);
+        let walked = match self.next {
+            0 => 0.0,
+            next => self.path_costs[next - 1],
+        };
+        total - walked
+    }
+
+    /// Returns the position the path currently starts from.
+    pub fn origin(&self) -> Position {
+        self.origin
+    }
+
+    /// Returns the position the path leads to.
+    pub fn destination(&self) -> Position {
+        self.destination
+    }
+
+    /// Takes the next step along the path, returning the position stepped to, or `None` if the
+    /// destination has been reached.
+    pub fn walk(&mut self) -> Option<Position> {
+        let step = *self.path.get(self.next)?;
+        self.next += 1;
+        self.origin = step;
+        Some(step)
+    }
+}
+
+/// Dijkstra's algorithm, working on the walkable cells of a map, or on the movement costs given
+/// by a callback. Computes the distance from a root cell to every cell of the map.
+#[derive(Debug)]
+pub struct Dijkstra {
+    grid: Grid,
+    roots: Vec<Position>,
+}
+
+impl Dijkstra {
+    /// Returns a new distance finder for the walkable cells of the given map. Later changes to
+    /// the map are not seen by the distance finder.
+    ///
+    /// The `diagonal_cost` is the cost of a diagonal step, relative to an orthogonal one; see
+    /// [`AStar::set_diagonal_cost`](struct.AStar.html#method.set_diagonal_cost).
+    ///
+    /// # Panics
+    ///
+    /// If the `diagonal_cost` is negative.
+    pub fn new(map: &FovMap, diagonal_cost: f32) -> Self {
+        Self::from_grid(Grid::from_map(map, diagonal_cost))
+    }
+
+    /// Returns a new distance finder for a map of the given size, where the cost of moving from
+    /// one cell to an adjacent one is given by the `callback`; see
+    /// [`AStar::new_with_callback`](struct.AStar.html#method.new_with_callback).
+    ///
+    /// # Panics
+    ///
+    /// * If the `width` or the `height` is 0.
+    /// * If the `diagonal_cost` is negative.
+    pub fn new_with_callback<F>(
+        width: usize,
+        height: usize,
+        callback: F,
+        diagonal_cost: f32,
+    ) -> Self
+    where
+        F: FnMut(i32, i32, i32, i32) -> f32 + 'static,
+    {
+        let costs = Costs::Callback(Box::new(callback));
+        Self::from_grid(Grid::new(width, height, costs, diagonal_cost))
+    }
+
+    fn from_grid(grid: Grid) -> Self {
+        Self {
+            grid,
+            roots: vec![Position::ORIGIN],
+        }
+    }
+
+    /// Returns the cost of a diagonal step, relative to an orthogonal one.
+    pub fn diagonal_cost(&self) -> f32 {
+        self.grid.diagonal_cost
+    }
+
+    /// Sets the cost of a diagonal step, relative to an orthogonal one. Takes effect on the next
+    /// computation.
+    ///
+    /// # Panics
+    ///
+    /// If the `cost` is negative.
+    pub fn set_diagonal_cost(&mut self, cost: f32) {
+        assert!(cost >= 0.0, "diagonal_cost must not be negative");
+        self.grid.diagonal_cost = cost;
+    }
+
+    /// Returns whether diagonal steps may cut corners.
+    pub fn corner_cutting(&self) -> CornerCutting {
+        self.grid.corner_cutting
+    }
+
+    /// Sets whether diagonal steps may cut corners; see [`CornerCutting`]. Has no effect if the
+    /// diagonal cost is `0.0`. Takes effect on the next computation.
+    ///
+    /// [`CornerCutting`]: enum.CornerCutting.html
+    pub fn set_corner_cutting(&mut self, corner_cutting: CornerCutting) {
+        self.grid.corner_cutting = corner_cutting;
+    }
+
+    /// Allows diagonal steps to cut corners or not; a shorthand for
+    /// [`set_corner_cutting`](#method.set_corner_cutting) with `CornerCutting::Allowed` or
+    /// `CornerCutting::NeedsOneOpen`.
+    pub fn set_allow_corner_cutting(&mut self, allow: bool) {
+        self.set_corner_cutting(if allow {
+            CornerCutting::Allowed
+        } else {
+            CornerCutting::NeedsOneOpen
+        });
+    }
+
+    /// Returns the cells that can be moved to in one step from `position`: the adjacent cells
+    /// inside the map that are walkable, or that the callback gives a positive cost, including
+    /// the diagonal ones unless the diagonal cost is `0.0` or the
+    /// [corner cutting](#method.set_corner_cutting) rule forbids them. Useful for writing other
+    /// searches over the same cells. Nothing is returned for a `position` outside the map.
+    ///
+    /// This takes `&mut self` since the callback may need to be called.
+    pub fn neighbors(&mut self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        self.grid.neighbor_positions(position)
+    }
+
+    /// Computes the distance from `root` to every cell of the map, replacing the previously
+    /// computed distances. The `root` cell does not need to be walkable.
+    ///
+    /// # Panics
+    ///
+    /// If the `root` is outside the range of the map.
+    pub fn compute(&mut self, root: Position) {
+        self.compute_multi(&[root]);
+    }
+
+    /// Computes the distance from the nearest of the `roots` to every cell of the map, in a
+    /// single pass, replacing the previously computed distances. The `roots` do not need to be
+    /// walkable. Without any roots, every cell is unreachable.
+    ///
+    /// # Panics
+    ///
+    /// If any of the `roots` is outside the range of the map.
+    pub fn compute_multi(&mut self, roots: &[Position]) {
+        let starts: Vec<_> = roots
+            .iter()
+            .map(|&root| self.grid.index(root).expect("root must be inside the map"))
+            .collect();
+        self.roots.clear();
+        self.roots.extend_from_slice(roots);
+        self.grid.reset_stats();
+        self.grid.search(&starts, None);
+    }
+
+    /// Returns the position the distances were last computed from. If they were computed from
+    /// several roots, this is the first of them; see [`roots`](#method.roots).
+    ///
+    /// # Panics
+    ///
+    /// If the distances were last computed from no roots at all.
+    pub fn root(&self) -> Position {
+        *self
+            .roots
+            .first()
+            .expect("distances were computed from no roots")
+    }
+
+    /// Returns the positions the distances were last computed from.
+    pub fn roots(&self) -> &[Position] {
+        &self.roots
+    }
+
+    /// Returns the distance from the nearest root to the given position, or `None` if the
+    /// position can't be reached from any root, or is outside the range of the map.
+    pub fn distance(&self, position: Position) -> Option<f32> {
+        self.grid
+            .index(position)
+            .map(|index| self.grid.distances[index])
+            .filter(|distance| distance.is_finite())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fov::{fixture, FovMap};
+    use crate::path::{AStar, CornerCutting, Dijkstra};
+    use crate::Position;
+
+    fn walk_all(path: &mut AStar) -> Vec<Position> {
+        std::iter::from_fn(|| path.walk()).collect()
+    }
+
+    fn open_map(width: usize, height: usize) -> FovMap {
+        let mut map = FovMap::new(width, height);
+        map.clear(true, true);
+        map
+    }
+
+    #[test]
+    fn path_goes_around_walls() {
+        let map = fixture(&["......", ".####.", "......"]);
+        let mut path = AStar::new(&map, 1.41);
+        assert!(path.compute(Position::new(0, 1), Position::new(5, 1)));
+        let steps = walk_all(&mut path);
+        assert_eq!(Some(&Position::new(5, 1)), steps.last());
+        assert_eq!(5, steps.len());
+        assert_eq!(Position::new(5, 1), path.origin());
+        assert!(steps
+            .iter()
+            .all(|&p| map.is_walkable((p.x as u32, p.y as u32).into())));
+    }
+
+    #[test]
+    fn unreachable_destination() {
+        let map = fixture(&["..#..", "..#..", "..#.."]);
+        let mut path = AStar::new(&map, 1.41);
+        assert!(!path.compute(Position::new(0, 0), Position::new(4, 0)));
+        assert!(!path.compute(Position::new(0, 0), Position::new(2, 0)));
+        assert!(!path.compute(Position::new(0, 0), Position::new(9, 0)));
+        assert_eq!(None, path.walk());
+
+        assert!(path.compute(Position::new(1, 1), Position::new(1, 1)));
+        assert_eq!(None, path.walk());
+    }
+
+    #[test]
+    fn closest_path_stops_next_to_the_blockage() {
+        let map = fixture(&["....#...", "....#...", "....#..."]);
+        let mut path = AStar::new(&map, 1.41);
+        let target = Position::new(6, 1);
+        assert!(!path.compute_to_closest(Position::new(0, 1), target));
+        let steps = walk_all(&mut path);
+        assert_eq!(Some(&Position::new(3, 1)), steps.last());
+        assert_eq!(3, steps.len());
+        assert_eq!(Position::new(3, 1), path.destination());
+
+        /* A blocked target is approached as closely as possible, too */
+        assert!(!path.compute_to_closest(Position::new(0, 1), Position::new(4, 0)));
+        assert_eq!(Some(&Position::new(3, 0)), path.steps().last());
+
+        /* Reachable targets get the same path as with compute */
+        assert!(path.compute_to_closest(Position::new(0, 0), Position::new(3, 2)));
+        let closest = path.steps().to_vec();
+        assert!(path.compute(Position::new(0, 0), Position::new(3, 2)));
+        assert_eq!(closest, path.steps());
+    }
+
+    #[test]
+    fn routes_visit_every_waypoint_in_order() {
+        let map = fixture(&["......", ".####.", "......"]);
+        let mut path = AStar::new(&map, 1.41);
+        let waypoints = [
+            Position::new(0, 0),
+            Position::new(5, 2),
+            Position::new(0, 2),
+            Position::new(5, 0),
+        ];
+        assert!(path.compute_through(&waypoints));
+        assert_eq!(Position::new(5, 0), path.destination());
+        let steps = walk_all(&mut path);
+        let mut visited = steps.iter();
+        for waypoint in &waypoints[1..] {
+            assert!(visited.any(|step| step == waypoint), "{}", waypoint);
+        }
+        assert_eq!(Some(&Position::new(5, 0)), steps.last());
+        assert!(steps.windows(2).all(|pair| {
+            (pair[0].x - pair[1].x).abs() <= 1 && (pair[0].y - pair[1].y).abs() <= 1
+        }));
+        assert_eq!(6 + 5 + 6, steps.len());
+
+        assert!(!path.compute_through(&[Position::new(0, 0), Position::new(2, 1)]));
+        assert!(path.steps().is_empty());
+    }
+
+    #[test]
+    fn search_effort_is_counted_per_computation() {
+        let map = fixture(&[
+            "..........",
+            "########..",
+            "..........",
+            "..########",
+            "..........",
+        ]);
+        let mut path = AStar::new(&map, 1.41);
+        assert_eq!(0, path.nodes_expanded());
+
+        assert!(path.compute(Position::new(0, 0), Position::new(1, 0)));
+        let (short_expanded, short_open) = (path.nodes_expanded(), path.max_open_set_size());
+        assert!((1..=2).contains(&short_expanded), "{}", short_expanded);
+        assert!(short_open >= 1);
+
+        assert!(path.compute(Position::new(0, 0), Position::new(0, 4)));
+        assert!(path.nodes_expanded() >= path.steps().len());
+        assert!(path.nodes_expanded() > 5 * short_expanded);
+        assert!(path.max_open_set_size() > short_open);
+
+        assert!(path.compute(Position::new(0, 0), Position::new(1, 0)));
+        assert_eq!(short_expanded, path.nodes_expanded());
+        assert_eq!(short_open, path.max_open_set_size());
+
+        assert!(!path.compute(Position::new(0, 0), Position::new(0, 1)));
+        assert_eq!(0, path.nodes_expanded());
+        assert_eq!(0, path.max_open_set_size());
+    }
+
+    #[test]
+    fn without_diagonals_only_orthogonal_steps_are_taken() {
+        let mut path = AStar::new(&open_map(8, 8), 0.0);
+        assert!(path.compute(Position::new(1, 1), Position::new(6, 5)));
+        let mut previous = Position::new(1, 1);
+        let steps = walk_all(&mut path);
+        for &step in &steps {
+            assert_eq!(1, (step.x - previous.x).abs() + (step.y - previous.y).abs());
+            previous = step;
+        }
+        assert_eq!(9, steps.len());
+    }
+
+    #[test]
+    fn diagonals_shorten_diagonal_routes() {
+        let map = open_map(8, 8);
+        let mut octile = AStar::new(&map, 1.41);
+        let mut chebyshev = AStar::new(&map, 0.0);
+        chebyshev.set_diagonal_cost(1.0);
+        let mut orthogonal = AStar::new(&map, 1.41);
+        orthogonal.set_diagonal_cost(0.0);
+
+        for path in &mut [&mut octile, &mut chebyshev, &mut orthogonal] {
+            assert!(path.compute(Position::new(0, 0), Position::new(5, 5)));
+        }
+        assert_eq!(5, walk_all(&mut octile).len());
+        assert_eq!(5, walk_all(&mut chebyshev).len());
+        assert_eq!(10, walk_all(&mut orthogonal).len());
+
+        /* An octile path only steps diagonally as often as it has to */
+        assert!(octile.compute(Position::new(0, 0), Position::new(6, 2)));
+        let steps = walk_all(&mut octile);
+        let mut previous = Position::new(0, 0);
+        let mut diagonal_steps = 0;
+        for &step in &steps {
+            if step.x != previous.x && step.y != previous.y {
+                diagonal_steps += 1;
+            }
+            previous = step;
+        }
+        assert_eq!(6, steps.len());
+        assert_eq!(2, diagonal_steps);
+    }
+
+    #[test]
+    fn dijkstra_distances() {
+        let map = fixture(&["........", ".####...", ".#..#...", ".####...", "........"]);
+        let mut dijkstra = Dijkstra::new(&map, 1.5);
+        assert_eq!(None, dijkstra.distance(Position::new(0, 0)));
+
+        let root = Position::new(0, 0);
+        dijkstra.compute(root);
+        assert_eq!(root, dijkstra.root());
+        assert_eq!(Some(0.0), dijkstra.distance(root));
+        assert_eq!(Some(1.0), dijkstra.distance(Position::new(1, 0)));
+        assert_eq!(Some(4.0), dijkstra.distance(Position::new(0, 4)));
+        assert_eq!(Some(5.5), dijkstra.distance(Position::new(5, 1)));
+
+        /* Walls and the cells they enclose are unreachable */
+        for &(x, y) in &[(2, 2), (3, 2), (1, 1), (4, 3)] {
+            assert_eq!(None, dijkstra.distance(Position::new(x, y)));
+        }
+        assert_eq!(None, dijkstra.distance(Position::new(8, 0)));
+
+        /* Every reachable cell other than the root has a neighbor closer to the root */
+        for y in 0..5 {
+            for x in 0..8 {
+                let position = Position::new(x, y);
+                let distance = match dijkstra.distance(position) {
+                    Some(distance) if position != root => distance,
+                    _ => continue,
+                };
+                let closer = (-1..=1)
+                    .flat_map(|dy| (-1..=1).map(move |dx| Position::new(x + dx, y + dy)))
+                    .filter_map(|neighbor| dijkstra.distance(neighbor))
+                    .any(|neighbor_distance| neighbor_distance < distance);
+                assert!(closer, "{}", position);
+            }
+        }
+
+        /* Dijkstra agrees with the cost of the path A* finds */
+        let mut path = AStar::new(&map, 1.0);
+        dijkstra.set_diagonal_cost(1.0);
+        dijkstra.compute(Position::new(7, 4));
+        assert!(path.compute(Position::new(7, 4), Position::new(0, 2)));
+        assert_eq!(
+            Some(walk_all(&mut path).len() as f32),
+            dijkstra.distance(Position::new(0, 2))
+        );
+    }
+
+    #[test]
+    fn dijkstra_from_several_roots() {
+        let map = fixture(&[".........", "..#...#..", ".........", "....#...."]);
+        let roots = [Position::new(0, 0), Position::new(8, 2)];
+        let mut multi = Dijkstra::new(&map, 1.5);
+        multi.compute_multi(&roots);
+        assert_eq!(roots, multi.roots());
+        assert_eq!(roots[0], multi.root());
+
+        let singles: Vec<_> = roots
+            .iter()
+            .map(|&root| {
+                let mut single = Dijkstra::new(&map, 1.5);
+                single.compute(root);
+                single
+            })
+            .collect();
+        let mut boundary = 0;
+        for y in 0..4 {
+            for x in 0..9 {
+                let position = Position::new(x, y);
+                let (first, second) =
+                    (singles[0].distance(position), singles[1].distance(position));
+                let nearest = match (first, second) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    _ => first.or(second),
+                };
+                assert_eq!(nearest, multi.distance(position), "{}", position);
+                if first.is_some() && first == second {
+                    boundary += 1;
+                }
+            }
+        }
+        /* The cells halfway between the roots are as far from one as from the other */
+        assert!(boundary > 0);
+        let middle = Position::new(4, 1);
+        assert_eq!(singles[0].distance(middle), singles[1].distance(middle));
+
+        multi.compute_multi(&[]);
+        assert!(multi.roots().is_empty());
+        assert_eq!(None, multi.distance(Position::new(0, 0)));
+        assert_eq!(None, multi.distance(Position::new(4, 1)));
+    }
+
+    #[test]
+    fn callback_costs_prefer_the_road() {
+        /* A straight route through swamp, or a detour along a road */
+        let rows = ["~~~~~~~~~", "=~~~~~~~=", "=========", "#########"];
+        let cost =
+            move |_: i32, _: i32, x: i32, y: i32| match rows[y as usize].as_bytes()[x as usize] {
+                b'=' => 1.0,
+                b'~' => 5.0,
+                _ => 0.0,
+            };
+
+        let mut path = AStar::new_with_callback(9, 4, cost, 0.0);
+        assert!(path.compute(Position::new(0, 0), Position::new(8, 0)));
+        let steps = walk_all(&mut path);
+        assert_eq!(12, steps.len());
+        assert!(steps.contains(&Position::new(4, 2)));
+        assert!(!steps.contains(&Position::new(4, 0)));
+        assert!(!path.compute(Position::new(0, 0), Position::new(4, 3)));
+
+        let mut dijkstra = Dijkstra::new_with_callback(9, 4, cost, 0.0);
+        dijkstra.compute(Position::new(0, 0));
+        assert_eq!(Some(11.0), dijkstra.distance(Position::new(8, 1)));
+        assert_eq!(Some(11.0 + 5.0), dijkstra.distance(Position::new(8, 0)));
+        assert_eq!(None, dijkstra.distance(Position::new(4, 3)));
+    }
+
+    #[test]
+    fn neighbors_follow_the_diagonal_setting() {
+        let map = fixture(&["....", ".#..", "...."]);
+        let mut path = AStar::new(&map, 1.41);
+        let neighbors = |finder: &mut AStar, x, y| {
+            let mut cells: Vec<_> = finder
+                .neighbors(Position::new(x, y))
+                .map(|p| (p.x, p.y))
+                .collect();
+            cells.sort_unstable();
+            cells
+        };
+
+        assert_eq!(vec![(0, 1), (1, 0)], neighbors(&mut path, 0, 0));
+        assert_eq!(
+            vec![(1, 0), (1, 2), (2, 0), (2, 2), (3, 0), (3, 1), (3, 2)],
+            neighbors(&mut path, 2, 1)
+        );
+        assert!(neighbors(&mut path, -1, 0).is_empty());
+
+        path.set_diagonal_cost(0.0);
+        assert_eq!(vec![(0, 1), (1, 0)], neighbors(&mut path, 0, 0));
+        assert_eq!(vec![(2, 0), (2, 2), (3, 1)], neighbors(&mut path, 2, 1));
+
+        let mut dijkstra = Dijkstra::new(&map, 1.0);
+        assert_eq!(3, dijkstra.neighbors(Position::new(3, 2)).count());
+    }
+
+    #[test]
+    fn corner_cutting() {
+        let map = fixture(&["......", "..#...", "...#..", "......"]);
+        let (from, to) = (Position::new(2, 2), Position::new(3, 1));
+
+        let mut path = AStar::new(&map, 1.0);
+        assert_eq!(CornerCutting::Allowed, path.corner_cutting());
+        assert!(path.compute(from, to));
+        assert_eq!(vec![to], walk_all(&mut path));
+        assert!(path.neighbors(from).any(|p| p == to));
+
+        path.set_allow_corner_cutting(false);
+        assert_eq!(CornerCutting::NeedsOneOpen, path.corner_cutting());
+        assert!(path.compute(from, to));
+        assert_eq!(3, path.len());
+        assert!(!path.neighbors(from).any(|p| p == to));
+        assert!(path.neighbors(from).any(|p| p == Position::new(1, 1)));
+
+        path.set_corner_cutting(CornerCutting::NeedsBothOpen);
+        assert!(path.compute(from, to));
+        let mut previous = from;
+        for step in walk_all(&mut path) {
+            if step.x != previous.x && step.y != previous.y {
+                assert!(map.is_walkable((step.x as u32, previous.y as u32).into()));
+                assert!(map.is_walkable((previous.x as u32, step.y as u32).into()));
+            }
+            previous = step;
+        }
+        assert!(!path.neighbors(from).any(|p| p == Position::new(1, 1)));
+
+        let mut dijkstra = Dijkstra::new(&map, 1.0);
+        dijkstra.compute(from);
+        assert_eq!(Some(1.0), dijkstra.distance(to));
+        dijkstra.set_allow_corner_cutting(false);
+        dijkstra.compute(from);
+        assert_eq!(Some(3.0), dijkstra.distance(to));
+    }
+
+    #[test]
+    fn path_length_and_cost() {
+        let rows = ["~~~~~~~~~", "=~~~~~~~=", "=========", "#########"];
+        let cost =
+            move |_: i32, _: i32, x: i32, y: i32| match rows[y as usize].as_bytes()[x as usize] {
+                b'=' => 1.0,
+                b'~' => 5.0,
+                _ => 0.0,
+            };
+
+        let mut path = AStar::new_with_callback(9, 4, cost, 0.0);
+        assert!(path.is_empty());
+        assert_eq!(0.0, path.cost());
+
+        assert!(path.compute(Position::new(0, 0), Position::new(8, 0)));
+        assert_eq!(12, path.len());
+        assert_eq!(16.0, path.cost());
+        assert_eq!(Some(Position::new(0, 1)), path.get(0));
+        assert_eq!(Some(Position::new(8, 0)), path.get(11));
+        assert_eq!(None, path.get(12));
+
+        path.walk();
+        assert_eq!(11, path.len());
+        assert_eq!(15.0, path.cost());
+        assert_eq!(Some(Position::new(0, 2)), path.get(0));
+
+        assert!(!path.compute(Position::new(0, 0), Position::new(4, 3)));
+        assert!(path.is_empty());
+        assert_eq!(0, path.len());
+        assert_eq!(0.0, path.cost());
+        assert_eq!(None, path.get(0));
+    }
+
+    #[test]
+    fn path_costs_match_dijkstra_for_any_diagonal_cost() {
+        let map = fixture(&[
+            "............",
+            "....#.......",
+            ".....#......",
+            "..##........",
+        ]);
+        let routes = [
+            (Position::new(0, 1), Position::new(10, 1)),
+            (Position::new(0, 0), Position::new(11, 3)),
+            (Position::new(3, 2), Position::new(8, 0)),
+            (Position::new(11, 0), Position::new(0, 3)),
+        ];
+        for &diagonal_cost in &[0.3, 0.6, 0.9, 1.5] {
+            let mut finder = AStar::new(&map, diagonal_cost);
+            let mut dijkstra = Dijkstra::new(&map, diagonal_cost);
+            for &(from, to) in &routes {
+                assert!(finder.compute(from, to));
+                dijkstra.compute(from);
+                let expected = dijkstra.distance(to).unwrap();
+                assert!(
+                    (finder.cost() - expected).abs() < 1e-4,
+                    "{} to {} with diagonal cost {}: {} instead of {}",
+                    from,
+                    to,
+                    diagonal_cost,
+                    finder.cost(),
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn recomputing_matches_fresh_path_finders() {
+        let map = fixture(&[
+            "...#......",
+            ".#.#.####.",
+            ".#...#....",
+            ".#####.##.",
+            "......#...",
+        ]);
+        let routes = [
+            (Position::new(0, 0), Position::new(9, 4)),
+            (Position::new(9, 0), Position::new(0, 4)),
+            (Position::new(4, 2), Position::new(4, 2)),
+            (Position::new(0, 0), Position::new(1, 1)),
+            (Position::new(7, 4), Position::new(2, 0)),
+        ];
+        let mut reused = AStar::new(&map, 1.41);
+        for &(from, to) in routes.iter().chain(routes.iter().rev()) {
+            let mut fresh = AStar::new(&map, 1.41);
+            assert_eq!(fresh.compute(from, to), reused.compute(from, to));
+            assert_eq!(fresh.steps(), reused.steps());
+            assert_eq!(walk_all(&mut fresh), reused.steps());
+        }
+
+        assert!(reused.compute(Position::new(0, 0), Position::new(2, 2)));
+        let first = reused.steps()[0];
+        assert_eq!(Some(first), reused.walk());
+        assert_eq!(reused.steps().last(), Some(&Position::new(2, 2)));
+        assert!(!reused.steps().contains(&first));
+    }
+}
@@ -0,0 +1,368 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::fov::FovMap;
+use crate::path::{Grid, Node};
+use crate::Position;
+
+/// The Jump Point Search path finding algorithm, working on the walkable cells of a map.
+///
+/// Jump Point Search finds paths just as short as [`AStar`](struct.AStar.html) does, but skips
+/// over the many equally short alternatives open areas offer, so it expands far fewer cells.
+/// It requires every orthogonal move to cost the same, so it only works with walkable maps, not
+/// cost callbacks, and diagonal moves always cost `√2`.
+#[derive(Debug)]
+pub struct JumpPointSearch {
+    grid: Grid,
+    origin: Position,
+    destination: Position,
+    path: Vec<Position>,
+    /* The index into `path` of the next step to walk */
+    next: usize,
+    /* Reused between expansions to avoid reallocating */
+    directions: Vec<(i32, i32)>,
+}
+
+impl JumpPointSearch {
+    /// Returns a new path finder for the walkable cells of the given map. Later changes to the
+    /// map are not seen by the path finder.
+    pub fn new(map: &FovMap) -> Self {
+        Self {
+            grid: Grid::from_map(map, std::f32::consts::SQRT_2),
+            origin: Position::ORIGIN,
+            destination: Position::ORIGIN,
+            path: Vec::new(),
+            next: 0,
+            directions: Vec::with_capacity(8),
+        }
+    }
+
+    /// Computes the shortest path between `from` and `to`, replacing the previously computed
+    /// one. Returns whether a path was found.
+    ///
+    /// The path excludes `from` but includes `to`. The `from` cell does not need to be walkable,
+    /// but `to` does.
+    ///
+    /// The buffers used by the computation are kept and reused by the next one, so computing
+    /// paths repeatedly with the same path finder doesn't allocate.
+    pub fn compute(&mut self, from: Position, to: Position) -> bool {
+        self.origin = from;
+        self.destination = to;
+        self.path.clear();
+        self.next = 0;
+        self.grid.reset();
+        self.grid.reset_stats();
+
+        let (start, goal) = match (self.grid.index(from), self.grid.index(to)) {
+            (Some(start), Some(goal)) if !self.grid.is_blocked(goal) => (start, goal),
+            _ => return false,
+        };
+
+        self.grid.distances[start] = 0.0;
+        self.grid.push_open(Node {
+            estimate: self.grid.heuristic(start, goal),
+            index: start,
+        });
+        while let Some(Node { index, .. }) = self.grid.open.pop() {
+            if index == goal {
+                break;
+            }
+            if self.grid.closed[index] {
+                continue;
+            }
+            self.grid.closed[index] = true;
+            self.grid.expanded += 1;
+
+            let position = self.grid.position(index);
+            let parent = self.grid.previous[index].map(|parent| self.grid.position(parent));
+            let mut directions = std::mem::take(&mut self.directions);
+            self.pruned_directions(position, parent, &mut directions);
+            for &(dx, dy) in &directions {
+                let next = Position::new(position.x + dx, position.y + dy);
+                let neighbor = match self.jump(next, (dx, dy), to) {
+                    Some(jump_point) => self
+                        .grid
+                        .index(jump_point)
+                        .expect("jump points are on the map"),
+                    None => continue,
+                };
+                let neighbor_distance =
+                    self.grid.distances[index] + self.grid.heuristic(index, neighbor);
+                if neighbor_distance < self.grid.distances[neighbor] {
+                    self.grid.distances[neighbor] = neighbor_distance;
+                    self.grid.previous[neighbor] = Some(index);
+                    self.grid.push_open(Node {
+                        estimate: neighbor_distance + self.grid.heuristic(neighbor, goal),
+                        index: neighbor,
+                    });
+                }
+            }
+            self.directions = directions;
+        }
+
+        if start != goal && self.grid.previous[goal].is_none() {
+            return false;
+        }
+        /* Fill in the straight lines between the jump points */
+        let mut current = goal;
+        while current != start {
+            let parent =
+                self.grid.previous[current].expect("every jump point on the path has a parent");
+            let target = self.grid.position(parent);
+            let mut step = self.grid.position(current);
+            while step != target {
+                self.path.push(step);
+                step.x += (target.x - step.x).signum();
+                step.y += (target.y - step.y).signum();
+            }
+            current = parent;
+        }
+        self.path.reverse();
+        true
+    }
+
+    /// Returns how many cells the last computation expanded; see
+    /// [`AStar::nodes_expanded`](struct.AStar.html#method.nodes_expanded).
+    pub fn nodes_expanded(&self) -> usize {
+        self.grid.expanded
+    }
+
+    /// Returns the largest number of cells the last computation had waiting to be expanded at
+    /// once; see [`AStar::max_open_set_size`](struct.AStar.html#method.max_open_set_size).
+    pub fn max_open_set_size(&self) -> usize {
+        self.grid.max_open
+    }
+
+    /// Returns the steps of the path that haven't been walked yet, in order. The slice is only
+    /// valid until the next computation.
+    pub fn steps(&self) -> &[Position] {
+        &self.path[self.next..]
+    }
+
+    /// Returns the position the path currently starts from.
+    pub fn origin(&self) -> Position {
+        self.origin
+    }
+
+    /// Returns the position the path leads to.
+    pub fn destination(&self) -> Position {
+        self.destination
+    }
+
+    /// Takes the next step along the path, returning the position stepped to, or `None` if the
+    /// destination has been reached.
+    pub fn walk(&mut self) -> Option<Position> {
+        let step = *self.path.get(self.next)?;
+        self.next += 1;
+        self.origin = step;
+        Some(step)
+    }
+
+    fn walkable(&self, x: i32, y: i32) -> bool {
+        self.grid.is_walkable(Position::new(x, y))
+    }
+
+    /* The directions worth exploring from a cell, given the jump point it was reached from */
+    fn pruned_directions(
+        &self,
+        position: Position,
+        parent: Option<Position>,
+        directions: &mut Vec<(i32, i32)>,
+    ) {
+        let Position { x, y } = position;
+        directions.clear();
+        let parent = if let Some(parent) = parent {
+            parent
+        } else {
+            /* The start cell explores every direction */
+            let all = (-1..=1)
+                .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                .filter(|&(dx, dy)| (dx, dy) != (0, 0) && self.walkable(x + dx, y + dy));
+            directions.extend(all);
+            return;
+        };
+
+        let dx = (x - parent.x).signum();
+        let dy = (y - parent.y).signum();
+        if dx != 0 && dy != 0 {
+            directions.extend_from_slice(&[(0, dy), (dx, 0), (dx, dy)]);
+            if !self.walkable(x - dx, y) {
+                directions.push((-dx, dy));
+            }
+            if !self.walkable(x, y - dy) {
+                directions.push((dx, -dy));
+            }
+        } else if dx == 0 {
+            directions.push((0, dy));
+            if !self.walkable(x + 1, y) {
+                directions.push((1, dy));
+            }
+            if !self.walkable(x - 1, y) {
+                directions.push((-1, dy));
+            }
+        } else {
+            directions.push((dx, 0));
+            if !self.walkable(x, y + 1) {
+                directions.push((dx, 1));
+            }
+            if !self.walkable(x, y - 1) {
+                directions.push((dx, -1));
+            }
+        }
+        directions.retain(|&(ddx, ddy)| self.walkable(x + ddx, y + ddy));
+    }
+
+    /* Moves from `position` in the given direction until reaching a cell worth expanding: the
+     * goal, or a cell with a neighbor that can't be reached as cheaply without passing it */
+    fn jump(
+        &self,
+        mut position: Position,
+        (dx, dy): (i32, i32),
+        goal: Position,
+    ) -> Option<Position> {
+        loop {
+            let Position { x, y } = position;
+            if !self.walkable(x, y) {
+                return None;
+            }
+            if position == goal {
+                return Some(position);
+            }
+
+            let forced = if dx != 0 && dy != 0 {
+                (self.walkable(x - dx, y + dy) && !self.walkable(x - dx, y))
+                    || (self.walkable(x + dx, y - dy) && !self.walkable(x, y - dy))
+                    || self.jump(Position::new(x + dx, y), (dx, 0), goal).is_some()
+                    || self.jump(Position::new(x, y + dy), (0, dy), goal).is_some()
+            } else if dx != 0 {
+                (self.walkable(x + dx, y + 1) && !self.walkable(x, y + 1))
+                    || (self.walkable(x + dx, y - 1) && !self.walkable(x, y - 1))
+            } else {
+                (self.walkable(x + 1, y + dy) && !self.walkable(x + 1, y))
+                    || (self.walkable(x - 1, y + dy) && !self.walkable(x - 1, y))
+            };
+            if forced {
+                return Some(position);
+            }
+
+            position = Position::new(x + dx, y + dy);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fov::{fixture, FovMap};
+    use crate::path::{AStar, JumpPointSearch};
+    use crate::Position;
+
+    /* The cost of walking a path, checking that every step is to a walkable neighbor */
+    fn walk_cost(map: &FovMap, from: Position, mut walk: impl FnMut() -> Option<Position>) -> f32 {
+        let mut cost = 0.0;
+        let mut previous = from;
+        while let Some(step) = walk() {
+            let (dx, dy) = ((step.x - previous.x).abs(), (step.y - previous.y).abs());
+            assert!(dx <= 1 && dy <= 1 && dx + dy > 0);
+            assert!(map.is_walkable((step.x as u32, step.y as u32).into()));
+            cost += if dx + dy == 2 {
+                std::f32::consts::SQRT_2
+            } else {
+                1.0
+            };
+            previous = step;
+        }
+        cost
+    }
+
+    #[test]
+    fn same_path_cost_as_a_star() {
+        let maps = [
+            fixture(&[
+                "..........",
+                "....#.....",
+                "....#..#..",
+                "....#..#..",
+                "..###..#..",
+                ".......#..",
+            ]),
+            fixture(&[
+                "...#......",
+                ".#.#.####.",
+                ".#...#....",
+                ".#####.##.",
+                "......#...",
+                "####.....#",
+            ]),
+            fixture(&["....", "....", "...."]),
+        ];
+        let routes = [
+            (Position::new(0, 0), Position::new(9, 5)),
+            (Position::new(9, 0), Position::new(0, 4)),
+            (Position::new(0, 5), Position::new(8, 2)),
+            (Position::new(2, 1), Position::new(3, 2)),
+        ];
+        for map in &maps {
+            let mut a_star = AStar::new(map, std::f32::consts::SQRT_2);
+            let mut jps = JumpPointSearch::new(map);
+            for &(from, to) in &routes {
+                let found = a_star.compute(from, to);
+                assert_eq!(found, jps.compute(from, to), "{} -> {}", from, to);
+                let a_star_cost = walk_cost(map, from, || a_star.walk());
+                let jps_cost = walk_cost(map, from, || jps.walk());
+                assert!(
+                    (a_star_cost - jps_cost).abs() < 1.0e-4,
+                    "{} -> {}",
+                    from,
+                    to
+                );
+                if found {
+                    assert_eq!(to, jps.origin());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn expands_fewer_cells_on_an_open_field() {
+        let mut map = FovMap::new(50, 50);
+        map.clear(true, true);
+        let (from, to) = (Position::new(2, 3), Position::new(47, 31));
+
+        let mut a_star = AStar::new(&map, std::f32::consts::SQRT_2);
+        let mut jps = JumpPointSearch::new(&map);
+        assert!(a_star.compute(from, to));
+        assert!(jps.compute(from, to));
+        assert!(jps.nodes_expanded() < a_star.nodes_expanded());
+        assert!(jps.nodes_expanded() <= 3);
+        assert!(jps.max_open_set_size() < a_star.max_open_set_size());
+    }
+}
@@ -37,9 +37,16 @@
 //! This toolkit used to be named `mersenne` in libtcod.
 
 pub mod algorithms;
-
-use crate::random::algorithms::Algorithm;
-use crate::random::algorithms::{ComplementaryMultiplyWithCarry, MersenneTwister};
+pub mod distributions;
+mod shared;
+pub use shared::SharedRng;
+
+use crate::color::Color;
+use crate::random::algorithms::{
+    entropy_seed, AlgorithmKind, ComplementaryMultiplyWithCarry, MersenneTwister, SplitMix64,
+};
+use crate::random::algorithms::{Algorithm, Bits};
+use crate::FPosition;
 use std::cmp::Ordering;
 use std::time::SystemTime;
 
@@ -48,6 +55,14 @@ pub trait Rng {
     /// Get an `i32` between `min` and `max`.
     fn get_i32(&mut self, min: i32, max: i32) -> i32;
 
+    /// Get an `i32` between `min` and `max` (both inclusive), with a linear distribution. If
+    /// `min` is greater than `max`, they are swapped.
+    ///
+    /// With the `libtcod-compat` feature enabled, the number is mapped onto the range by modulo
+    /// just like in libtcod, bias included, so that ported code generates the same sequences.
+    /// Otherwise, every number in the range is equally likely.
+    fn get_int(&mut self, min: i32, max: i32) -> i32;
+
     /// Get an `f32` between `min` and `max`.
     fn get_f32(&mut self, min: f32, max: f32) -> f32;
 
@@ -75,6 +90,9 @@ pub struct Random<A: Algorithm> {
 
     // Used for gaussian result caching
     y2: Option<f64>,
+
+    /* the seed substreams are derived from; unknown for algorithms passed in from the outside */
+    seed: Option<u64>,
 }
 
 impl<A: Algorithm> Random<A> {
@@ -120,9 +138,598 @@ impl<A: Algorithm> Random<A> {
         self.algo.get_double() * delta + min
     }
 
+    /// Get a `u32` between 0 (inclusive) and `bound` (exclusive), regardless of the current
+    /// `distribution`.
+    ///
+    /// Unlike taking the generated number modulo `bound`, this uses rejection sampling, so every
+    /// number in the range is equally likely, even when `bound` isn't a power of two.
+    ///
+    /// # Panics
+    /// If `bound` is 0.
+    pub fn get_bounded_u32(&mut self, bound: u32) -> u32 {
+        assert_ne!(0, bound, "The bound must be greater than 0.");
+
+        // Numbers below the threshold would make the lower results more likely than the
+        // higher ones, since 2^32 isn't necessarily divisible by the bound.
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let number = self.algo.get_int();
+            if number >= threshold {
+                return number % bound;
+            }
+        }
+    }
+
+    /// Get an `i32` between `min` and `max` (both inclusive), regardless of the current
+    /// `distribution`.
+    ///
+    /// Every number in the range is equally likely; see
+    /// [`get_bounded_u32`](#method.get_bounded_u32). If `min` is greater than `max`, they are
+    /// swapped. If they are equal, that value is returned without generating a number.
+    pub fn get_int_range(&mut self, mut min: i32, mut max: i32) -> i32 {
+        match max.cmp(&min) {
+            Ordering::Less => std::mem::swap(&mut min, &mut max),
+            Ordering::Equal => return min,
+            Ordering::Greater => (),
+        }
+
+        let span = max.wrapping_sub(min) as u32;
+        let offset = match span.checked_add(1) {
+            Some(bound) => self.get_bounded_u32(bound),
+            None => self.algo.get_int(),
+        };
+
+        min.wrapping_add(offset as i32)
+    }
+
+    /// Get a `u32` in the given `range`, regardless of the current `distribution`.
+    ///
+    /// Every number in the range is equally likely; see
+    /// [`get_bounded_u32`](#method.get_bounded_u32). If the start of the range is greater than
+    /// its end, they are swapped. If the range is empty, its start is returned without
+    /// generating a number.
+    pub fn get_u32_range(&mut self, range: std::ops::Range<u32>) -> u32 {
+        let (start, end) = if range.end < range.start {
+            (range.end, range.start)
+        } else {
+            (range.start, range.end)
+        };
+        if start == end {
+            return start;
+        }
+
+        start + self.get_bounded_u32(end - start)
+    }
+
+    /// Get an `f32` between `min` and `max` (both inclusive), regardless of the current
+    /// `distribution`.
+    ///
+    /// Rather than scaling a number between 0 and 1, which can only produce a fraction of the
+    /// numbers in the range, the number is generated directly in the range using an extension of
+    /// the Allen Downey algorithm used by `Algorithm::get_float`. This makes every representable
+    /// number in the range possible, with a probability proportional to the distance to the
+    /// next representable number. If `min` is greater than `max`, they are swapped. If they are
+    /// equal, that value is returned.
+    pub fn get_float_range(&mut self, min: f32, max: f32) -> f32 {
+        self.get_range(min, max)
+    }
+
+    /// Get an `f64` between `min` and `max` (both inclusive), regardless of the current
+    /// `distribution`.
+    ///
+    /// See [`get_float_range`](#method.get_float_range) for details.
+    pub fn get_double_range(&mut self, min: f64, max: f64) -> f64 {
+        self.get_range(min, max)
+    }
+
+    /// Get an `f32` between 0 and 1 (both inclusive), regardless of the current
+    /// `distribution`, like libtcod's `TCOD_random_get_float(rng, 0.0f, 1.0f)`.
+    ///
+    /// This is the same as [`get_float_range(0.0, 1.0)`](#method.get_float_range). Unlike
+    /// `Algorithm::get_float`, whose numbers are meant to be scaled, every representable number
+    /// in the range is weighted by the distance to the next representable number, and 1.0 is
+    /// weighted by the distance to the number above it, so its probability is no longer
+    /// dependent on how `get_float` rounds; it comes up about once in every 2<sup>23</sup>
+    /// numbers.
+    pub fn get_float_inclusive(&mut self) -> f32 {
+        self.get_range(0.0, 1.0)
+    }
+
+    /// Get an `f64` between 0 and 1 (both inclusive), regardless of the current
+    /// `distribution`.
+    ///
+    /// See [`get_float_inclusive`](#method.get_float_inclusive) for details; 1.0 comes up about
+    /// once in every 2<sup>52</sup> numbers.
+    pub fn get_double_inclusive(&mut self) -> f64 {
+        self.get_range(0.0, 1.0)
+    }
+
+    fn get_range<F: RangeFloat>(&mut self, mut min: F, mut max: F) -> F {
+        if max.to_f64() < min.to_f64() {
+            std::mem::swap(&mut min, &mut max);
+        }
+        let (low, high) = (min.to_f64(), max.to_f64());
+        #[allow(clippy::float_cmp)]
+        if low == high {
+            return min;
+        }
+
+        if low >= 0.0 {
+            F::from_bits(self.get_positive_range_bits::<F>(min.abs_bits(), max.abs_bits()))
+        } else if high <= 0.0 {
+            F::from_bits(
+                self.get_positive_range_bits::<F>(max.abs_bits(), min.abs_bits()) | F::SIGN,
+            )
+        } else if self.algo.get_double() * (high - low) < -low {
+            F::from_bits(self.get_positive_range_bits::<F>(0, min.abs_bits()) | F::SIGN)
+        } else {
+            F::from_bits(self.get_positive_range_bits::<F>(0, max.abs_bits()))
+        }
+    }
+
+    /* picks the bits of a non-negative number between two non-negative numbers' bits */
+    fn get_positive_range_bits<F: RangeFloat>(&mut self, low: u64, high: u64) -> u64 {
+        // Numbers with the same exponent are evenly spaced, as are the subnormal numbers and
+        // the numbers with the lowest exponent, so they're all equally likely.
+        let exponent = |bits: u64| (bits >> F::MANTISSA_BITS).max(1);
+        let (low_exponent, high_exponent) = (exponent(low), exponent(high));
+        if low_exponent == high_exponent {
+            return low + self.get_bounded_u64(high - low + 1);
+        }
+
+        // Otherwise, the partially covered exponents at either end are chosen by their share of
+        // the range, while the exponents in between are chosen by walking down the exponents
+        // like the Downey algorithm does.
+        let value = |bits: u64| F::from_bits(bits).to_f64();
+        let top_start = high_exponent << F::MANTISSA_BITS;
+        let bottom_end = (low_exponent + 1) << F::MANTISSA_BITS;
+        // Each number covers the distance up to the next number, including `high`.
+        let top = value(high + 1) - value(top_start);
+        let bottom = value(bottom_end) - value(low);
+        let middle = value(top_start) - value(bottom_end);
+        let target = self.algo.get_double() * (top + middle + bottom);
+
+        let (start, end) = if target < top {
+            (top_start, high)
+        } else if target < top + bottom || low_exponent + 1 == high_exponent {
+            (low, bottom_end - 1)
+        } else {
+            let e = self.get_exponent(low_exponent + 1, high_exponent - 1);
+            (e << F::MANTISSA_BITS, ((e + 1) << F::MANTISSA_BITS) - 1)
+        };
+
+        start + self.get_bounded_u64(end - start + 1)
+    }
+
+    /* picks an exponent between low and high (both inclusive) with probability proportional to
+    the size of the numbers it covers */
+    fn get_exponent(&mut self, low: u64, high: u64) -> u64 {
+        let mut bits = Bits::new(&mut self.algo);
+        loop {
+            let mut e = high;
+            while e > low {
+                if bits.get_bit() != 0 {
+                    return e;
+                }
+                e -= 1;
+            }
+            // The lowest exponent covers half as much as the one above it.
+            if bits.get_bit() != 0 {
+                return e;
+            }
+        }
+    }
+
+    /// Shuffles `slice` in place, using the Fisher-Yates algorithm.
+    ///
+    /// # Panics
+    /// If `slice` has more than 2^32 elements.
+    pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+        assert!(
+            slice.len() as u64 <= 1 << 32,
+            "Only slices of up to 2^32 elements can be shuffled."
+        );
+
+        for i in (1..slice.len()).rev() {
+            let j = self.get_bounded_u32((i + 1) as u32) as usize;
+            slice.swap(i, j);
+        }
+    }
+
+    /// Returns a shuffled copy of `slice`. See [`shuffle`](#method.shuffle) for details.
+    pub fn shuffled<T: Clone>(&mut self, slice: &[T]) -> Vec<T> {
+        let mut shuffled = slice.to_vec();
+        self.shuffle(&mut shuffled);
+
+        shuffled
+    }
+
+    /// Returns `k` items chosen uniformly at random from `iter`, without collecting all of its
+    /// items first.
+    ///
+    /// If `iter` yields no more than `k` items, all of them are returned in the order they were
+    /// encountered. Otherwise, the order of the returned items is unspecified.
+    pub fn sample_iter<T, I: Iterator<Item = T>>(&mut self, iter: I, k: usize) -> Vec<T> {
+        /* k may be far more than the number of items, so it can't be trusted for the capacity */
+        let mut reservoir = Vec::with_capacity(k.min(iter.size_hint().0));
+        if k == 0 {
+            return reservoir;
+        }
+
+        // Algorithm R: the nth item replaces a random item in the reservoir with probability k/n.
+        for (i, item) in iter.enumerate() {
+            if i < k {
+                reservoir.push(item);
+            } else {
+                let j = self.get_bounded_usize(i + 1);
+                if j < k {
+                    reservoir[j] = item;
+                }
+            }
+        }
+
+        reservoir
+    }
+
+    /// Samples a value from the given [`Distribution`](distributions/trait.Distribution.html).
+    ///
+    /// The `distribution` field isn't used; how the value is generated is entirely up to the
+    /// given distribution.
+    pub fn sample<T, D: distributions::Distribution<T>>(&mut self, distribution: &D) -> T {
+        distribution.sample(self)
+    }
+
+    /// Returns an endless iterator of values sampled from the given
+    /// [`Distribution`](distributions/trait.Distribution.html), like calling
+    /// [`sample`](#method.sample) repeatedly.
+    ///
+    /// Not to be confused with [`sample_iter`](#method.sample_iter), which picks items from an
+    /// iterator.
+    pub fn samples<'a, T: 'a, D: distributions::Distribution<T>>(
+        &'a mut self,
+        distribution: &'a D,
+    ) -> impl Iterator<Item = T> + 'a {
+        std::iter::repeat_with(move || distribution.sample(self))
+    }
+
+    /// Get a number in the given `range`, regardless of the current `distribution`.
+    ///
+    /// Unlike [`get_int_range`](#method.get_int_range), this works with any of the integer types
+    /// implementing [`SampleBound`](trait.SampleBound.html), so there's no need to cast to and
+    /// from `i32`, which could overflow for e.g. an index into a large map. Every number in the
+    /// range is equally likely; see [`get_bounded_u32`](#method.get_bounded_u32).
+    ///
+    /// # Panics
+    /// In debug builds, if the range is empty. In release builds, its start is returned without
+    /// generating a number instead.
+    pub fn gen_range<T: SampleBound>(&mut self, range: std::ops::Range<T>) -> T {
+        debug_assert!(
+            range.start < range.end,
+            "Cannot generate a number in an empty range."
+        );
+        if range.start >= range.end {
+            return range.start;
+        }
+
+        T::sample_range(self, range)
+    }
+
+    fn get_bounded_usize(&mut self, bound: usize) -> usize {
+        self.get_bounded_u64(bound as u64) as usize
+    }
+
+    fn get_bounded_u64(&mut self, bound: u64) -> u64 {
+        if bound <= u64::from(u32::MAX) {
+            return u64::from(self.get_bounded_u32(bound as u32));
+        }
+
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let number = self.algo.get_u64();
+            if number >= threshold {
+                return number % bound;
+            }
+        }
+    }
+
+    /// Returns `true` with the given `probability`, regardless of the current `distribution`.
+    ///
+    /// A `probability` of 0.0 or less always gives `false`, and one of 1.0 or more always gives
+    /// `true`; in both cases, no number is generated.
+    pub fn get_bool(&mut self, probability: f64) -> bool {
+        if probability <= 0.0 {
+            false
+        } else if probability >= 1.0 {
+            true
+        } else {
+            self.algo.get_double() < probability
+        }
+    }
+
+    /// Returns `true` with a probability of one in `n`, regardless of the current
+    /// `distribution`. If `n` is 1, `true` is returned without generating a number.
+    ///
+    /// # Panics
+    /// If `n` is 0.
+    pub fn one_in(&mut self, n: u32) -> bool {
+        assert_ne!(0, n, "n must be greater than 0.");
+
+        n == 1 || self.get_bounded_u32(n) == 0
+    }
+
+    /// Returns `base` with an offset between `-amount` and `amount` (both inclusive) added to
+    /// each of its red, green and blue components, regardless of the current `distribution`.
+    /// Each component gets an offset of its own, and saturates at 0 and 255. The alpha is kept.
+    ///
+    /// If `amount` is 0, `base` is returned without generating any numbers.
+    pub fn jitter_color(&mut self, base: Color, amount: u8) -> Color {
+        if amount == 0 {
+            return base;
+        }
+
+        let amount = i32::from(amount);
+        let mut jitter = |component: u8| {
+            (i32::from(component) + self.get_int_range(-amount, amount)).clamp(0, 255) as u8
+        };
+        Color::new_with_alpha(jitter(base.r), jitter(base.g), jitter(base.b), base.a)
+    }
+
+    /// Returns points scattered over the `width` by `height` area starting at the origin, no two
+    /// of which are closer to each other than `min_distance`, regardless of the current
+    /// `distribution`.
+    ///
+    /// Unlike points placed independently, which tend to clump together and leave gaps, these
+    /// are spread out evenly while still looking random ("blue noise"), which makes them a good
+    /// fit for placing things like trees or rocks. They're generated with Bridson's algorithm:
+    /// starting from a single random point, up to `k` candidates are tried around each point
+    /// before giving up on it, so a higher `k` packs the points more tightly, at the cost of
+    /// speed. [`POISSON_DISK_ATTEMPTS`](constant.POISSON_DISK_ATTEMPTS.html) is the usual choice.
+    ///
+    /// The points are returned as `(x, y)` tuples; each one converts into an `FPosition` with
+    /// `Into`.
+    ///
+    /// If the area is empty, or `min_distance` isn't positive, no points are returned.
+    pub fn poisson_disk_2d(
+        &mut self,
+        width: f32,
+        height: f32,
+        min_distance: f32,
+        k: usize,
+    ) -> Vec<(f32, f32)> {
+        let valid = |value: f32| value > 0.0 && value.is_finite();
+        if !(valid(width) && valid(height) && valid(min_distance)) {
+            return Vec::new();
+        }
+
+        /* cells are small enough to hold at most one point each, so only the cells within two
+        of a candidate's need to be checked */
+        let cell_size = min_distance / std::f32::consts::SQRT_2;
+        let columns = (width / cell_size).ceil() as usize;
+        let rows = (height / cell_size).ceil() as usize;
+        let cell_of = |point: FPosition| {
+            let column = ((point.x / cell_size) as usize).min(columns - 1);
+            let row = ((point.y / cell_size) as usize).min(rows - 1);
+            (column, row)
+        };
+        let mut grid: Vec<Option<usize>> = vec![None; columns * rows];
+
+        let first = FPosition::new(
+            self.get_float_range(0.0, width),
+            self.get_float_range(0.0, height),
+        );
+        let (first_column, first_row) = cell_of(first);
+        grid[first_row * columns + first_column] = Some(0);
+        let mut points = vec![first];
+        let mut active = vec![0];
+
+        while !active.is_empty() {
+            let active_index = self.get_u32_range(0..active.len() as u32) as usize;
+            let center = points[active[active_index]];
+
+            let mut found = false;
+            for _ in 0..k {
+                /* uniformly distributed over the annulus between min_distance and twice that */
+                let angle = self.get_float_range(0.0, 2.0 * std::f32::consts::PI);
+                let distance = min_distance * self.get_float_range(1.0, 4.0).sqrt();
+                let candidate = FPosition::new(
+                    center.x + distance * angle.cos(),
+                    center.y + distance * angle.sin(),
+                );
+                if !(candidate.x >= 0.0
+                    && candidate.x <= width
+                    && candidate.y >= 0.0
+                    && candidate.y <= height)
+                {
+                    continue;
+                }
+
+                let (column, row) = cell_of(candidate);
+                let too_close = (row.saturating_sub(2)..(row + 3).min(rows)).any(|r| {
+                    (column.saturating_sub(2)..(column + 3).min(columns)).any(|c| {
+                        grid[r * columns + c].is_some_and(|i| {
+                            let (dx, dy) = (points[i].x - candidate.x, points[i].y - candidate.y);
+                            dx * dx + dy * dy < min_distance * min_distance
+                        })
+                    })
+                });
+                if !too_close {
+                    grid[row * columns + column] = Some(points.len());
+                    active.push(points.len());
+                    points.push(candidate);
+                    found = true;
+                    break;
+                }
+            }
+
+            if !found {
+                active.swap_remove(active_index);
+            }
+        }
+
+        points.into_iter().map(Into::into).collect()
+    }
+
+    /// Returns an index into `weights`, chosen with a probability proportional to its weight,
+    /// or `None` if `weights` is empty or none of the weights are positive.
+    ///
+    /// Negative weights are considered an error, and will trigger an assertion in debug builds.
+    /// In release builds, they are treated as if they were zero.
+    pub fn weighted_index(&mut self, weights: &[f64]) -> Option<usize> {
+        self.get_weighted_index(weights.iter().copied())
+    }
+
+    /// Returns a reference to one of the items, chosen with a probability proportional to its
+    /// weight, or `None` if `items` is empty or none of the weights are positive.
+    ///
+    /// See [`weighted_index`](#method.weighted_index) for details.
+    pub fn weighted_choice<'a, T>(&mut self, items: &'a [(T, f64)]) -> Option<&'a T> {
+        self.get_weighted_index(items.iter().map(|&(_, w)| w))
+            .map(|i| &items[i].0)
+    }
+
+    /// Returns references to `n` different items, chosen with probabilities proportional to
+    /// their weights, regardless of the current `distribution`.
+    ///
+    /// This is like calling [`weighted_choice`](#method.weighted_choice) `n` times, except that
+    /// every item can only be chosen once, so it's useful for e.g. drawing a number of distinct
+    /// rewards. The items are returned in the order they were chosen, so the heavier ones tend to
+    /// come first. Items whose weights aren't positive are never chosen, and if there are no more
+    /// than `n` of the others, all of them are returned.
+    ///
+    /// Negative weights are considered an error, and will trigger an assertion in debug builds.
+    /// In release builds, they are treated as if they were zero.
+    pub fn sample_weighted_n<'a, T>(&mut self, items: &'a [(T, f64)], n: usize) -> Vec<&'a T> {
+        debug_assert!(
+            !items.iter().any(|&(_, w)| w < 0.0),
+            "Weights must not be negative."
+        );
+        if n == 0 {
+            return Vec::new();
+        }
+
+        /* Efraimidis and Spirakis' A-Res: give every item the key u^(1/w), where u is uniform
+        between 0 and 1, and take the items with the n largest keys; the logarithm of the key is
+        used, since it orders the same way, but doesn't underflow for small weights */
+        let mut keyed: Vec<(f64, &T)> = items
+            .iter()
+            .filter(|&&(_, w)| w > 0.0)
+            .map(|(item, w)| ((1.0 - self.algo.get_double()).ln() / w, item))
+            .collect();
+        let by_key = |a: &(f64, &T), b: &(f64, &T)| b.0.total_cmp(&a.0);
+        if n < keyed.len() {
+            keyed.select_nth_unstable_by(n, by_key);
+            keyed.truncate(n);
+        }
+        keyed.sort_unstable_by(by_key);
+
+        keyed.into_iter().map(|(_, item)| item).collect()
+    }
+
+    fn get_weighted_index<I: Iterator<Item = f64> + Clone>(&mut self, weights: I) -> Option<usize> {
+        debug_assert!(
+            !weights.clone().any(|w| w < 0.0),
+            "Weights must not be negative."
+        );
+
+        let total: f64 = weights.clone().filter(|&w| w > 0.0).sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let target = self.algo.get_double() * total;
+        let mut cumulative = 0.0;
+        let mut last = None;
+        for (i, w) in weights.enumerate().filter(|&(_, w)| w > 0.0) {
+            cumulative += w;
+            if target < cumulative {
+                return Some(i);
+            }
+            last = Some(i);
+        }
+
+        // Only reachable if the target ended up at the very end of the range, which can happen
+        // since the generated number may be exactly 1.0, or due to rounding.
+        last
+    }
+
+    /// Get an `f64` from an exponential distribution with the rate `lambda`, regardless of the
+    /// current `distribution`. The mean of the distribution is `1 / lambda`.
+    ///
+    /// # Panics
+    /// If `lambda` isn't positive.
+    pub fn get_exponential(&mut self, lambda: f64) -> f64 {
+        assert!(lambda > 0.0, "lambda must be positive.");
+
+        // Reject 1.0, which would make the logarithm infinite.
+        let u = loop {
+            let u = self.algo.get_double();
+            if u < 1.0 {
+                break u;
+            }
+        };
+
+        -(1.0 - u).ln() / lambda
+    }
+
+    /// Get a `u32` from a Poisson distribution with the mean `lambda`, regardless of the current
+    /// `distribution`.
+    ///
+    /// Small means use Knuth's multiplication method, while means of 10 or more use Hörmann's
+    /// transformed rejection method, so the cost doesn't grow with `lambda`.
+    ///
+    /// # Panics
+    /// If `lambda` isn't positive.
+    pub fn get_poisson(&mut self, lambda: f64) -> u32 {
+        assert!(lambda > 0.0, "lambda must be positive.");
+
+        if lambda < 10.0 {
+            let limit = (-lambda).exp();
+            let mut k = 0;
+            let mut p = self.algo.get_double();
+            while p > limit {
+                k += 1;
+                p *= self.algo.get_double();
+            }
+
+            return k;
+        }
+
+        /* "The transformed rejection method for generating Poisson random variables",
+        W. Hörmann, 1993 */
+        let sqrt_lambda = lambda.sqrt();
+        let ln_lambda = lambda.ln();
+        let b = 0.931 + 2.53 * sqrt_lambda;
+        let a = -0.059 + 0.024_83 * b;
+        let inv_alpha = 1.123_9 + 1.132_8 / (b - 3.4);
+        let v_r = 0.927_7 - 3.622_4 / (b - 2.0);
+
+        loop {
+            let u = self.algo.get_double() - 0.5;
+            let v = self.algo.get_double();
+            let us = 0.5 - u.abs();
+            let k = ((2.0 * a / us + b) * u + lambda + 0.43).floor();
+            if us >= 0.07 && v <= v_r {
+                return k as u32;
+            }
+            if k < 0.0 || (us < 0.013 && v > us) {
+                continue;
+            }
+            if v.ln() + inv_alpha.ln() - (a / (us * us) + b).ln()
+                <= -lambda + k * ln_lambda - ln_factorial(k)
+            {
+                return k as u32;
+            }
+        }
+    }
+
     /* Box-Muller transform (Gaussian distribution) */
 
-    fn get_gaussian_double(&mut self, mean: f64, std_deviation: f64) -> f64 {
+    /// Get an `f64` from a gaussian distribution with the given `mean` and `std_deviation`,
+    /// regardless of the current `distribution`.
+    ///
+    /// The numbers are generated in pairs using the Box-Muller transform; the second number of a
+    /// pair is kept and returned by the next call.
+    pub fn get_gaussian_double(&mut self, mean: f64, std_deviation: f64) -> f64 {
         if let Some(y2) = self.y2.take() {
             return mean + y2 * std_deviation;
         }
@@ -142,7 +749,11 @@ impl<A: Algorithm> Random<A> {
         mean + y1 * std_deviation
     }
 
-    fn get_gaussian_float(&mut self, mean: f32, std_deviation: f32) -> f32 {
+    /// Get an `f32` from a gaussian distribution with the given `mean` and `std_deviation`,
+    /// regardless of the current `distribution`.
+    ///
+    /// See [`get_gaussian_double`](#method.get_gaussian_double) for details.
+    pub fn get_gaussian_float(&mut self, mean: f32, std_deviation: f32) -> f32 {
         self.get_gaussian_double(f64::from(mean), f64::from(std_deviation)) as f32
     }
 
@@ -153,7 +764,12 @@ impl<A: Algorithm> Random<A> {
 
     /* Box-Muller, ranges */
 
-    fn get_gaussian_double_range(&mut self, mut min: f64, mut max: f64) -> f64 {
+    /// Get an `f64` between `min` and `max` from a gaussian distribution, regardless of the
+    /// current `distribution`.
+    ///
+    /// The mean lies in the middle of the range, and the standard deviation is a sixth of the
+    /// range, so that almost all values fall inside it. The few that don't are clamped to it.
+    pub fn get_gaussian_double_range(&mut self, mut min: f64, mut max: f64) -> f64 {
         if max < min {
             std::mem::swap(&mut min, &mut max);
         }
@@ -166,7 +782,11 @@ impl<A: Algorithm> Random<A> {
             .min(max)
     }
 
-    fn get_gaussian_float_range(&mut self, min: f32, max: f32) -> f32 {
+    /// Get an `f32` between `min` and `max` from a gaussian distribution, regardless of the
+    /// current `distribution`.
+    ///
+    /// See [`get_gaussian_double_range`](#method.get_gaussian_double_range) for details.
+    pub fn get_gaussian_float_range(&mut self, min: f32, max: f32) -> f32 {
         self.get_gaussian_double_range(f64::from(min), f64::from(max)) as f32
     }
 
@@ -195,7 +815,11 @@ impl<A: Algorithm> Random<A> {
             as f32
     }
 
-    fn get_gaussian_int_range_custom(&mut self, min: i32, max: i32, mean: i32) -> i32 {
+    fn get_gaussian_int_range_custom(&mut self, mut min: i32, mut max: i32, mean: i32) -> i32 {
+        if max < min {
+            std::mem::swap(&mut min, &mut max);
+        }
+
         (self
             .get_gaussian_double_range_custom(f64::from(min), f64::from(max), f64::from(mean))
             .round() as i32)
@@ -251,7 +875,11 @@ impl<A: Algorithm> Random<A> {
         self.get_gaussian_double_range_inv(f64::from(min), f64::from(max)) as f32
     }
 
-    fn get_gaussian_int_range_inv(&mut self, min: i32, max: i32) -> i32 {
+    fn get_gaussian_int_range_inv(&mut self, mut min: i32, mut max: i32) -> i32 {
+        if max < min {
+            std::mem::swap(&mut min, &mut max);
+        }
+
         (self
             .get_gaussian_double_range_inv(f64::from(min), f64::from(max))
             .round() as i32)
@@ -285,7 +913,11 @@ impl<A: Algorithm> Random<A> {
             as f32
     }
 
-    fn get_gaussian_int_range_custom_inv(&mut self, min: i32, max: i32, mean: i32) -> i32 {
+    fn get_gaussian_int_range_custom_inv(&mut self, mut min: i32, mut max: i32, mean: i32) -> i32 {
+        if max < min {
+            std::mem::swap(&mut min, &mut max);
+        }
+
         (self
             .get_gaussian_double_range_custom_inv(f64::from(min), f64::from(max), f64::from(mean))
             .round() as i32)
@@ -305,6 +937,21 @@ impl<A: Algorithm> Rng for Random<A> {
         }
     }
 
+    fn get_int(&mut self, min: i32, max: i32) -> i32 {
+        if cfg!(feature = "libtcod-compat") {
+            let (min, max) = if max < min { (max, min) } else { (min, max) };
+            /* a delta of 0 means the range covers every i32 */
+            let delta = max.wrapping_sub(min).wrapping_add(1) as u32;
+            let offset = match delta {
+                0 => self.algo.get_int(),
+                _ => self.algo.get_int() % delta,
+            };
+            min.wrapping_add(offset as i32)
+        } else {
+            self.get_int_range(min, max)
+        }
+    }
+
     fn get_f32(&mut self, min: f32, max: f32) -> f32 {
         match self.distribution {
             Distribution::Linear => self.get_f(min, max),
@@ -353,6 +1000,10 @@ impl<A: Algorithm> Rng for Random<A> {
     }
 }
 
+/// The number of candidates usually tried around each point by
+/// [`Random::poisson_disk_2d`](struct.Random.html#method.poisson_disk_2d).
+pub const POISSON_DISK_ATTEMPTS: usize = 30;
+
 impl Random<MersenneTwister> {
     /// Returns a new `Random` using the Mersenne Twister algorithm.
     pub fn new_mt() -> Self {
@@ -366,8 +1017,43 @@ impl Random<MersenneTwister> {
             distribution: Distribution::Linear,
 
             y2: None,
+            seed: Some(u64::from(seed)),
         }
     }
+
+    /// Returns a new `Random` using the Mersenne Twister algorithm, seeded from system entropy.
+    ///
+    /// This is non-deterministic; every call gives a different sequence. Use
+    /// [`new_mt_from_seed`](#method.new_mt_from_seed) if the sequence needs to be reproducible.
+    pub fn new_seeded() -> Self {
+        Self {
+            algo: MersenneTwister::new_seeded(),
+            distribution: Distribution::Linear,
+
+            y2: None,
+            seed: Some(entropy_seed()),
+        }
+    }
+
+    /// Reseeds the Mersenne Twister algorithm with the given `seed`, which restarts its sequence.
+    pub fn reseed(&mut self, seed: u32) {
+        self.algo = MersenneTwister::new(seed);
+        self.y2 = None;
+        self.seed = Some(u64::from(seed));
+    }
+
+    /// Returns an independent generator derived from this one's seed and the given `name`,
+    /// without consuming any of this generator's numbers.
+    ///
+    /// The same seed and name always give the same sequence, so giving each system of a game
+    /// its own substream, e.g. `"terrain"` or `"loot"`, keeps changes to how much randomness one
+    /// system uses from shifting the numbers every other system gets.
+    pub fn substream(&self, name: &str) -> Self {
+        let mut substream =
+            Self::new_mt_from_seed(substream_seed(self.seed.unwrap_or_default(), name));
+        substream.distribution = self.distribution;
+        substream
+    }
 }
 
 impl Random<ComplementaryMultiplyWithCarry> {
@@ -384,6 +1070,66 @@ impl Random<ComplementaryMultiplyWithCarry> {
             distribution: Distribution::Linear,
 
             y2: None,
+            seed: Some(u64::from(seed)),
+        }
+    }
+
+    /// Returns a new `Random` using the Complementary Multiply With Carry algorithm, seeded from
+    /// system entropy.
+    ///
+    /// This is non-deterministic; every call gives a different sequence. Use
+    /// [`new_cmwc_from_seed`](#method.new_cmwc_from_seed) if the sequence needs to be
+    /// reproducible.
+    pub fn new_seeded() -> Self {
+        Self {
+            algo: ComplementaryMultiplyWithCarry::new_seeded(),
+            distribution: Distribution::Linear,
+
+            y2: None,
+            seed: Some(entropy_seed()),
+        }
+    }
+
+    /// Reseeds the Complementary Multiply With Carry algorithm with the given `seed`, which
+    /// restarts its sequence.
+    pub fn reseed(&mut self, seed: u32) {
+        self.algo = ComplementaryMultiplyWithCarry::new(seed);
+        self.y2 = None;
+        self.seed = Some(u64::from(seed));
+    }
+
+    /// Returns an independent generator derived from this one's seed and the given `name`,
+    /// without consuming any of this generator's numbers.
+    ///
+    /// The same seed and name always give the same sequence, so giving each system of a game
+    /// its own substream, e.g. `"terrain"` or `"loot"`, keeps changes to how much randomness one
+    /// system uses from shifting the numbers every other system gets.
+    pub fn substream(&self, name: &str) -> Self {
+        let mut substream =
+            Self::new_cmwc_from_seed(substream_seed(self.seed.unwrap_or_default(), name));
+        substream.distribution = self.distribution;
+        substream
+    }
+}
+
+impl Random<Box<dyn Algorithm>> {
+    /// Returns a new `Random` using the algorithm of the given `kind`, seeded with the given
+    /// `seed`.
+    pub fn with_algorithm_kind(kind: AlgorithmKind, seed: u32) -> Self {
+        Self {
+            seed: Some(u64::from(seed)),
+            ..Self::from_boxed(kind.create(seed))
+        }
+    }
+
+    /// Returns a new `Random` using the given algorithm.
+    pub fn from_boxed(algo: Box<dyn Algorithm>) -> Self {
+        Self {
+            algo,
+            distribution: Distribution::Linear,
+
+            y2: None,
+            seed: None,
         }
     }
 }
@@ -405,6 +1151,113 @@ pub enum Distribution {
     GaussianRangeInverse,
 }
 
+/// An integer type that [`Random::gen_range`](struct.Random.html#method.gen_range) can generate.
+pub trait SampleBound: Copy + PartialOrd {
+    /// Returns a number in `range`, which is never empty, with every number in it equally
+    /// likely.
+    fn sample_range<A: Algorithm>(random: &mut Random<A>, range: std::ops::Range<Self>) -> Self;
+}
+
+/* the span of the range is computed in the unsigned type of the same width, where it can't
+overflow, and the offset into it added back with wrapping, which gives the right number even for
+signed types when the range crosses 0 */
+macro_rules! impl_sample_bound {
+    ($bounded:ident, $unsigned:ty; $($t:ty),+) => {
+        $(
+            impl SampleBound for $t {
+                #[allow(trivial_numeric_casts)] // The casts are no-ops for the unsigned types
+                fn sample_range<A: Algorithm>(
+                    random: &mut Random<A>,
+                    range: std::ops::Range<Self>,
+                ) -> Self {
+                    let span = range.end.wrapping_sub(range.start) as $unsigned;
+                    range.start.wrapping_add(random.$bounded(span) as Self)
+                }
+            }
+        )+
+    };
+}
+
+impl_sample_bound!(get_bounded_u32, u32; u32, i32);
+impl_sample_bound!(get_bounded_u64, u64; u64, i64);
+
+impl SampleBound for usize {
+    fn sample_range<A: Algorithm>(random: &mut Random<A>, range: std::ops::Range<Self>) -> Self {
+        u64::sample_range(random, range.start as u64..range.end as u64) as Self
+    }
+}
+
+/* the operations needed for generating floating point numbers in a range */
+trait RangeFloat: Copy {
+    const MANTISSA_BITS: u32;
+    const SIGN: u64;
+
+    fn to_f64(self) -> f64;
+    fn abs_bits(self) -> u64;
+    fn from_bits(bits: u64) -> Self;
+}
+
+impl RangeFloat for f32 {
+    const MANTISSA_BITS: u32 = 23;
+    const SIGN: u64 = 1 << 31;
+
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    fn abs_bits(self) -> u64 {
+        u64::from(self.abs().to_bits())
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        Self::from_bits(bits as u32)
+    }
+}
+
+impl RangeFloat for f64 {
+    const MANTISSA_BITS: u32 = 52;
+    const SIGN: u64 = 1 << 63;
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn abs_bits(self) -> u64 {
+        self.abs().to_bits()
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        Self::from_bits(bits)
+    }
+}
+
+/* derives the seed of a named substream, using FNV-1a since, unlike the standard library's
+ * hasher, it's guaranteed to stay the same across platforms and Rust versions */
+fn substream_seed(seed: u64, name: &str) -> u32 {
+    let hash = seed
+        .to_le_bytes()
+        .iter()
+        .chain(name.as_bytes())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+
+    /* FNV mixes the last few bytes poorly, so scramble it some more */
+    (SplitMix64::new(hash).get_u64() >> 32) as u32
+}
+
+/* the natural logarithm of k!, using Stirling's series for large k */
+fn ln_factorial(k: f64) -> f64 {
+    if k < 10.0 {
+        return (2..=k as u32).map(|i| f64::from(i).ln()).sum();
+    }
+
+    let k2 = k * k;
+    k * k.ln() - k + 0.5 * (2.0 * std::f64::consts::PI * k).ln() + 1.0 / (12.0 * k)
+        - 1.0 / (360.0 * k2 * k)
+        + 1.0 / (1260.0 * k2 * k2 * k)
+}
+
 /* string hashing function */
 /* not used (yet)
 fn hash(data: &[u8]) -> u32 {
@@ -423,7 +1276,7 @@ fn hash(data: &[u8]) -> u32 {
 */
 
 /// Represents a set of dice and rules for calculating their value when rolled
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Dice {
     nb_rolls: i32,
     nb_faces: i32,
@@ -439,76 +1292,280 @@ impl Dice {
     /// * once all the dice have been thrown, `offset` is added to their value,
     /// * and finally, that number is multiplied by `mul`.
     ///
+    /// See the `FromStr` implementation for the full syntax, and for a way of handling invalid
+    /// specifications without panicking.
+    ///
     /// # Example
     /// ```
     /// # use doryen_extra::random::Dice;
     /// let dice = Dice::new("5*3d6+2");
     /// ```
+    ///
+    /// # Panics
+    /// If the dice specification is invalid.
     pub fn new<S: AsRef<str>>(s: S) -> Self {
-        let mut s = s.as_ref();
+        match s.as_ref().parse() {
+            Ok(dice) => dice,
+            Err(e) => panic!("Incorrect dice specification format: {}", e),
+        }
+    }
 
-        /* get multiplier */
-        let multiplier = if let Some(m) = s.find(|c| c == '*' || c == 'x') {
-            let value = s[0..m].parse::<f32>().unwrap_or_default();
-            s = &s[m + 1..];
+    /// Roll the dice according to their parameters. See the documentation of `new()` for how these
+    /// parameters get used.
+    pub fn roll<R: Rng>(&self, mersenne: &mut R) -> i32 {
+        let mut result = 0;
+        for _ in 0..self.nb_rolls {
+            /* the Gaussian distributions can go past the number of faces */
+            result = mersenne.get_i32(1, self.nb_faces).saturating_add(result);
+        }
 
-            value
-        } else {
-            1.0
-        };
+        self.apply_modifiers(result as f32)
+    }
 
-        /* get rolls */
-        let r = s
-            .find(|c| c == 'd' || c == 'D')
-            .expect("Incorrect dice specification format");
-        let nb_rolls = s[0..r].parse::<i32>().unwrap_or_default();
-        s = &s[r + 1..];
+    /// Create a `Dice` and roll these dice once according to the given dice specification. See the
+    /// documentation of `new()` for how this specification works. If you intend to use this dice
+    /// set more than once, it's generally better to store the `Dice` instance and call `roll()`
+    /// rather than to call this method over and over.
+    pub fn single_roll<R: Rng, S: AsRef<str>>(mersenne: &mut R, s: S) -> i32 {
+        Self::new(s).roll(mersenne)
+    }
 
-        /* get faces */
-        let nb_faces = if let Some(f) = s.find(|c| c == '+' || c == '-') {
-            let value = s[0..f].parse::<i32>().unwrap_or_default();
-            s = &s[f..];
+    /// Returns the smallest value `roll()` can return.
+    pub fn min(&self) -> i32 {
+        self.apply_modifiers(self.nb_rolls as f32)
+            .min(self.apply_modifiers((self.nb_rolls * self.nb_faces) as f32))
+    }
 
-            value
-        } else {
-            let value = s[0..].parse::<i32>().unwrap_or_default();
-            s = &s[s.len()..];
+    /// Returns the largest value `roll()` can return.
+    pub fn max(&self) -> i32 {
+        self.apply_modifiers(self.nb_rolls as f32)
+            .max(self.apply_modifiers((self.nb_rolls * self.nb_faces) as f32))
+    }
+
+    /// Returns the average value of the dice, before it's truncated to an integer.
+    pub fn mean(&self) -> f32 {
+        let mean_roll = self.nb_rolls as f32 * (self.nb_faces as f32 + 1.0) / 2.0;
+
+        (mean_roll + self.add_sub) * self.multiplier
+    }
+
+    fn apply_modifiers(&self, result: f32) -> i32 {
+        ((result + self.add_sub) * self.multiplier) as i32
+    }
+}
+
+impl std::str::FromStr for Dice {
+    type Err = DiceParseError;
+
+    /// Parses a dice specification of the form `[mul*]<rolls>d<faces>[+/-offset][*mul]`.
+    ///
+    /// * `x` may be used instead of `*`, and `D` instead of `d`.
+    /// * The multiplier may be given before the dice or after the offset, but not both.
+    /// * `rolls` may be left out, in which case a single die is thrown.
+    /// * The multiplier and offset may be decimal numbers.
+    /// * Whitespace is allowed between all the parts.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DiceParser { s, position: 0 }.parse()
+    }
+}
+
+impl std::fmt::Display for Dice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        #[allow(clippy::float_cmp)]
+        if self.multiplier != 1.0 {
+            write!(f, "{}x", self.multiplier)?;
+        }
+        write!(f, "{}d{}", self.nb_rolls, self.nb_faces)?;
+        if self.add_sub > 0.0 {
+            write!(f, "+{}", self.add_sub)?;
+        } else if self.add_sub < 0.0 {
+            write!(f, "{}", self.add_sub)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The error type returned when parsing a dice specification fails.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DiceParseError {
+    /// The byte position in the specification where the error was found.
+    pub position: usize,
+    /// The kind of error that was found.
+    pub kind: DiceParseErrorKind,
+}
+
+/// The kinds of errors that can occur when parsing a dice specification.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DiceParseErrorKind {
+    /// A number was expected but not found.
+    ExpectedNumber,
+    /// The `d` separating the number of rolls from the number of faces was not found.
+    ExpectedDice,
+    /// A number was found but could not be used, e.g. because it's too large, because the
+    /// number of faces was zero, or because the dice could roll more than `i32::MAX`.
+    InvalidNumber,
+    /// A character was found where none was expected.
+    UnexpectedCharacter(char),
+}
+
+impl std::fmt::Display for DiceParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid dice specification at position {}: ",
+            self.position
+        )?;
+        match self.kind {
+            DiceParseErrorKind::ExpectedNumber => write!(f, "expected a number"),
+            DiceParseErrorKind::ExpectedDice => write!(f, "expected 'd'"),
+            DiceParseErrorKind::InvalidNumber => write!(f, "invalid number"),
+            DiceParseErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character {:?}", c),
+        }
+    }
+}
+
+impl std::error::Error for DiceParseError {}
+
+struct DiceParser<'a> {
+    s: &'a str,
+    position: usize,
+}
+
+impl<'a> DiceParser<'a> {
+    fn parse(mut self) -> Result<Dice, DiceParseError> {
+        let mut multiplier = None;
+
+        /* get multiplier or rolls */
+        self.skip_whitespace();
+        let first = self.number();
+        self.skip_whitespace();
+        if let Some(first) = first {
+            if self.eat(&['*', 'x', 'X']) {
+                multiplier = Some(Self::to_f32(first)?);
+            } else {
+                self.position = first.0;
+            }
+        }
 
-            value
+        /* get rolls */
+        self.skip_whitespace();
+        let nb_rolls = match self.number() {
+            Some(rolls) => Self::to_i32(rolls)?,
+            None => 1,
         };
+        self.skip_whitespace();
+        if !self.eat(&['d', 'D']) {
+            return Err(self.error_here(DiceParseErrorKind::ExpectedDice));
+        }
+
+        /* get faces */
+        self.skip_whitespace();
+        let faces = self.expect_number()?;
+        let nb_faces = Self::to_i32(faces)?;
+        /* the largest possible roll must fit in an i32 */
+        if nb_faces < 1 || nb_rolls.checked_mul(nb_faces).is_none() {
+            return Err(DiceParseError {
+                position: faces.0,
+                kind: DiceParseErrorKind::InvalidNumber,
+            });
+        }
 
         /* get add_sub */
-        let add_sub = if s.is_empty() {
-            0.0
-        } else {
-            s[0..].parse::<f32>().unwrap_or_default()
-        };
+        self.skip_whitespace();
+        let mut add_sub = 0.0;
+        if let Some(sign) = self.peek().filter(|&c| c == '+' || c == '-') {
+            self.position += 1;
+            self.skip_whitespace();
+            add_sub = Self::to_f32(self.expect_number()?)?;
+            if sign == '-' {
+                add_sub = -add_sub;
+            }
+        }
 
-        Self {
-            multiplier,
+        /* get trailing multiplier */
+        self.skip_whitespace();
+        if multiplier.is_none() && self.eat(&['*', 'x', 'X']) {
+            self.skip_whitespace();
+            multiplier = Some(Self::to_f32(self.expect_number()?)?);
+        }
+
+        self.skip_whitespace();
+        if let Some(c) = self.peek() {
+            return Err(self.error_here(DiceParseErrorKind::UnexpectedCharacter(c)));
+        }
+
+        Ok(Dice {
             nb_rolls,
             nb_faces,
+            multiplier: multiplier.unwrap_or(1.0),
             add_sub,
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.s[self.position..].chars().next()
+    }
+
+    fn eat(&mut self, any_of: &[char]) -> bool {
+        match self.peek() {
+            Some(c) if any_of.contains(&c) => {
+                self.position += c.len_utf8();
+                true
+            }
+            _ => false,
         }
     }
 
-    /// Roll the dice according to their parameters. See the documentation of `new()` for how these
-    /// parameters get used.
-    pub fn roll<R: Rng>(&self, mersenne: &mut R) -> i32 {
-        let mut result = 0;
-        for _ in 0..self.nb_rolls {
-            result += mersenne.get_i32(1, self.nb_faces);
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.position += c.len_utf8();
+        }
+    }
+
+    /* returns the start position and text of the number at the current position, if any */
+    fn number(&mut self) -> Option<(usize, &'a str)> {
+        let start = self.position;
+        let s = self.s;
+        let rest = &s[start..];
+        let length = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        if length == 0 {
+            return None;
         }
+        self.position += length;
 
-        ((result as f32 + self.add_sub) * self.multiplier) as i32
+        Some((start, &rest[..length]))
     }
 
-    /// Create a `Dice` and roll these dice once according to the given dice specification. See the
-    /// documentation of `new()` for how this specification works. If you intend to use this dice
-    /// set more than once, it's generally better to store the `Dice` instance and call `roll()`
-    /// rather than to call this method over and over.
-    pub fn single_roll<R: Rng, S: AsRef<str>>(mersenne: &mut R, s: S) -> i32 {
-        Self::new(s).roll(mersenne)
+    fn expect_number(&mut self) -> Result<(usize, &'a str), DiceParseError> {
+        let position = self.position;
+        self.number().ok_or(DiceParseError {
+            position,
+            kind: DiceParseErrorKind::ExpectedNumber,
+        })
+    }
+
+    fn to_i32((position, number): (usize, &str)) -> Result<i32, DiceParseError> {
+        number.parse().map_err(|_| DiceParseError {
+            position,
+            kind: DiceParseErrorKind::InvalidNumber,
+        })
+    }
+
+    fn to_f32((position, number): (usize, &str)) -> Result<f32, DiceParseError> {
+        number.parse().map_err(|_| DiceParseError {
+            position,
+            kind: DiceParseErrorKind::InvalidNumber,
+        })
+    }
+
+    fn error_here(&self, kind: DiceParseErrorKind) -> DiceParseError {
+        DiceParseError {
+            position: self.position,
+            kind,
+        }
     }
 }
 
@@ -519,13 +1576,11 @@ impl<A: Algorithm> rand_core::RngCore for Random<A> {
     }
 
     fn next_u64(&mut self) -> u64 {
-        use rand_core::impls;
-        impls::next_u64_via_u32(self)
+        self.algo.get_u64()
     }
 
     fn fill_bytes(&mut self, dest: &mut [u8]) {
-        use rand_core::impls;
-        impls::fill_bytes_via_next(self, dest)
+        self.algo.fill_bytes(dest)
     }
 
     #[allow(clippy::unit_arg)] // Recommended by documentation
@@ -559,3 +1614,802 @@ impl rand_core::SeedableRng for Random<ComplementaryMultiplyWithCarry> {
         Self::new_cmwc_from_seed(seed)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::random::algorithms::{
+        Algorithm, AlgorithmKind, ComplementaryMultiplyWithCarry, MersenneTwister,
+    };
+    use crate::random::{
+        Dice, DiceParseError, DiceParseErrorKind, Random, Rng, SampleBound, POISSON_DISK_ATTEMPTS,
+    };
+    use std::collections::HashSet;
+
+    fn sorted_unique<T: Ord>(mut values: Vec<T>) -> Vec<T> {
+        values.sort_unstable();
+        values.dedup();
+        values
+    }
+
+    fn mean_and_std_deviation(values: &[f64]) -> (f64, f64) {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+
+        (mean, variance.sqrt())
+    }
+
+    #[test]
+    fn substreams_are_reproducible_and_independent() {
+        let mut parent = Random::new_mt_from_seed(1234);
+        let mut untouched = parent.clone();
+
+        let numbers =
+            |random: &mut Random<_>| (0..10).map(|_| random.get_i32(0, 1000)).collect::<Vec<_>>();
+        let terrain = numbers(&mut parent.substream("terrain"));
+        assert_eq!(terrain, numbers(&mut parent.substream("terrain")));
+        assert_ne!(terrain, numbers(&mut parent.substream("loot")));
+        assert_ne!(
+            terrain,
+            numbers(&mut Random::new_mt_from_seed(4321).substream("terrain"))
+        );
+        assert_eq!(numbers(&mut untouched), numbers(&mut parent));
+
+        let cmwc = Random::new_cmwc_from_seed(1234);
+        let loot: Vec<_> = (0..10)
+            .map(|_| cmwc.substream("loot").get_i32(0, 1000))
+            .collect();
+        assert!(loot.iter().all(|&n| n == loot[0]));
+    }
+
+    #[cfg(feature = "rng_support")]
+    #[test]
+    fn rng_core_matches_the_algorithm() {
+        use rand_core::RngCore;
+
+        let mut expected = MersenneTwister::new(42);
+        let mut sut = Random::new_mt_from_seed(42);
+        for _ in 0..10 {
+            assert_eq!(expected.get_u64(), sut.next_u64());
+        }
+
+        let mut expected_bytes = [0; 7];
+        let mut bytes = [0; 7];
+        Algorithm::fill_bytes(&mut expected, &mut expected_bytes);
+        RngCore::fill_bytes(&mut sut, &mut bytes);
+        assert_eq!(expected_bytes, bytes);
+    }
+
+    #[test]
+    fn get_int_is_inclusive_and_swaps_bounds() {
+        let mut random = Random::new_mt_from_seed(5);
+        let values: HashSet<_> = (0..1000).map(|_| random.get_int(3, -2)).collect();
+        assert_eq!((-2..=3).collect::<HashSet<_>>(), values);
+        assert_eq!(7, random.get_int(7, 7));
+        random.get_int(i32::MIN, i32::MAX);
+    }
+
+    #[test]
+    #[cfg(feature = "libtcod-compat")]
+    fn get_int_matches_libtcod() {
+        let mut random = Random::new_mt_from_seed(5489);
+        assert_eq!(
+            vec![12, 2, 34, 85, 4],
+            (0..5).map(|_| random.get_int(0, 99)).collect::<Vec<_>>()
+        );
+
+        let mut reversed = Random::new_mt_from_seed(5489);
+        assert_eq!(
+            vec![3, 3, 5, 6, 5],
+            (0..5).map(|_| reversed.get_int(10, 1)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn gaussian_double_has_requested_mean_and_std_deviation() {
+        let mut random = Random::new_mt_from_seed(42);
+        let values: Vec<_> = (0..100_000)
+            .map(|_| random.get_gaussian_double(10.0, 2.0))
+            .collect();
+
+        let (mean, std_deviation) = mean_and_std_deviation(&values);
+        assert!((mean - 10.0).abs() < 0.05, "mean was {}", mean);
+        assert!(
+            (std_deviation - 2.0).abs() < 0.05,
+            "standard deviation was {}",
+            std_deviation
+        );
+    }
+
+    #[test]
+    fn gaussian_float_has_requested_mean_and_std_deviation() {
+        let mut random = Random::new_cmwc_from_seed(42);
+        let values: Vec<_> = (0..100_000)
+            .map(|_| f64::from(random.get_gaussian_float(-5.0, 0.5)))
+            .collect();
+
+        let (mean, std_deviation) = mean_and_std_deviation(&values);
+        assert!((mean + 5.0).abs() < 0.05, "mean was {}", mean);
+        assert!(
+            (std_deviation - 0.5).abs() < 0.05,
+            "standard deviation was {}",
+            std_deviation
+        );
+    }
+
+    #[test]
+    fn gaussian_range_stays_in_range() {
+        let mut random = Random::new_mt_from_seed(42);
+        for _ in 0..10_000 {
+            let value = random.get_gaussian_float_range(10.0, -10.0);
+            assert!((-10.0..=10.0).contains(&value));
+        }
+    }
+
+    #[test]
+    fn int_mean_is_the_mode_and_stays_in_range() {
+        let mut random = Random::new_mt_from_seed(42);
+        for &(min, max, mean) in &[(0, 6, 2), (6, 0, 2), (0, 6, 0), (0, 6, 6), (7, 7, 7)] {
+            let mut counts = [0; 8];
+            for _ in 0..20_000 {
+                let value = random.get_i32_mean(min, max, mean);
+                assert!(value >= min.min(max) && value <= max.max(min));
+                counts[value as usize] += 1;
+            }
+
+            // Values that fall outside the range are clamped to it, so the end points are
+            // overrepresented unless the mean sits on one of them.
+            let (low, high) = (min.min(max) as usize, max.max(min) as usize);
+            let (low, high) = if mean as usize == low || mean as usize == high {
+                (low, high)
+            } else {
+                (low + 1, high - 1)
+            };
+            let mode = (low..=high).max_by_key(|&i| counts[i]).unwrap();
+            assert_eq!(mean as usize, mode);
+        }
+    }
+
+    #[test]
+    fn float_mean_stays_in_range() {
+        let mut random = Random::new_cmwc_from_seed(42);
+        for &(min, max, mean) in &[(0.0, 1.0, 0.25), (1.0, 0.0, 0.25), (0.0, 1.0, 1.0)] {
+            for _ in 0..20_000 {
+                let value = random.get_f32_mean(min, max, mean);
+                assert!((0.0..=1.0).contains(&value));
+            }
+        }
+        assert_eq!(3.0, random.get_f32_mean(3.0, 3.0, 3.0));
+    }
+
+    #[test]
+    fn dice_parses_libtcod_syntax() {
+        let dice: Dice = "3x2d6+1.5".parse().unwrap();
+        assert_eq!(
+            Dice {
+                nb_rolls: 2,
+                nb_faces: 6,
+                multiplier: 3.0,
+                add_sub: 1.5,
+            },
+            dice
+        );
+
+        assert_eq!(dice, " 2 D 6 + 1.5 * 3 ".parse().unwrap());
+        assert_eq!(Dice::new("1d20-2"), "d20 - 2".parse().unwrap());
+    }
+
+    #[test]
+    fn dice_reports_error_positions() {
+        let error = |position, kind| Err(DiceParseError { position, kind });
+
+        assert_eq!(
+            error(0, DiceParseErrorKind::ExpectedDice),
+            "".parse::<Dice>()
+        );
+        assert_eq!(
+            error(1, DiceParseErrorKind::ExpectedDice),
+            "3+6".parse::<Dice>()
+        );
+        assert_eq!(
+            error(2, DiceParseErrorKind::ExpectedNumber),
+            "3d".parse::<Dice>()
+        );
+        assert_eq!(
+            error(2, DiceParseErrorKind::InvalidNumber),
+            "3d0".parse::<Dice>()
+        );
+        assert_eq!(
+            error(0, DiceParseErrorKind::InvalidNumber),
+            "1.5d6".parse::<Dice>()
+        );
+        assert_eq!(
+            error(7, DiceParseErrorKind::InvalidNumber),
+            "100000d100000".parse::<Dice>()
+        );
+        let largest: Dice = "2d1073741823".parse().unwrap();
+        assert!(largest.max() > largest.min());
+        assert_eq!(
+            error(4, DiceParseErrorKind::ExpectedNumber),
+            "3d6+".parse::<Dice>()
+        );
+        assert_eq!(
+            error(6, DiceParseErrorKind::UnexpectedCharacter('x')),
+            "2x3d6 x2".parse::<Dice>()
+        );
+        assert_eq!(
+            error(3, DiceParseErrorKind::UnexpectedCharacter('?')),
+            "3d6?".parse::<Dice>()
+        );
+    }
+
+    #[test]
+    fn dice_display_round_trips() {
+        for &spec in &["3d6", "2x1d4+2", "0.5x4d8-1.5", "1d100-3", "0d6+1"] {
+            let dice: Dice = spec.parse().unwrap();
+            assert_eq!(spec, dice.to_string());
+            assert_eq!(Ok(dice), dice.to_string().parse());
+        }
+    }
+
+    #[test]
+    fn dice_min_max_and_mean() {
+        let dice = Dice::new("3d6+2");
+        assert_eq!(5, dice.min());
+        assert_eq!(20, dice.max());
+        assert!((dice.mean() - 12.5).abs() < f32::EPSILON);
+
+        let negative = Dice::new("2x1d4-5");
+        assert_eq!(-8, negative.min());
+        assert_eq!(-2, negative.max());
+
+        let mut random = Random::new_mt_from_seed(42);
+        let multiplied = Dice::new("2x3d6-1");
+        for _ in 0..1000 {
+            let roll = multiplied.roll(&mut random);
+            assert!((multiplied.min()..=multiplied.max()).contains(&roll));
+        }
+    }
+
+    #[test]
+    fn weighted_index_is_proportional() {
+        let mut random = Random::new_mt_from_seed(42);
+        let mut counts = [0; 2];
+        for _ in 0..100_000 {
+            counts[random.weighted_index(&[1.0, 3.0]).unwrap()] += 1;
+        }
+
+        let ratio = f64::from(counts[1]) / 100_000.0;
+        assert!((ratio - 0.75).abs() < 0.01, "ratio was {}", ratio);
+    }
+
+    #[test]
+    fn weighted_index_skips_zero_weights() {
+        let mut random = Random::new_mt_from_seed(42);
+        assert_eq!(None, random.weighted_index(&[]));
+        assert_eq!(None, random.weighted_index(&[0.0, 0.0]));
+        for _ in 0..1000 {
+            assert_eq!(Some(1), random.weighted_index(&[0.0, 2.0, 0.0]));
+        }
+    }
+
+    #[test]
+    fn weighted_choice_is_proportional() {
+        let mut random = Random::new_cmwc_from_seed(42);
+        let items = [("common", 3.0), ("rare", 1.0)];
+        let mut common = 0;
+        for _ in 0..100_000 {
+            if *random.weighted_choice(&items).unwrap() == "common" {
+                common += 1;
+            }
+        }
+
+        let ratio = f64::from(common) / 100_000.0;
+        assert!((ratio - 0.75).abs() < 0.01, "ratio was {}", ratio);
+        assert_eq!(None, random.weighted_choice::<()>(&[]));
+    }
+
+    #[test]
+    fn sample_weighted_n_favors_heavy_items() {
+        let mut random = Random::new_cmwc_from_seed(42);
+        let items = [
+            ("heavy", 20.0),
+            ("light", 1.0),
+            ("a", 5.0),
+            ("b", 5.0),
+            ("c", 5.0),
+        ];
+        let (mut heavy, mut light) = (0, 0);
+        for _ in 0..10_000 {
+            let sample = random.sample_weighted_n(&items, 2);
+            assert_eq!(2, sample.len());
+            assert_ne!(sample[0], sample[1]);
+            heavy += sample.iter().filter(|&&&item| item == "heavy").count();
+            light += sample.iter().filter(|&&&item| item == "light").count();
+        }
+        assert!(heavy > 8_000, "heavy was chosen {} times", heavy);
+        assert!(light < 1_000, "light was chosen {} times", light);
+        assert!(heavy > 10 * light, "{} vs {}", heavy, light);
+
+        let mut all = random.sample_weighted_n(&items, 10);
+        all.sort_unstable();
+        assert_eq!(vec![&"a", &"b", &"c", &"heavy", &"light"], all);
+        assert_eq!(
+            vec![&"b"],
+            random.sample_weighted_n(&[("a", 0.0), ("b", 1.0)], 2)
+        );
+        assert!(random.sample_weighted_n(&items, 0).is_empty());
+    }
+
+    #[test]
+    fn bounded_u32_is_unbiased() {
+        let mut random = Random::new_mt_from_seed(42);
+        let mut counts = [0; 3];
+        for _ in 0..30_000 {
+            counts[random.get_bounded_u32(3) as usize] += 1;
+        }
+
+        for &count in &counts {
+            assert!(
+                (9_500..=10_500).contains(&count),
+                "counts were {:?}",
+                counts
+            );
+        }
+        assert_eq!(0, random.get_bounded_u32(1));
+    }
+
+    #[test]
+    fn int_range_is_uniform() {
+        const SAMPLES: i32 = 70_000;
+
+        let mut random = Random::new_mt_from_seed(42);
+        let mut counts = [0; 7];
+        for _ in 0..SAMPLES {
+            let value = random.get_int_range(3, -3);
+            counts[(value + 3) as usize] += 1;
+        }
+
+        // The critical value of the chi-squared distribution with 6 degrees of freedom at 0.001.
+        let expected = f64::from(SAMPLES) / counts.len() as f64;
+        let chi_squared: f64 = counts
+            .iter()
+            .map(|&count| (f64::from(count) - expected).powi(2) / expected)
+            .sum();
+        assert!(chi_squared < 22.458, "counts were {:?}", counts);
+    }
+
+    #[test]
+    fn int_range_handles_degenerate_and_full_ranges() {
+        let mut expected = Random::new_mt_from_seed(42);
+        let mut random = Random::new_mt_from_seed(42);
+
+        assert_eq!(5, random.get_int_range(5, 5));
+        assert_eq!(7, random.get_u32_range(7..7));
+        assert_eq!(
+            i32::MIN.wrapping_add(expected.algo.get_int() as i32),
+            random.get_int_range(i32::MIN, i32::MAX)
+        );
+
+        let (start, end) = (20, 10);
+        for _ in 0..1000 {
+            assert!((10..20).contains(&random.get_u32_range(start..end)));
+            assert!(
+                (i32::MAX - 1..=i32::MAX).contains(&random.get_int_range(i32::MAX - 1, i32::MAX))
+            );
+        }
+    }
+
+    #[test]
+    fn shuffle_is_reproducible_permutation() {
+        let sorted: Vec<_> = (0..100).collect();
+
+        let shuffled = Random::new_mt_from_seed(42).shuffled(&sorted);
+        assert_eq!(shuffled, Random::new_mt_from_seed(42).shuffled(&sorted));
+        assert_ne!(sorted, shuffled);
+
+        let mut permutation = shuffled;
+        permutation.sort_unstable();
+        assert_eq!(sorted, permutation);
+
+        let mut empty: [i32; 0] = [];
+        Random::new_mt_from_seed(42).shuffle(&mut empty);
+    }
+
+    #[test]
+    fn sample_iter_returns_short_iterators_in_order() {
+        let mut random = Random::new_mt_from_seed(42);
+        assert_eq!(vec![0, 1, 2], random.sample_iter(0..3, 5));
+        assert_eq!(vec![0, 1, 2], random.sample_iter(0..3, 3));
+        assert!(random.sample_iter(0..3, 0).is_empty());
+        assert_eq!(
+            (0..10).collect::<Vec<_>>(),
+            random.sample_iter(0..10, usize::MAX)
+        );
+        assert_eq!(
+            vec![1, 3],
+            random.sample_iter((0..4).filter(|i| i % 2 == 1), 8)
+        );
+    }
+
+    #[test]
+    fn sample_iter_is_uniform() {
+        const N: usize = 10;
+        const K: usize = 3;
+        const TRIALS: usize = 30_000;
+
+        let mut random = Random::new_cmwc_from_seed(42);
+        let mut counts = [0; N];
+        for _ in 0..TRIALS {
+            let sample = random.sample_iter(0..N, K);
+            assert_eq!(K, sample.len());
+            for i in sample {
+                counts[i] += 1;
+            }
+        }
+
+        for &count in &counts {
+            let probability = f64::from(count) / TRIALS as f64;
+            assert!(
+                (probability - K as f64 / N as f64).abs() < 0.01,
+                "counts were {:?}",
+                counts
+            );
+        }
+    }
+
+    #[test]
+    fn with_algorithm_kind_uses_chosen_algorithm() {
+        let mut mt = Random::with_algorithm_kind(AlgorithmKind::MersenneTwister, 42);
+        assert_eq!(MersenneTwister::new(42).get_int(), mt.algo.get_int());
+        assert_eq!(Some(42), mt.seed);
+
+        let mut cmwc =
+            Random::with_algorithm_kind(AlgorithmKind::ComplementaryMultiplyWithCarry, 42);
+        assert_eq!(
+            ComplementaryMultiplyWithCarry::new(42).get_int(),
+            cmwc.algo.get_int()
+        );
+
+        let mut boxed = Random::from_boxed(Box::new(MersenneTwister::new(7)));
+        assert_eq!(None, boxed.seed);
+        let mut expected = Random::new_mt_from_seed(7);
+        assert_eq!(expected.get_i32(0, 100), boxed.get_i32(0, 100));
+    }
+
+    #[test]
+    fn exponential_has_expected_mean_and_variance() {
+        let mut random = Random::new_mt_from_seed(42);
+        let values: Vec<_> = (0..100_000).map(|_| random.get_exponential(2.0)).collect();
+
+        let (mean, std_deviation) = mean_and_std_deviation(&values);
+        assert!((mean - 0.5).abs() < 0.01, "mean was {}", mean);
+        assert!(
+            (std_deviation.powi(2) - 0.25).abs() < 0.01,
+            "variance was {}",
+            std_deviation.powi(2)
+        );
+        assert!(values.iter().all(|v| v.is_finite() && *v >= 0.0));
+    }
+
+    #[test]
+    fn poisson_has_expected_mean_and_variance() {
+        let mut random = Random::new_cmwc_from_seed(42);
+        for &lambda in &[0.5, 3.0, 10.0, 250.0] {
+            let values: Vec<_> = (0..100_000)
+                .map(|_| f64::from(random.get_poisson(lambda)))
+                .collect();
+
+            let (mean, std_deviation) = mean_and_std_deviation(&values);
+            let variance = std_deviation.powi(2);
+            assert!(
+                (mean - lambda).abs() < 0.02 * lambda.max(1.0),
+                "mean was {} for lambda {}",
+                mean,
+                lambda
+            );
+            assert!(
+                (variance - lambda).abs() < 0.05 * lambda.max(1.0),
+                "variance was {} for lambda {}",
+                variance,
+                lambda
+            );
+        }
+    }
+
+    #[test]
+    fn ln_factorial_is_accurate() {
+        let mut exact = 0.0;
+        for k in 1..30 {
+            exact += f64::from(k).ln();
+            assert!((super::ln_factorial(f64::from(k)) - exact).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "lambda must be positive.")]
+    fn poisson_rejects_nonpositive_lambda() {
+        Random::new_mt_from_seed(42).get_poisson(0.0);
+    }
+
+    #[test]
+    fn float_range_stays_in_range() {
+        let mut random = Random::new_mt_from_seed(42);
+        for &(min, max) in &[
+            (0.0, 1.0),
+            (-1.0, 3.0),
+            (5.0, -5.0),
+            (-10.0, -0.001),
+            (1.5, 1e30),
+        ] {
+            let (low, high) = if min < max { (min, max) } else { (max, min) };
+            let mut sum = 0.0;
+            for _ in 0..10_000 {
+                let value = random.get_float_range(min, max);
+                assert!(
+                    (low..=high).contains(&value),
+                    "{} not in {:?}",
+                    value,
+                    (min, max)
+                );
+                sum += f64::from(value);
+            }
+            if high - low < 1e6 {
+                let mean = sum / 10_000.0;
+                let expected = f64::from(low + high) / 2.0;
+                assert!(
+                    (mean - expected).abs() < 0.02 * f64::from(high - low),
+                    "mean was {}",
+                    mean
+                );
+            }
+        }
+        assert_eq!(2.0, random.get_float_range(2.0, 2.0));
+    }
+
+    #[test]
+    fn double_range_stays_in_range() {
+        let mut random = Random::new_cmwc_from_seed(42);
+        for &(min, max) in &[(0.0, 1.0), (-1e200, 1e-200), (3.0, 2.999_999)] {
+            for _ in 0..10_000 {
+                let value = random.get_double_range(min, max);
+                assert!(value >= min.min(max) && value <= max.max(min));
+            }
+        }
+    }
+
+    #[test]
+    fn float_range_reaches_every_value() {
+        let mut random = Random::new_mt_from_seed(42);
+
+        // Every value of a tiny range is produced.
+        let low = 1.0_f32;
+        let high = f32::from_bits(low.to_bits() + 3);
+        let mut seen = HashSet::new();
+        for _ in 0..1000 {
+            seen.insert(random.get_float_range(low, high).to_bits());
+        }
+        assert_eq!(4, seen.len());
+
+        // Subnormal numbers and zero are reachable.
+        let subnormal = (0..1000)
+            .map(|_| random.get_float_range(f32::MIN_POSITIVE / 2.0, f32::MIN_POSITIVE * 2.0))
+            .any(|value| value < f32::MIN_POSITIVE);
+        assert!(subnormal);
+
+        // As are the numbers on both sides of an exponent boundary.
+        let below_two = f32::from_bits(2.0_f32.to_bits() - 3);
+        let mut seen_across = HashSet::new();
+        for _ in 0..1000 {
+            seen_across.insert(random.get_float_range(below_two, 2.0).to_bits());
+        }
+        assert_eq!(4, seen_across.len());
+
+        let values: Vec<_> = (0..10_000)
+            .map(|_| random.get_float_range(0.0, f32::from_bits(3)))
+            .collect();
+        assert!(values.contains(&0.0));
+        assert!(values.contains(&f32::from_bits(3)));
+    }
+
+    #[test]
+    fn inclusive_floats_reach_the_top_of_the_range() {
+        let mut random = Random::new_mt_from_seed(7);
+        let mut sum = 0.0;
+        for _ in 0..10_000 {
+            let (float, double) = (random.get_float_inclusive(), random.get_double_inclusive());
+            assert!((0.0..=1.0).contains(&float), "{}", float);
+            assert!((0.0..=1.0).contains(&double), "{}", double);
+            sum += double;
+        }
+        assert!((sum / 10_000.0 - 0.5).abs() < 0.02);
+
+        // The maximum is as likely as any of its neighbors, so narrow ranges reach it quickly.
+        let below_one = f32::from_bits(1.0_f32.to_bits() - 2);
+        let floats: Vec<_> = (0..1000)
+            .map(|_| random.get_float_range(below_one, 1.0))
+            .collect();
+        assert!(floats.contains(&1.0));
+        assert!(floats.iter().all(|&value| value <= 1.0));
+
+        let doubles: Vec<_> = (0..1000)
+            .map(|_| random.get_double_range(-1.0, f64::from_bits((-1.0_f64).to_bits() - 2)))
+            .collect();
+        assert!(doubles.contains(&-1.0));
+        assert!(doubles.iter().all(|&value| value >= -1.0));
+    }
+
+    fn check_gen_range<T: SampleBound + std::fmt::Debug>(range: std::ops::Range<T>) -> Vec<T> {
+        let mut random = Random::new_mt_from_seed(29);
+        let values: Vec<_> = (0..1000).map(|_| random.gen_range(range.clone())).collect();
+        for value in &values {
+            assert!(range.start <= *value && *value < range.end, "{:?}", value);
+        }
+        values
+    }
+
+    #[test]
+    fn gen_range_small_ranges_near_the_top() {
+        assert_eq!(
+            vec![u32::MAX - 3, u32::MAX - 2, u32::MAX - 1],
+            sorted_unique(check_gen_range(u32::MAX - 3..u32::MAX))
+        );
+        assert_eq!(
+            vec![i32::MAX - 2, i32::MAX - 1],
+            sorted_unique(check_gen_range(i32::MAX - 2..i32::MAX))
+        );
+        assert_eq!(
+            vec![u64::MAX - 3, u64::MAX - 2, u64::MAX - 1],
+            sorted_unique(check_gen_range(u64::MAX - 3..u64::MAX))
+        );
+        assert_eq!(
+            vec![i64::MAX - 2, i64::MAX - 1],
+            sorted_unique(check_gen_range(i64::MAX - 2..i64::MAX))
+        );
+        assert_eq!(
+            vec![usize::MAX - 2, usize::MAX - 1],
+            sorted_unique(check_gen_range(usize::MAX - 2..usize::MAX))
+        );
+        assert_eq!(vec![-1, 0, 1], sorted_unique(check_gen_range(-1_i64..2)));
+    }
+
+    #[test]
+    fn gen_range_full_width() {
+        /* over (almost) the whole type, every eighth of it should come up */
+        fn eighths(values: Vec<f64>, min: f64, max: f64) -> Vec<u32> {
+            sorted_unique(
+                values
+                    .into_iter()
+                    .map(|value| ((value - min) / (max - min) * 8.0).min(7.0) as u32)
+                    .collect(),
+            )
+        }
+        fn floats<T: Copy>(values: Vec<T>, to_f64: fn(T) -> f64) -> Vec<f64> {
+            values.into_iter().map(to_f64).collect()
+        }
+        let all: Vec<u32> = (0..8).collect();
+
+        let u32s = floats(check_gen_range(0..u32::MAX), f64::from);
+        assert_eq!(all, eighths(u32s, 0.0, f64::from(u32::MAX)));
+        let i32s = floats(check_gen_range(i32::MIN..i32::MAX), f64::from);
+        assert_eq!(all, eighths(i32s, f64::from(i32::MIN), f64::from(i32::MAX)));
+        let u64s = floats(check_gen_range(0..u64::MAX), |v| v as f64);
+        assert_eq!(all, eighths(u64s, 0.0, u64::MAX as f64));
+        let i64s = floats(check_gen_range(i64::MIN..i64::MAX), |v| v as f64);
+        assert_eq!(all, eighths(i64s, i64::MIN as f64, i64::MAX as f64));
+        let usizes = floats(check_gen_range(0..usize::MAX), |v| v as f64);
+        assert_eq!(all, eighths(usizes, 0.0, usize::MAX as f64));
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range")]
+    #[cfg(debug_assertions)]
+    fn gen_range_empty_range() {
+        Random::new_mt_from_seed(29).gen_range(5_u64..5);
+    }
+
+    #[test]
+    fn poisson_disk_points_keep_their_distance() {
+        let mut random = Random::new_mt_from_seed(17);
+        let points = random.poisson_disk_2d(40.0, 25.0, 3.0, POISSON_DISK_ATTEMPTS);
+        /* a 40x25 area fits between roughly 60 and 110 points spaced 3 apart */
+        assert!(points.len() > 60, "only {} points", points.len());
+        for (i, &(ax, ay)) in points.iter().enumerate() {
+            assert!(
+                (0.0..=40.0).contains(&ax) && (0.0..=25.0).contains(&ay),
+                "({}, {})",
+                ax,
+                ay
+            );
+            for &(bx, by) in &points[i + 1..] {
+                let distance = ((ax - bx).powi(2) + (ay - by).powi(2)).sqrt();
+                assert!(
+                    distance >= 3.0,
+                    "{:?} are {} apart",
+                    ((ax, ay), (bx, by)),
+                    distance
+                );
+            }
+        }
+
+        let mut same_seed = Random::new_mt_from_seed(17);
+        assert_eq!(
+            points,
+            same_seed.poisson_disk_2d(40.0, 25.0, 3.0, POISSON_DISK_ATTEMPTS)
+        );
+        let mut other_seed = Random::new_mt_from_seed(18);
+        assert_ne!(
+            points,
+            other_seed.poisson_disk_2d(40.0, 25.0, 3.0, POISSON_DISK_ATTEMPTS)
+        );
+    }
+
+    #[test]
+    fn poisson_disk_degenerate_inputs() {
+        let mut random = Random::new_mt_from_seed(17);
+        assert!(random.poisson_disk_2d(0.0, 25.0, 3.0, 30).is_empty());
+        assert!(random.poisson_disk_2d(40.0, -1.0, 3.0, 30).is_empty());
+        assert!(random.poisson_disk_2d(40.0, 25.0, 0.0, 30).is_empty());
+        assert!(random.poisson_disk_2d(40.0, 25.0, f32::NAN, 30).is_empty());
+        assert_eq!(1, random.poisson_disk_2d(40.0, 25.0, 100.0, 30).len());
+        assert_eq!(1, random.poisson_disk_2d(40.0, 25.0, 3.0, 0).len());
+    }
+
+    #[test]
+    fn jitter_color() {
+        let base = Color::new_with_alpha(250, 3, 128, 77);
+        let mut random = Random::new_mt_from_seed(4321);
+        let mut again = Random::new_mt_from_seed(4321);
+
+        let mut changed = false;
+        for _ in 0..1000 {
+            let jittered = random.jitter_color(base, 10);
+            assert_eq!(jittered, again.jitter_color(base, 10));
+            assert!(jittered.r >= 240);
+            assert!(jittered.g <= 13);
+            assert!(jittered.b >= 118 && jittered.b <= 138);
+            assert_eq!(77, jittered.a);
+            changed |= jittered != base;
+        }
+        assert!(changed);
+
+        let mut untouched = random.clone();
+        assert_eq!(base, random.jitter_color(base, 0));
+        assert_eq!(
+            untouched.get_int_range(0, 1000),
+            random.get_int_range(0, 1000)
+        );
+    }
+
+    #[test]
+    fn get_bool_and_one_in() {
+        let mut random = Random::new_mt_from_seed(42);
+        let mut untouched = random.clone();
+        assert!(!random.get_bool(0.0));
+        assert!(!random.get_bool(-1.0));
+        assert!(random.get_bool(1.0));
+        assert!(random.get_bool(2.0));
+        assert!(random.one_in(1));
+        assert_eq!(
+            untouched.get_int_range(0, 1000),
+            random.get_int_range(0, 1000)
+        );
+
+        let heads = (0..10_000).filter(|_| random.one_in(2)).count();
+        assert!((4_800..=5_200).contains(&heads), "{}", heads);
+        let sixes = (0..10_000).filter(|_| random.one_in(6)).count();
+        assert!((1_500..=1_830).contains(&sixes), "{}", sixes);
+        let likely = (0..10_000).filter(|_| random.get_bool(0.9)).count();
+        assert!((8_850..=9_150).contains(&likely), "{}", likely);
+    }
+
+    #[test]
+    fn reseed_clears_cached_gaussian() {
+        let mut random = Random::new_mt_from_seed(42);
+        let first = random.get_gaussian_double(0.0, 1.0);
+
+        random.reseed(42);
+
+        assert_eq!(first, random.get_gaussian_double(0.0, 1.0));
+    }
+}
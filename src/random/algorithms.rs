@@ -33,17 +33,50 @@
 
 //! Random number generator algorithms.
 
-use std::mem::{transmute, MaybeUninit};
+mod jump;
 
 const RAND_DIV: f32 = 1.0 / 0xffff_ffff_u32 as f32; // u32::MAX
 #[allow(clippy::unnecessary_cast)]
 const RAND_DIV_DOUBLE: f64 = 1.0 / 0xffff_ffff_u32 as f64; // u32::MAX
 
 /// Random number generator algorithm trait.
-pub trait Algorithm {
+///
+/// Algorithms have to be `Clone`, so that they can be forked through
+/// [`ForkableAlgorithm`](trait.ForkableAlgorithm.html).
+pub trait Algorithm: ForkableAlgorithm {
     /// Generate a 32-bit integer.
     fn get_int(&mut self) -> u32;
 
+    /// Fill `dest` with random bytes.
+    ///
+    /// The bytes are taken from successive `get_int` calls in little-endian order. If the length
+    /// of `dest` isn't a multiple of four, the remaining bytes of the last int are discarded.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.get_int().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    /// Fill `dest` with 32-bit integers.
+    ///
+    /// This produces the same numbers as calling `get_int` once for each element, but some
+    /// algorithms can do it faster.
+    fn fill_u32(&mut self, dest: &mut [u32]) {
+        for d in dest {
+            *d = self.get_int();
+        }
+    }
+
+    /// Generate a 64-bit integer.
+    ///
+    /// By default, this takes two successive `get_int` calls, using the first one as the high 32
+    /// bits and the second one as the low 32 bits. Algorithms that natively generate 64-bit
+    /// integers return those directly instead.
+    fn get_u64(&mut self) -> u64 {
+        u64::from(self.get_int()) << 32 | u64::from(self.get_int())
+    }
+
     /// Generate a 32-bit floating point number.
     fn get_float(&mut self) -> f32 {
         if cfg!(feature = "libtcod-compat") {
@@ -126,6 +159,121 @@ pub trait Algorithm {
     }
 }
 
+#[cfg(feature = "std")]
+impl<A: Algorithm + ?Sized> Algorithm for Box<A>
+where
+    Self: Clone + 'static,
+{
+    fn get_int(&mut self) -> u32 {
+        (**self).get_int()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        (**self).fill_bytes(dest)
+    }
+
+    fn fill_u32(&mut self, dest: &mut [u32]) {
+        (**self).fill_u32(dest)
+    }
+
+    fn get_u64(&mut self) -> u64 {
+        (**self).get_u64()
+    }
+
+    fn get_float(&mut self) -> f32 {
+        (**self).get_float()
+    }
+
+    fn get_double(&mut self) -> f64 {
+        (**self).get_double()
+    }
+}
+
+/// Duplicates an [`Algorithm`](trait.Algorithm.html), even through a `dyn Algorithm`.
+///
+/// `Clone` can't be used on a trait object, so every `Algorithm` has to implement this trait
+/// instead, which makes [`fork`](#tymethod.fork) available on any `Algorithm`. It's implemented
+/// for every `Algorithm` that implements `Clone`, so there's no need to implement it by hand.
+pub trait ForkableAlgorithm {
+    /// Returns a copy of the algorithm in its current state, which generates the same numbers
+    /// from here on, without affecting the original. This lets callers snapshot a generator,
+    /// e.g. to explore alternative outcomes and then throw them away.
+    #[cfg(feature = "std")]
+    fn fork(&self) -> Box<dyn Algorithm>;
+}
+
+impl<A: Algorithm + Clone + 'static> ForkableAlgorithm for A {
+    #[cfg(feature = "std")]
+    fn fork(&self) -> Box<dyn Algorithm> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clone for Box<dyn Algorithm> {
+    fn clone(&self) -> Self {
+        /* forks the boxed algorithm itself, rather than boxing the box */
+        (**self).fork()
+    }
+}
+
+/// The kinds of algorithms that can be chosen at runtime, e.g. from a configuration file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AlgorithmKind {
+    /// The `MersenneTwister` algorithm. Parsed from `"mersenne"`, `"mt"` or `"mersenne-twister"`.
+    MersenneTwister,
+    /// The `ComplementaryMultiplyWithCarry` algorithm. Parsed from `"cmwc"` or
+    /// `"complementary-multiply-with-carry"`.
+    ComplementaryMultiplyWithCarry,
+}
+
+impl AlgorithmKind {
+    /// Create a new instance of the algorithm of this kind, seeded with the given `seed`.
+    #[cfg(feature = "std")]
+    pub fn create(self, seed: u32) -> Box<dyn Algorithm> {
+        match self {
+            Self::MersenneTwister => Box::new(MersenneTwister::new(seed)),
+            Self::ComplementaryMultiplyWithCarry => {
+                Box::new(ComplementaryMultiplyWithCarry::new(seed))
+            }
+        }
+    }
+}
+
+impl core::str::FromStr for AlgorithmKind {
+    type Err = ParseAlgorithmKindError;
+
+    /// Parses an algorithm kind from its name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        /* compared without lowercasing, as that would need an allocation */
+        let name = s.trim();
+        let is_any_of = |names: &[&str]| names.iter().any(|n| n.eq_ignore_ascii_case(name));
+        if is_any_of(&["mersenne", "mt", "mersenne-twister"]) {
+            Ok(Self::MersenneTwister)
+        } else if is_any_of(&["cmwc", "complementary-multiply-with-carry"]) {
+            Ok(Self::ComplementaryMultiplyWithCarry)
+        } else {
+            Err(ParseAlgorithmKindError)
+        }
+    }
+}
+
+/// The error type returned when parsing an `AlgorithmKind` from an unknown name.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ParseAlgorithmKindError;
+
+impl core::fmt::Display for ParseAlgorithmKindError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "unknown algorithm name; expected \"mersenne\" or \"cmwc\""
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseAlgorithmKindError {}
+
 /// Mersenne Twister algorithm.
 #[derive(Clone, Copy)]
 pub struct MersenneTwister {
@@ -146,70 +294,163 @@ impl MersenneTwister {
     const MT19937_LOWER_MASK: u32 = (1 << (Self::MT19937_SEPARATION_POINT)) as u32;
     const MT19937_UPPER_MASK: u32 = !Self::MT19937_LOWER_MASK;
 
+    /* below this many array regenerations, regenerating is cheaper than the polynomial jump */
+    const NAIVE_DISCARD_BLOCK_LIMIT: u128 = 2048;
+
+    /// The number of outputs skipped by [`jump`](#method.jump).
+    pub const JUMP_DISTANCE: u64 = 1 << 32;
+
     /// Create a new Mersenne Twister algorithm instance.
+    ///
+    /// Unless the `libtcod-compat` feature is enabled, the state is filled using a `SplitMix64`
+    /// generator seeded with `seed`, so that similar seeds produce unrelated sequences. With the
+    /// feature enabled, this is the same as [`new_libtcod`](#method.new_libtcod).
     pub fn new(seed: u32) -> Self {
+        if cfg!(feature = "libtcod-compat") {
+            Self::new_libtcod(seed)
+        } else {
+            Self::from_splitmix(SplitMix64::new(u64::from(seed)))
+        }
+    }
+
+    /// Create a new Mersenne Twister algorithm instance, seeded from system entropy.
+    ///
+    /// This is non-deterministic; every call gives a different sequence. Use one of the other
+    /// constructors if the sequence needs to be reproducible. See [`entropy_seed`] for where the
+    /// entropy comes from.
+    #[cfg(feature = "std")]
+    pub fn new_seeded() -> Self {
+        Self::from_splitmix(SplitMix64::new(entropy_seed()))
+    }
+
+    fn from_splitmix(mut splitmix: SplitMix64) -> Self {
+        let mut mt = [0; Self::MT19937_RECURRENCE_DEGREE];
+        splitmix.fill_u32s(&mut mt);
+
+        Self {
+            cur_mt: Self::MT19937_RECURRENCE_DEGREE,
+            mt,
+        }
+    }
+
+    /// Create a new Mersenne Twister algorithm instance, seeded the same way as libtcod and the
+    /// MT19937 reference implementation.
+    pub fn new_libtcod(seed: u32) -> Self {
         Self {
             cur_mt: 624,
             mt: Self::mt_init(seed),
         }
     }
 
+    /// Returns a copy of the generator's internal state.
+    ///
+    /// The state can be stored and later restored with
+    /// [`from_state`](#method.from_state) to continue the sequence exactly where it left off.
+    pub fn state(&self) -> MersenneTwisterState {
+        MersenneTwisterState {
+            mt: self.mt,
+            cur_mt: self.cur_mt,
+        }
+    }
+
+    /// Restores a generator from a state previously obtained with [`state`](#method.state).
+    ///
+    /// # Errors
+    /// Returns `InvalidStateError::CursorOutOfRange` if `state.cur_mt` is greater than 624.
+    pub fn from_state(state: MersenneTwisterState) -> Result<Self, InvalidStateError> {
+        if state.cur_mt > Self::MT19937_RECURRENCE_DEGREE {
+            return Err(InvalidStateError::CursorOutOfRange {
+                cursor: state.cur_mt,
+                max: Self::MT19937_RECURRENCE_DEGREE,
+            });
+        }
+
+        Ok(Self {
+            mt: state.mt,
+            cur_mt: state.cur_mt,
+        })
+    }
+
+    /// Advances the generator by `n` outputs, as if `get_int` had been called `n` times.
+    ///
+    /// Large skips use the MT19937 polynomial jump-ahead, so the cost grows logarithmically
+    /// with `n` rather than linearly.
+    pub fn discard(&mut self, n: u64) {
+        let degree = Self::MT19937_RECURRENCE_DEGREE as u128;
+        let position = self.cur_mt as u128 + u128::from(n);
+        let blocks = position / degree;
+        if blocks > Self::NAIVE_DISCARD_BLOCK_LIMIT {
+            jump::jump_blocks(&mut self.mt, blocks);
+        } else {
+            for _ in 0..blocks {
+                Self::generate(&mut self.mt);
+            }
+        }
+        self.cur_mt = (position % degree) as usize;
+    }
+
+    /// Advances the generator by `MersenneTwister::JUMP_DISTANCE` (2^32) outputs.
+    ///
+    /// Jumping a clone of a generator repeatedly is a cheap way of obtaining several
+    /// non-overlapping subsequences from a single seed.
+    pub fn jump(&mut self) {
+        self.discard(Self::JUMP_DISTANCE);
+    }
+
     /* initialize the mersenne twister array */
-    #[allow(unsafe_code)]
     fn mt_init(seed: u32) -> [u32; Self::MT19937_RECURRENCE_DEGREE] {
-        let mut mt: [MaybeUninit<u32>; Self::MT19937_RECURRENCE_DEGREE] =
-            unsafe { MaybeUninit::uninit().assume_init() };
-        mt[0] = MaybeUninit::new(seed);
+        let mut mt = [0; Self::MT19937_RECURRENCE_DEGREE];
+        mt[0] = seed;
         for i in 1..mt.len() {
-            mt[i] = MaybeUninit::new(unsafe {
-                Self::MT19937.wrapping_mul(
-                    (*mt[i - 1].as_ptr()
-                        ^ (*mt[i - 1].as_ptr() >> (Self::MT19937_WORD_SIZE as u32 - 2)))
-                        .wrapping_add(i as u32),
-                )
-            });
+            let previous = mt[i - 1];
+            mt[i] = Self::MT19937
+                .wrapping_mul(previous ^ (previous >> (Self::MT19937_WORD_SIZE as u32 - 2)))
+                .wrapping_add(i as u32);
         }
 
-        unsafe { transmute(mt) }
+        mt
+    }
+
+    /* generate the next 624 values of the mersenne twister array */
+    fn generate(mt: &mut [u32; Self::MT19937_RECURRENCE_DEGREE]) {
+        for i in 0..Self::MT19937_RECURRENCE_DEGREE {
+            Self::twist_one(mt, i);
+        }
+    }
+
+    /* replace the value at index i with the value that follows the one at index i - 1 */
+    fn twist_one(mt: &mut [u32; Self::MT19937_RECURRENCE_DEGREE], i: usize) {
+        let next = (i + 1) % Self::MT19937_RECURRENCE_DEGREE;
+        let middle = (i + Self::MT19937_MIDDLE_WORD) % Self::MT19937_RECURRENCE_DEGREE;
+        let y = (mt[i] & Self::MT19937_LOWER_MASK) | (mt[next] & Self::MT19937_UPPER_MASK);
+        if y & 1 == 0 {
+            /* even y */
+            mt[i] = mt[middle] ^ (y >> 1);
+        } else {
+            /* odd y */
+            mt[i] = mt[middle]
+                ^ (y >> 1)
+                ^ Self::MT19937_RATIONAL_NORMAL_FORM_TWIST_MATRIX_COEFFICIENTS;
+        }
     }
 
     /* get the next random value from the mersenne twister array */
     fn mt_rand(mt: &mut [u32; Self::MT19937_RECURRENCE_DEGREE], cur_mt: &mut usize) -> u32 {
         if *cur_mt == Self::MT19937_RECURRENCE_DEGREE {
             /* our 624 sequence is finished. generate a new one */
-            for i in 0..Self::MT19937_RECURRENCE_DEGREE - 1 {
-                let y = (mt[i] & Self::MT19937_LOWER_MASK) | (mt[i + 1] & Self::MT19937_UPPER_MASK);
-                if y & 1 == 0 {
-                    /* even y */
-                    mt[i] = mt[(i + Self::MT19937_MIDDLE_WORD) % Self::MT19937_RECURRENCE_DEGREE]
-                        ^ (y >> 1);
-                } else {
-                    /* odd y */
-                    mt[i] = mt[(i + Self::MT19937_MIDDLE_WORD) % Self::MT19937_RECURRENCE_DEGREE]
-                        ^ (y >> 1)
-                        ^ Self::MT19937_RATIONAL_NORMAL_FORM_TWIST_MATRIX_COEFFICIENTS;
-                }
-            }
-
-            let y = (mt[Self::MT19937_RECURRENCE_DEGREE - 1] & Self::MT19937_LOWER_MASK)
-                | (mt[0] & Self::MT19937_UPPER_MASK);
-            if y & 1 == 0 {
-                /* even y */
-                mt[Self::MT19937_RECURRENCE_DEGREE - 1] =
-                    mt[Self::MT19937_MIDDLE_WORD - 1] ^ (y >> 1);
-            } else {
-                /* odd y */
-                mt[Self::MT19937_RECURRENCE_DEGREE - 1] = mt[Self::MT19937_MIDDLE_WORD - 1]
-                    ^ (y >> 1)
-                    ^ Self::MT19937_RATIONAL_NORMAL_FORM_TWIST_MATRIX_COEFFICIENTS;
-            }
+            Self::generate(mt);
 
             *cur_mt = 0;
         }
 
-        let mut y = mt[*cur_mt];
+        let y = mt[*cur_mt];
         *cur_mt += 1;
 
+        Self::temper(y)
+    }
+
+    /* temper a value from the mersenne twister array */
+    fn temper(mut y: u32) -> u32 {
         y ^= y >> Self::MT19937_ADDITIONAL_TEMPERING.0;
         y ^= (y << Self::MT19937_TGFSR_R_TEMPERING_BIT_SHIFTS.0)
             & Self::MT19937_TGFSR_R_TEMPERING_BIT_MASKS.0;
@@ -221,8 +462,8 @@ impl MersenneTwister {
     }
 }
 
-impl std::fmt::Debug for MersenneTwister {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+impl core::fmt::Debug for MersenneTwister {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         write!(f, "MersenneTwister {{ cur_mt: {} }}", self.cur_mt)
     }
 }
@@ -231,41 +472,233 @@ impl Algorithm for MersenneTwister {
     fn get_int(&mut self) -> u32 {
         Self::mt_rand(&mut self.mt, &mut self.cur_mt)
     }
+
+    fn fill_u32(&mut self, mut dest: &mut [u32]) {
+        while !dest.is_empty() {
+            if self.cur_mt == Self::MT19937_RECURRENCE_DEGREE {
+                Self::generate(&mut self.mt);
+                self.cur_mt = 0;
+            }
+
+            let count = (Self::MT19937_RECURRENCE_DEGREE - self.cur_mt).min(dest.len());
+            let (head, tail) = core::mem::take(&mut dest).split_at_mut(count);
+            for (d, &y) in head.iter_mut().zip(&self.mt[self.cur_mt..]) {
+                *d = Self::temper(y);
+            }
+            self.cur_mt += count;
+            dest = tail;
+        }
+    }
 }
 
+#[cfg(feature = "rng_support")]
+impl rand_core::RngCore for MersenneTwister {
+    fn next_u32(&mut self) -> u32 {
+        self.get_int()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.get_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        Algorithm::fill_bytes(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        Algorithm::fill_bytes(self, dest);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rng_support")]
+impl rand_core::SeedableRng for MersenneTwister {
+    type Seed = [u8; 4];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let seed = u32::from(seed[0]) << 24
+            | u32::from(seed[1]) << 16
+            | u32::from(seed[2]) << 8
+            | u32::from(seed[3]);
+        Self::new(seed)
+    }
+}
+
+/// The internal state of a `MersenneTwister`.
+#[derive(Clone, Copy)]
+pub struct MersenneTwisterState {
+    /// The 624-word state array.
+    pub mt: [u32; 624],
+    /// The index of the next word to be tempered and returned. A value of 624 means the
+    /// array is exhausted and will be regenerated on the next call.
+    pub cur_mt: usize,
+}
+
+impl core::fmt::Debug for MersenneTwisterState {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "MersenneTwisterState {{ cur_mt: {} }}", self.cur_mt)
+    }
+}
+
+/* these values are recommended by George Marsaglia */
+const CMWC_MULTIPLIER: u64 = 18782;
+const CMWC_CARRY_MAX: u32 = 809_430_660;
+/* larger multipliers can make the carry overflow */
+const CMWC_MULTIPLIER_LIMIT: u64 = 0xffff_ffff;
+
 /// Complementary-Multiply-With-Carry algorithm.
+///
+/// Each draw advances the cursor `n` through a 4096-word lag table `q` and computes
+/// `t = a * q[n] + c` in 64 bits, where `a` is the multiplier and `c` the carry. The new carry
+/// is the high word of `t`, and the output `x` is `(2^32 - 2) - (t + c)` (mod `2^32 - 1`), which
+/// also replaces `q[n]`. The defaults, a multiplier of `18782` and an initial carry below
+/// `809430660`, are the ones recommended by George Marsaglia and used by libtcod; see
+/// [`with_params`](#method.with_params) to change them.
 #[derive(Clone, Copy)]
 pub struct ComplementaryMultiplyWithCarry {
     q: [u32; 4096],
     c: u32,
     cur: usize,
+    multiplier: u64,
 }
 
 impl ComplementaryMultiplyWithCarry {
     /// Create a new Complementary-Multiply-With-Carry algorithm instance.
-    #[allow(unsafe_code)]
+    ///
+    /// Unless the `libtcod-compat` feature is enabled, the state is filled using a `SplitMix64`
+    /// generator seeded with `seed`, so that similar seeds produce unrelated sequences. With the
+    /// feature enabled, this is the same as [`new_libtcod`](#method.new_libtcod).
     pub fn new(seed: u32) -> Self {
+        Self::with_params(seed, CMWC_MULTIPLIER, CMWC_CARRY_MAX)
+    }
+
+    /// Create a new Complementary-Multiply-With-Carry algorithm instance with a custom
+    /// multiplier and initial carry cap.
+    ///
+    /// The lag table is seeded the same way as with [`new`](#method.new); the initial carry is
+    /// taken modulo `carry_max`. `new(seed)` is the same as
+    /// `with_params(seed, 18782, 809430660)`.
+    ///
+    /// Not every multiplier gives a full-period generator; the defaults are chosen so that it
+    /// does, and other values should be picked with the same care.
+    ///
+    /// # Panics
+    /// If `multiplier` is not less than 2<sup>32</sup> - 1, or if `carry_max` is 0.
+    pub fn with_params(seed: u32, multiplier: u64, carry_max: u32) -> Self {
+        assert!(
+            multiplier < CMWC_MULTIPLIER_LIMIT,
+            "the multiplier must be less than {}, but was {}",
+            CMWC_MULTIPLIER_LIMIT,
+            multiplier
+        );
+        assert!(carry_max > 0, "the carry cap must be positive");
+
+        let mut cmwc = if cfg!(feature = "libtcod-compat") {
+            Self::libtcod_seeded(seed, carry_max)
+        } else {
+            Self::from_splitmix(SplitMix64::new(u64::from(seed)), carry_max)
+        };
+        cmwc.multiplier = multiplier;
+        cmwc
+    }
+
+    /// Create a new Complementary-Multiply-With-Carry algorithm instance, seeded from system
+    /// entropy.
+    ///
+    /// This is non-deterministic; every call gives a different sequence. Use one of the other
+    /// constructors if the sequence needs to be reproducible. See [`entropy_seed`] for where the
+    /// entropy comes from.
+    #[cfg(feature = "std")]
+    pub fn new_seeded() -> Self {
+        Self::from_splitmix(SplitMix64::new(entropy_seed()), CMWC_CARRY_MAX)
+    }
+
+    fn from_splitmix(mut splitmix: SplitMix64, carry_max: u32) -> Self {
+        let mut q = [0; 4096];
+        splitmix.fill_u32s(&mut q);
+        let c = splitmix.get_int() % carry_max;
+
+        Self {
+            q,
+            c,
+            cur: 0,
+            multiplier: CMWC_MULTIPLIER,
+        }
+    }
+
+    /// Create a new Complementary-Multiply-With-Carry algorithm instance, seeded the same way as
+    /// libtcod.
+    pub fn new_libtcod(seed: u32) -> Self {
+        Self::libtcod_seeded(seed, CMWC_CARRY_MAX)
+    }
+
+    fn libtcod_seeded(seed: u32, carry_max: u32) -> Self {
         let mut s = seed;
-        let mut q: [MaybeUninit<u32>; 4096] = unsafe { MaybeUninit::uninit().assume_init() };
+        let mut q = [0; 4096];
         for qe in &mut q[..] {
             s = s.wrapping_mul(1_103_515_245).wrapping_add(12345); /* glibc LCG */
-            unsafe {
-                qe.as_mut_ptr().write(s);
-            }
+            *qe = s;
         }
-        let c = s.wrapping_mul(1_103_515_245).wrapping_add(12345) % 809_430_660; /* this max value is recommended by George Marsaglia */
+        let c = s.wrapping_mul(1_103_515_245).wrapping_add(12345) % carry_max;
         let cur = 0;
 
         Self {
-            q: unsafe { transmute(q) },
+            q,
             c,
             cur,
+            multiplier: CMWC_MULTIPLIER,
+        }
+    }
+
+    /// Returns a copy of the generator's internal state.
+    ///
+    /// The state can be stored and later restored with
+    /// [`from_state`](#method.from_state) to continue the sequence exactly where it left off.
+    pub fn state(&self) -> ComplementaryMultiplyWithCarryState {
+        ComplementaryMultiplyWithCarryState {
+            q: self.q,
+            c: self.c,
+            cur: self.cur,
+            multiplier: self.multiplier,
+        }
+    }
+
+    /// Restores a generator from a state previously obtained with [`state`](#method.state).
+    ///
+    /// # Errors
+    /// Returns `InvalidStateError::CursorOutOfRange` if `state.cur` is not less than 4096, and
+    /// `InvalidStateError::MultiplierOutOfRange` if `state.multiplier` is not less than
+    /// 2<sup>32</sup> - 1.
+    pub fn from_state(
+        state: ComplementaryMultiplyWithCarryState,
+    ) -> Result<Self, InvalidStateError> {
+        if state.cur >= state.q.len() {
+            return Err(InvalidStateError::CursorOutOfRange {
+                cursor: state.cur,
+                max: state.q.len() - 1,
+            });
         }
+        if state.multiplier >= CMWC_MULTIPLIER_LIMIT {
+            return Err(InvalidStateError::MultiplierOutOfRange {
+                multiplier: state.multiplier,
+                limit: CMWC_MULTIPLIER_LIMIT,
+            });
+        }
+
+        Ok(Self {
+            q: state.q,
+            c: state.c,
+            cur: state.cur,
+            multiplier: state.multiplier,
+        })
     }
 
     fn get_number(&mut self) -> u32 {
+        /* Since both the multiplier and the previous carry are below 2^32, the new carry is at
+        most the multiplier, and at most one of the increments below happens, so the carry
+        can't overflow as long as the multiplier is below 2^32 - 1, whatever the cap was */
         self.cur = (self.cur + 1) & 4095;
-        let t = 18782_u64 * u64::from(self.q[self.cur]) + u64::from(self.c);
+        let t = self.multiplier * u64::from(self.q[self.cur]) + u64::from(self.c);
         self.c = (t >> 32) as u32;
         let mut x = (t + u64::from(self.c)) as u32;
         if x < self.c {
@@ -282,12 +715,12 @@ impl ComplementaryMultiplyWithCarry {
     }
 }
 
-impl std::fmt::Debug for ComplementaryMultiplyWithCarry {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+impl core::fmt::Debug for ComplementaryMultiplyWithCarry {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         write!(
             f,
-            "ComplementaryMultiplyWithCarry {{ c: {}, cur: {} }}",
-            self.c, self.cur
+            "ComplementaryMultiplyWithCarry {{ c: {}, cur: {}, multiplier: {} }}",
+            self.c, self.cur, self.multiplier
         )
     }
 }
@@ -298,14 +731,363 @@ impl Algorithm for ComplementaryMultiplyWithCarry {
     }
 }
 
-struct Bits<'a, A: Algorithm + ?Sized> {
+#[cfg(feature = "rng_support")]
+impl rand_core::RngCore for ComplementaryMultiplyWithCarry {
+    fn next_u32(&mut self) -> u32 {
+        self.get_int()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.get_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        Algorithm::fill_bytes(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        Algorithm::fill_bytes(self, dest);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rng_support")]
+impl rand_core::SeedableRng for ComplementaryMultiplyWithCarry {
+    type Seed = [u8; 4];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let seed = u32::from(seed[0]) << 24
+            | u32::from(seed[1]) << 16
+            | u32::from(seed[2]) << 8
+            | u32::from(seed[3]);
+        Self::new(seed)
+    }
+}
+
+/// The internal state of a `ComplementaryMultiplyWithCarry`.
+#[derive(Clone, Copy)]
+pub struct ComplementaryMultiplyWithCarryState {
+    /// The 4096-word lag table.
+    pub q: [u32; 4096],
+    /// The carry.
+    pub c: u32,
+    /// The index of the most recently used entry in `q`.
+    pub cur: usize,
+    /// The multiplier used by the recurrence.
+    pub multiplier: u64,
+}
+
+impl core::fmt::Debug for ComplementaryMultiplyWithCarryState {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(
+            f,
+            "ComplementaryMultiplyWithCarryState {{ c: {}, cur: {}, multiplier: {} }}",
+            self.c, self.cur, self.multiplier
+        )
+    }
+}
+
+/// The error type returned when restoring an algorithm from an invalid state.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum InvalidStateError {
+    /// The cursor of the state is outside the range of the state array.
+    CursorOutOfRange {
+        /// The cursor value found in the state.
+        cursor: usize,
+        /// The largest allowed cursor value.
+        max: usize,
+    },
+    /// The multiplier of a Complementary-Multiply-With-Carry state is too large for the carry
+    /// to stay within 32 bits.
+    MultiplierOutOfRange {
+        /// The multiplier found in the state.
+        multiplier: u64,
+        /// The multiplier must be less than this.
+        limit: u64,
+    },
+}
+
+impl core::fmt::Display for InvalidStateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::CursorOutOfRange { cursor, max } => write!(
+                f,
+                "state cursor {} is out of range; it must be at most {}",
+                cursor, max
+            ),
+            Self::MultiplierOutOfRange { multiplier, limit } => write!(
+                f,
+                "state multiplier {} is out of range; it must be less than {}",
+                multiplier, limit
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidStateError {}
+
+/// SplitMix64 algorithm.
+///
+/// A very fast generator with a tiny state, which is mainly useful for expanding a small seed
+/// into the larger states of the other algorithms, but also works well on its own for
+/// non-critical randomness.
+#[derive(Clone, Copy, Debug)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// Create a new SplitMix64 algorithm instance.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Create a new SplitMix64 algorithm instance, seeded from system entropy.
+    ///
+    /// This is non-deterministic; every call gives a different sequence. See [`entropy_seed`]
+    /// for where the entropy comes from.
+    #[cfg(feature = "std")]
+    pub fn new_seeded() -> Self {
+        Self::new(entropy_seed())
+    }
+
+    /// Generate a 64-bit integer.
+    pub fn get_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl SplitMix64 {
+    fn fill_u32s(&mut self, dest: &mut [u32]) {
+        for pair in dest.chunks_mut(2) {
+            let value = self.get_u64();
+            pair[0] = value as u32;
+            if let Some(high) = pair.get_mut(1) {
+                *high = (value >> 32) as u32;
+            }
+        }
+    }
+}
+
+impl Algorithm for SplitMix64 {
+    fn get_int(&mut self) -> u32 {
+        (self.get_u64() >> 32) as u32
+    }
+
+    fn get_u64(&mut self) -> u64 {
+        Self::get_u64(self)
+    }
+}
+
+/// Xoshiro256** algorithm.
+///
+/// A fast, high quality generator with a small state of 256 bits, which makes it well suited for
+/// having many independent generators at once.
+#[derive(Clone, Copy, Debug)]
+pub struct Xoshiro256StarStar {
+    s: [u64; 4],
+}
+
+impl Xoshiro256StarStar {
+    const JUMP: [u64; 4] = [
+        0x180E_C6D3_3CFD_0ABA,
+        0xD5A6_1266_F0C9_392C,
+        0xA958_2618_E03F_C9AA,
+        0x39AB_DC45_29B1_661C,
+    ];
+    const LONG_JUMP: [u64; 4] = [
+        0x76E1_5D3E_FEFD_CBBF,
+        0xC500_4E44_1C52_2FB3,
+        0x7771_0069_854E_E241,
+        0x3910_9BB0_2ACB_E635,
+    ];
+
+    /// Create a new Xoshiro256** algorithm instance, with its state filled using a `SplitMix64`
+    /// generator seeded with `seed`.
+    pub fn new(seed: u64) -> Self {
+        let mut splitmix = SplitMix64::new(seed);
+        let mut s = [0; 4];
+        for e in &mut s {
+            *e = splitmix.get_u64();
+        }
+
+        Self { s }
+    }
+
+    /// Create a new Xoshiro256** algorithm instance, seeded from system entropy.
+    ///
+    /// This is non-deterministic; every call gives a different sequence. See [`entropy_seed`]
+    /// for where the entropy comes from.
+    #[cfg(feature = "std")]
+    pub fn new_seeded() -> Self {
+        Self::new(entropy_seed())
+    }
+
+    /// Generate a 64-bit integer.
+    pub fn get_u64(&mut self) -> u64 {
+        let result = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+
+        result
+    }
+
+    /// Advances the generator by 2^128 outputs.
+    ///
+    /// Jumping a clone of a generator repeatedly gives up to 2^128 non-overlapping
+    /// subsequences for parallel computations.
+    pub fn jump(&mut self) {
+        self.jump_with(Self::JUMP);
+    }
+
+    /// Advances the generator by 2^192 outputs.
+    ///
+    /// Long-jumping a clone of a generator repeatedly gives up to 2^64 starting points, each of
+    /// which can in turn be split into 2^64 subsequences using [`jump`](#method.jump).
+    pub fn long_jump(&mut self) {
+        self.jump_with(Self::LONG_JUMP);
+    }
+
+    fn jump_with(&mut self, polynomial: [u64; 4]) {
+        let mut s = [0; 4];
+        for &word in &polynomial {
+            for b in 0..64 {
+                if word & 1 << b != 0 {
+                    for (e, &own) in s.iter_mut().zip(&self.s) {
+                        *e ^= own;
+                    }
+                }
+                self.get_u64();
+            }
+        }
+
+        self.s = s;
+    }
+}
+
+impl Algorithm for Xoshiro256StarStar {
+    fn get_int(&mut self) -> u32 {
+        (self.get_u64() >> 32) as u32
+    }
+
+    fn get_u64(&mut self) -> u64 {
+        Self::get_u64(self)
+    }
+}
+
+/// PCG32 algorithm, using the PCG-XSH-RR output function.
+///
+/// A small, fast generator with good statistical quality and a 64-bit state. Besides its seed,
+/// each generator has a stream, and generators on different streams give independent sequences
+/// even when they have the same seed, so a single seed can drive many separate systems.
+#[derive(Clone, Copy, Debug)]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+impl Pcg32 {
+    const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+
+    /// Create a new PCG32 algorithm instance with the given `seed`, on the given `stream`.
+    ///
+    /// The top bit of the `stream` is ignored, so there are 2^63 distinct streams.
+    pub fn new(seed: u64, stream: u64) -> Self {
+        let mut pcg = Self {
+            state: 0,
+            increment: stream << 1 | 1,
+        };
+        pcg.step();
+        pcg.state = pcg.state.wrapping_add(seed);
+        pcg.step();
+
+        pcg
+    }
+
+    /// Create a new PCG32 algorithm instance, with its seed and stream taken from system entropy.
+    ///
+    /// This is non-deterministic; every call gives a different sequence. See [`entropy_seed`]
+    /// for where the entropy comes from.
+    #[cfg(feature = "std")]
+    pub fn new_seeded() -> Self {
+        Self::new(entropy_seed(), entropy_seed())
+    }
+
+    fn step(&mut self) {
+        self.state = self
+            .state
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(self.increment);
+    }
+}
+
+impl Algorithm for Pcg32 {
+    fn get_int(&mut self) -> u32 {
+        let old = self.state;
+        self.step();
+
+        let xor_shifted = (((old >> 18) ^ old) >> 27) as u32;
+        xor_shifted.rotate_right((old >> 59) as u32)
+    }
+}
+
+/// Returns a non-deterministic seed, for when any seed will do.
+///
+/// With the `entropy` feature enabled, the seed is read from the operating system's random
+/// number source. Otherwise, or if that fails, it's derived from the current time mixed with a
+/// process-wide counter, so that seeds requested in quick succession still differ.
+#[cfg(feature = "std")]
+pub fn entropy_seed() -> u64 {
+    #[cfg(feature = "entropy")]
+    {
+        let mut bytes = [0; 8];
+        if getrandom::getrandom(&mut bytes).is_ok() {
+            return u64::from_le_bytes(bytes);
+        }
+    }
+
+    time_seed()
+}
+
+#[cfg(feature = "std")]
+fn time_seed() -> u64 {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::SystemTime;
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    // Scramble both the time and the counter so that neither dominates the seed.
+    SplitMix64::new(nanos ^ SplitMix64::new(count).get_u64()).get_u64()
+}
+
+/// A helper for drawing random bits from an algorithm a few at a time, without wasting the
+/// remaining bits of each generated integer.
+pub struct Bits<'a, A: Algorithm + ?Sized> {
     algorithm: &'a mut A,
     bits: u32,
     bits_left: u32,
 }
 
 impl<'a, A: Algorithm + ?Sized> Bits<'a, A> {
-    fn new(algorithm: &'a mut A) -> Self {
+    /// Create a new `Bits` instance drawing its bits from the given `algorithm`.
+    pub fn new(algorithm: &'a mut A) -> Self {
         Self {
             algorithm,
             bits: 0,
@@ -313,7 +1095,8 @@ impl<'a, A: Algorithm + ?Sized> Bits<'a, A> {
         }
     }
 
-    fn get_bit(&mut self) -> u32 {
+    /// Get a single random bit, as either 0 or 1.
+    pub fn get_bit(&mut self) -> u32 {
         if self.bits_left == 0 {
             self.bits = self.algorithm.get_int();
             self.bits_left = 32;
@@ -324,4 +1107,1200 @@ impl<'a, A: Algorithm + ?Sized> Bits<'a, A> {
         self.bits_left -= 1;
         bit
     }
+
+    /// Get `count` random bits, packed into the lowest bits of a `u32`.
+    ///
+    /// The bits are drawn in the same order as `get_bit` would have drawn them, with the
+    /// first bit drawn ending up as the lowest bit.
+    ///
+    /// # Panics
+    /// If `count` is greater than 32.
+    pub fn get_bits(&mut self, count: u32) -> u32 {
+        assert!(count <= 32, "At most 32 bits can be drawn at once.");
+
+        let mut result = 0;
+        let mut filled = 0;
+        while filled < count {
+            if self.bits_left == 0 {
+                self.bits = self.algorithm.get_int();
+                self.bits_left = 32;
+            }
+
+            let take = (count - filled).min(self.bits_left);
+            let mask = u32::MAX.checked_shr(32 - take).unwrap_or(0);
+            result |= (self.bits & mask) << filled;
+            self.bits = self.bits.checked_shr(take).unwrap_or(0);
+            self.bits_left -= take;
+            filled += take;
+        }
+
+        result
+    }
+}
+
+impl<A: Algorithm + ?Sized> core::fmt::Debug for Bits<'_, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "Bits {{ bits_left: {} }}", self.bits_left)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random::algorithms::{
+        Algorithm, AlgorithmKind, Bits, ComplementaryMultiplyWithCarry, InvalidStateError,
+        MersenneTwister, Pcg32, SplitMix64, Xoshiro256StarStar,
+    };
+    use std::collections::HashSet;
+    /* The test harness always links std, even when the crate itself doesn't */
+    #[cfg(not(feature = "std"))]
+    extern crate std;
+    #[cfg(not(feature = "std"))]
+    use std::{vec, vec::Vec};
+
+    #[test]
+    fn get_u64_is_high_word_first() {
+        let mut expected = MersenneTwister::new(17);
+        let mut sut = expected;
+        for _ in 0..10 {
+            let value = sut.get_u64();
+            assert_eq!(expected.get_int(), (value >> 32) as u32);
+            assert_eq!(expected.get_int(), value as u32);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn boxed_algorithms_forward_to_the_algorithm() {
+        let mut native = Xoshiro256StarStar::new(17);
+        let mut boxed: Box<dyn Algorithm> = Box::new(native);
+        assert_eq!(native.get_u64(), boxed.get_u64());
+
+        let mut expected = MersenneTwister::new(42);
+        let mut values = [0; 1];
+        let mut boxed_mt: Box<dyn Algorithm> = Box::new(expected);
+        boxed_mt.fill_u32(&mut values);
+        assert_eq!(expected.get_int(), values[0]);
+    }
+
+    #[test]
+    fn libtcod_seeding_is_unchanged() {
+        let mut mt = MersenneTwister::new_libtcod(0xdead_beef);
+        assert_eq!(
+            vec![
+                956_529_277,
+                3_842_322_136,
+                3_319_553_134,
+                1_843_186_657,
+                2_704_993_644,
+                595_827_513,
+                938_518_626,
+                1_676_224_337
+            ],
+            (0..8).map(|_| mt.get_int()).collect::<Vec<_>>()
+        );
+
+        let mut cmwc = ComplementaryMultiplyWithCarry::new_libtcod(0xdead_beef);
+        assert_eq!(
+            vec![
+                274_782_743,
+                609_537_464,
+                1_594_502_262,
+                3_752_982_437,
+                1_484_327_717,
+                2_266_346_226,
+                1_012_413_304,
+                2_243_420_718
+            ],
+            (0..8).map(|_| cmwc.get_int()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn mersenne_twister_state_round_trip() {
+        let mut original = MersenneTwister::new(42);
+        for _ in 0..1000 {
+            original.get_int();
+        }
+
+        let mut restored = MersenneTwister::from_state(original.state()).unwrap();
+        for _ in 0..1000 {
+            assert_eq!(original.get_int(), restored.get_int());
+        }
+    }
+
+    #[test]
+    fn mersenne_twister_state_rejects_out_of_range_cursor() {
+        let mut state = MersenneTwister::new(42).state();
+        state.cur_mt = 625;
+
+        assert_eq!(
+            Err(InvalidStateError::CursorOutOfRange {
+                cursor: 625,
+                max: 624
+            }),
+            MersenneTwister::from_state(state).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn mersenne_twister_discard_matches_get_int() {
+        for &k in &[0, 1, 623, 624, 625, 10_000, 2_000_000] {
+            let mut stepped = MersenneTwister::new(1337);
+            stepped.get_int();
+            let mut discarded = stepped;
+
+            for _ in 0..k {
+                stepped.get_int();
+            }
+            discarded.discard(k);
+
+            for _ in 0..1000 {
+                assert_eq!(stepped.get_int(), discarded.get_int());
+            }
+        }
+    }
+
+    #[test]
+    fn mersenne_twister_jump_matches_regeneration() {
+        let mut regenerated = MersenneTwister::new(1337);
+        let mut jumped = regenerated;
+
+        for _ in 0..5 {
+            MersenneTwister::generate(&mut regenerated.mt);
+        }
+        super::jump::jump_blocks(&mut jumped.mt, 5);
+
+        assert_eq!(&regenerated.mt[..], &jumped.mt[..]);
+    }
+
+    #[cfg(feature = "rng_support")]
+    #[test]
+    fn rng_core_matches_get_int() {
+        use rand_core::RngCore;
+
+        let mut expected = MersenneTwister::new(42);
+        let mut sut = expected;
+
+        let high = u64::from(expected.get_int());
+        let low = u64::from(expected.get_int());
+        assert_eq!(high << 32 | low, sut.next_u64());
+
+        let mut bytes = [0; 6];
+        RngCore::fill_bytes(&mut sut, &mut bytes);
+        assert_eq!(expected.get_int().to_le_bytes(), bytes[..4]);
+        assert_eq!(expected.get_int().to_le_bytes()[..2], bytes[4..]);
+
+        assert_eq!(expected.get_int(), sut.next_u32());
+    }
+
+    #[test]
+    fn fill_bytes_is_deterministic() {
+        for len in 0..10 {
+            let mut a = MersenneTwister::new(42);
+            let mut b = MersenneTwister::new(42);
+            let mut bytes_a = vec![0; len];
+            let mut bytes_b = vec![0; len];
+
+            a.fill_bytes(&mut bytes_a);
+            b.fill_bytes(&mut bytes_b);
+
+            assert_eq!(bytes_a, bytes_b);
+            assert_eq!(a.get_int(), b.get_int());
+        }
+    }
+
+    #[test]
+    fn fill_bytes_handles_partial_chunks() {
+        let mut expected = ComplementaryMultiplyWithCarry::new(42);
+        let mut sut = expected;
+
+        let mut bytes = [0; 7];
+        sut.fill_bytes(&mut bytes);
+
+        assert_eq!(expected.get_int().to_le_bytes(), bytes[..4]);
+        assert_eq!(expected.get_int().to_le_bytes()[..3], bytes[4..]);
+        assert_eq!(expected.get_int(), sut.get_int());
+    }
+
+    #[test]
+    fn fill_bytes_with_empty_buffer_consumes_nothing() {
+        let mut expected = MersenneTwister::new(42);
+        let mut sut = expected;
+
+        sut.fill_bytes(&mut []);
+
+        assert_eq!(expected.get_int(), sut.get_int());
+    }
+
+    #[test]
+    fn bits_get_bits_packs_bits_in_draw_order() {
+        let mut expected = MersenneTwister::new(42);
+        let mut algorithm = expected;
+
+        let mut bits = Bits::new(&mut algorithm);
+        assert_eq!(expected.get_int(), bits.get_bits(32));
+
+        let bytes = expected.get_int();
+        let mut packed = 0;
+        for i in 0..4 {
+            packed |= bits.get_bits(8) << (8 * i);
+        }
+        assert_eq!(bytes, packed);
+
+        let first = expected.get_int();
+        assert_eq!(first & 1, bits.get_bit());
+        assert_eq!(first >> 1 & 0xFFF, bits.get_bits(12));
+        assert_eq!(0, bits.get_bits(0));
+        let second = expected.get_int();
+        assert_eq!(first >> 13 | (second & 0x1FFF) << 19, bits.get_bits(32));
+
+        assert_eq!(expected.get_int(), algorithm.get_int());
+    }
+
+    #[test]
+    fn splitmix64_matches_reference() {
+        let mut splitmix = SplitMix64::new(1_234_567);
+        for &expected in &[
+            6_457_827_717_110_365_317,
+            3_203_168_211_198_807_973,
+            9_817_491_932_198_370_423,
+            4_593_380_528_125_082_431,
+            16_408_922_859_458_223_821,
+        ] {
+            assert_eq!(expected, splitmix.get_u64());
+        }
+
+        let mut high = SplitMix64::new(1_234_567);
+        assert_eq!((6_457_827_717_110_365_317_u64 >> 32) as u32, high.get_int());
+    }
+
+    #[test]
+    fn xoshiro256starstar_matches_reference() {
+        let mut xoshiro = Xoshiro256StarStar { s: [1, 2, 3, 4] };
+        for &expected in &[
+            11_520,
+            0,
+            1_509_978_240,
+            1_215_971_899_390_074_240,
+            1_216_172_134_540_287_360,
+            607_988_272_756_665_600,
+            16_172_922_978_634_559_625,
+            8_476_171_486_693_032_832,
+            10_595_114_339_597_558_777,
+            2_904_607_092_377_533_576,
+        ] {
+            assert_eq!(expected, xoshiro.get_u64());
+        }
+    }
+
+    #[test]
+    fn xoshiro256starstar_jumps_are_disjoint() {
+        let original = Xoshiro256StarStar::new(42);
+        let mut jumped = original;
+        jumped.jump();
+        let mut long_jumped = original;
+        long_jumped.long_jump();
+
+        let mut original = original;
+        let sequence: HashSet<_> = (0..10_000).map(|_| original.get_u64()).collect();
+        assert!((0..10_000).all(|_| !sequence.contains(&jumped.get_u64())));
+        assert!((0..10_000).all(|_| !sequence.contains(&long_jumped.get_u64())));
+    }
+
+    /* The first block of outputs of the MT19937 reference implementation for its default seed,
+     * 5489, as given by e.g. C++'s `std::mt19937` */
+    const MT19937_FIRST_BLOCK: [u32; 624] = [
+        3_499_211_612,
+        581_869_302,
+        3_890_346_734,
+        3_586_334_585,
+        545_404_204,
+        4_161_255_391,
+        3_922_919_429,
+        949_333_985,
+        2_715_962_298,
+        1_323_567_403,
+        418_932_835,
+        2_350_294_565,
+        1_196_140_740,
+        809_094_426,
+        2_348_838_239,
+        4_264_392_720,
+        4_112_460_519,
+        4_279_768_804,
+        4_144_164_697,
+        4_156_218_106,
+        676_943_009,
+        3_117_454_609,
+        4_168_664_243,
+        4_213_834_039,
+        4_111_000_746,
+        471_852_626,
+        2_084_672_536,
+        3_427_838_553,
+        3_437_178_460,
+        1_275_731_771,
+        609_397_212,
+        20_544_909,
+        1_811_450_929,
+        483_031_418,
+        3_933_054_126,
+        2_747_762_695,
+        3_402_504_553,
+        3_772_830_893,
+        4_120_988_587,
+        2_163_214_728,
+        2_816_384_844,
+        3_427_077_306,
+        153_380_495,
+        1_551_745_920,
+        3_646_982_597,
+        910_208_076,
+        4_011_470_445,
+        2_926_416_934,
+        2_915_145_307,
+        1_712_568_902,
+        3_254_469_058,
+        3_181_055_693,
+        3_191_729_660,
+        2_039_073_006,
+        1_684_602_222,
+        1_812_852_786,
+        2_815_256_116,
+        746_745_227,
+        735_241_234,
+        1_296_707_006,
+        3_032_444_839,
+        3_424_291_161,
+        136_721_026,
+        1_359_573_808,
+        1_189_375_152,
+        3_747_053_250,
+        198_304_612,
+        640_439_652,
+        417_177_801,
+        4_269_491_673,
+        3_536_724_425,
+        3_530_047_642,
+        2_984_266_209,
+        537_655_879,
+        1_361_931_891,
+        3_280_281_326,
+        4_081_172_609,
+        2_107_063_880,
+        147_944_788,
+        2_850_164_008,
+        1_884_392_678,
+        540_721_923,
+        1_638_781_099,
+        902_841_100,
+        3_287_869_586,
+        219_972_873,
+        3_415_357_582,
+        156_513_983,
+        802_611_720,
+        1_755_486_969,
+        2_103_522_059,
+        1_967_048_444,
+        1_913_778_154,
+        2_094_092_595,
+        2_775_893_247,
+        3_410_096_536,
+        3_046_698_742,
+        3_955_127_111,
+        3_241_354_600,
+        3_468_319_344,
+        1_185_518_681,
+        3_031_277_329,
+        2_919_300_778,
+        12_105_075,
+        2_813_624_502,
+        3_052_449_900,
+        698_412_071,
+        2_765_791_248,
+        511_091_141,
+        1_958_646_067,
+        2_140_457_296,
+        3_323_948_758,
+        4_122_068_897,
+        2_464_257_528,
+        1_461_945_556,
+        3_765_644_424,
+        2_513_705_832,
+        3_471_087_299,
+        961_264_978,
+        76_338_300,
+        3_226_667_454,
+        3_527_224_675,
+        1_095_625_157,
+        3_525_484_323,
+        2_173_068_963,
+        4_037_587_209,
+        3_002_511_655,
+        1_772_389_185,
+        3_826_400_342,
+        1_817_480_335,
+        4_120_125_281,
+        2_495_189_930,
+        2_350_272_820,
+        678_852_156,
+        595_387_438,
+        3_271_610_651,
+        641_212_874,
+        988_512_770,
+        1_105_989_508,
+        3_477_783_405,
+        3_610_853_094,
+        4_245_667_946,
+        1_092_133_642,
+        1_427_854_500,
+        3_497_326_703,
+        1_287_767_370,
+        1_045_931_779,
+        58_150_106,
+        3_991_156_885,
+        933_029_415,
+        1_503_168_825,
+        3_897_101_788,
+        844_370_145,
+        3_644_141_418,
+        1_078_396_938,
+        4_101_769_245,
+        2_645_891_717,
+        3_345_340_191,
+        2_032_760_103,
+        4_241_106_803,
+        1_510_366_103,
+        290_319_951,
+        3_568_381_791,
+        3_408_475_658,
+        2_513_690_134,
+        2_553_373_352,
+        2_361_044_915,
+        3_147_346_559,
+        3_939_316_793,
+        2_986_002_498,
+        1_227_669_233,
+        2_919_803_768,
+        3_252_150_224,
+        1_685_003_584,
+        3_237_241_796,
+        2_411_870_849,
+        1_634_002_467,
+        893_645_500,
+        2_438_775_379,
+        2_265_043_167,
+        325_791_709,
+        1_736_062_366,
+        231_714_000,
+        1_515_103_006,
+        2_279_758_133,
+        2_546_159_170,
+        3_346_497_776,
+        1_530_490_810,
+        4_011_545_318,
+        4_144_499_009,
+        557_942_923,
+        663_307_952,
+        2_443_079_012,
+        1_696_117_849,
+        2_016_017_442,
+        1_663_423_246,
+        51_119_001,
+        3_122_246_755,
+        1_447_930_741,
+        1_668_894_615,
+        696_567_687,
+        3_983_551_422,
+        3_411_426_125,
+        1_873_110_678,
+        1_336_658_413,
+        3_705_174_600,
+        2_270_032_533,
+        2_664_425_968,
+        711_455_903,
+        513_451_233,
+        2_585_492_744,
+        2_027_039_028,
+        1_129_453_058,
+        1_461_232_481,
+        2_809_248_324,
+        2_275_654_012,
+        2_960_153_730,
+        3_075_629_128,
+        3_213_286_615,
+        4_245_057_188,
+        1_935_061_435,
+        3_094_495_853,
+        360_010_077,
+        3_919_490_483,
+        983_448_591,
+        2_171_099_548,
+        3_922_754_098,
+        2_397_746_050,
+        654_458_600,
+        2_161_184_684,
+        3_546_856_898,
+        1_986_311_591,
+        2_312_163_142,
+        2_347_594_600,
+        4_278_366_025,
+        1_922_360_368,
+        335_761_339,
+        3_669_839_044,
+        1_901_288_696,
+        2_595_154_464,
+        458_070_173,
+        2_141_230_976,
+        4_131_320_786,
+        4_208_748_424,
+        19_903_848,
+        147_391_738,
+        3_328_215_103,
+        4_196_191_786,
+        3_510_290_616,
+        1_559_873_971,
+        3_731_015_357,
+        2_918_514_861,
+        362_649_214,
+        1_487_061_100,
+        1_717_053_387,
+        3_675_955_720,
+        1_116_134_897,
+        193_529_268,
+        3_436_267_940,
+        2_835_191_639,
+        1_852_908_272,
+        3_220_971_953,
+        3_911_201_640,
+        571_213_604,
+        781_027_019,
+        4_219_206_494,
+        1_133_024_903,
+        409_547_355,
+        625_085_180,
+        1_214_072_539,
+        584_409_985,
+        3_445_042_528,
+        3_733_581_611,
+        333_104_904,
+        2_489_812_253,
+        2_694_595_213,
+        2_361_631_596,
+        34_763_086,
+        622_576_118,
+        2_921_810_672,
+        3_663_740_744,
+        2_293_225_236,
+        2_671_706_445,
+        1_884_059_696,
+        1_507_329_019,
+        857_065_948,
+        2_204_390_003,
+        592_711_182,
+        1_725_752_375,
+        1_642_107_460,
+        326_274_448,
+        3_274_574_484,
+        1_030_432_041,
+        173_822_100,
+        529_650_788,
+        1_086_437_636,
+        789_877_945,
+        2_167_974_914,
+        1_030_588_245,
+        3_533_061_365,
+        1_792_148_406,
+        4_216_468_704,
+        213_264_131,
+        3_536_714_075,
+        3_877_136_173,
+        1_296_338_417,
+        4_057_830_103,
+        205_919_137,
+        2_108_245_233,
+        1_064_497_347,
+        2_101_324_080,
+        2_336_703_164,
+        1_450_493_809,
+        3_812_754_708,
+        3_865_701_845,
+        1_476_779_561,
+        1_585_902_852,
+        142_887_412,
+        477_612_192,
+        699_530_444,
+        3_351_157_089,
+        3_768_249_319,
+        1_673_915_577,
+        903_239_649,
+        1_038_056_164,
+        1_171_465_372,
+        1_734_789_440,
+        2_115_022_236,
+        414_269_055,
+        959_581_346,
+        566_820_984,
+        2_105_828_892,
+        4_046_076_449,
+        4_101_450_561,
+        4_106_566_571,
+        2_800_184_123,
+        2_470_502_098,
+        3_253_453_343,
+        256_751_188,
+        1_869_365_987,
+        1_008_372_035,
+        2_374_606_708,
+        1_516_804_538,
+        228_288_551,
+        3_527_001_547,
+        1_385_173_098,
+        66_157_275,
+        1_739_381_798,
+        184_785_808,
+        3_901_692_666,
+        725_806_641,
+        3_475_217_997,
+        2_787_929_747,
+        1_109_372_433,
+        3_142_723_729,
+        557_686_578,
+        2_782_047_723,
+        2_118_822_689,
+        1_936_702_581,
+        1_625_646_963,
+        2_349_385_293,
+        3_085_804_937,
+        1_272_688_179,
+        1_236_112_995,
+        3_198_431_244,
+        2_677_635_414,
+        811_555_596,
+        3_486_972_196,
+        2_949_678_043,
+        1_342_211_552,
+        788_174_404,
+        1_656_614_077,
+        1_582_629_285,
+        1_477_167_035,
+        2_687_011_245,
+        3_503_701_453,
+        3_351_051_324,
+        2_874_557_775,
+        348_432_514,
+        1_629_591_495,
+        3_991_682_351,
+        1_969_229_192,
+        3_331_660_584,
+        1_304_012_077,
+        2_090_754_125,
+        3_910_846_836,
+        1_871_998_370,
+        2_098_597_104,
+        1_918_921_592,
+        3_246_092_887,
+        1_315_760_974,
+        464_122_393,
+        2_184_028_058,
+        1_690_455_542,
+        2_193_747_147,
+        3_737_423_698,
+        3_511_684_278,
+        1_549_884_962,
+        3_413_774_919,
+        3_938_991_454,
+        2_767_325_310,
+        2_335_626_851,
+        1_626_114_941,
+        601_913_200,
+        3_485_711_542,
+        858_447_440,
+        2_288_468_476,
+        4_075_602_213,
+        1_506_361_431,
+        4_252_489_875,
+        4_032_981_007,
+        1_031_118_352,
+        3_762_145_731,
+        70_955_369,
+        2_362_903_502,
+        1_669_089_455,
+        2_673_510_137,
+        3_348_740_333,
+        2_521_337_794,
+        2_047_144_929,
+        892_246_357,
+        2_319_875_070,
+        1_293_843_163,
+        79_245_769,
+        2_022_600_352,
+        3_866_257_397,
+        989_939_126,
+        835_351_312,
+        3_626_278_636,
+        3_805_332_945,
+        836_506_264,
+        1_895_040_349,
+        970_326_679,
+        634_920_763,
+        733_185_481,
+        1_028_655_248,
+        977_810_701,
+        3_434_484_235,
+        1_871_311_609,
+        2_031_584_214,
+        1_336_174_158,
+        385_787_519,
+        3_965_885_375,
+        2_768_323_462,
+        1_847_726_660,
+        2_718_987_737,
+        793_780_050,
+        2_509_902_580,
+        3_886_434_164,
+        3_120_956_802,
+        4_207_987_247,
+        1_523_159_183,
+        1_884_932_179,
+        2_922_324_286,
+        477_253_416,
+        3_037_922_812,
+        1_108_379_444,
+        697_195_677,
+        1_755_438_379,
+        574_393_398,
+        2_555_059_183,
+        1_930_828_628,
+        1_126_190_880,
+        180_621_093,
+        2_589_191_337,
+        3_424_652_760,
+        3_054_648_512,
+        719_646_637,
+        952_394_946,
+        3_570_038_180,
+        504_304_985,
+        1_395_707_758,
+        1_274_213_163,
+        2_816_553_213,
+        1_369_142_370,
+        1_804_702_100,
+        1_821_782_344,
+        3_358_274_235,
+        2_181_234_724,
+        486_158_240,
+        367_287_522,
+        4_267_199_121,
+        1_127_352_639,
+        779_850_007,
+        3_440_331_597,
+        3_276_765_484,
+        125_500_149,
+        1_142_120_513,
+        3_989_398_167,
+        1_048_565_860,
+        3_136_747_194,
+        432_668_526,
+        2_098_559_576,
+        1_478_877_150,
+        2_484_746_208,
+        1_209_580_219,
+        1_019_125_185,
+        4_160_278_734,
+        1_970_740_713,
+        918_146_921,
+        4_136_433_784,
+        2_602_441_845,
+        2_348_512_686,
+        973_030_509,
+        2_238_261_365,
+        815_637_919,
+        994_690_313,
+        1_724_736_366,
+        2_099_799_816,
+        1_775_069_742,
+        2_680_317_667,
+        730_798_472,
+        2_916_864_943,
+        1_284_417_767,
+        1_698_724_919,
+        2_733_611_686,
+        1_578_128_411,
+        651_006_053,
+        4_243_350_375,
+        3_303_874_296,
+        162_087_183,
+        3_796_616_231,
+        3_801_767_645,
+        4_119_825_424,
+        3_922_537_059,
+        77_594_039,
+        3_419_583_692,
+        2_503_306_160,
+        423_966_005,
+        3_293_613_218,
+        1_124_728_190,
+        1_407_880_681,
+        1_440_346_680,
+        554_334_954,
+        2_919_409_323,
+        1_253_962_019,
+        586_491_243,
+        3_638_308_238,
+        3_097_648_541,
+        991_125_519,
+        458_538_714,
+        2_155_963_569,
+        2_807_866_455,
+        6_862_945,
+        2_122_460_897,
+        53_853_750,
+        3_346_001_678,
+        1_230_879_976,
+        3_071_060_893,
+        423_909_157,
+        3_881_450_262,
+        1_652_511_030,
+        3_826_483_009,
+        1_526_211_009,
+        1_435_219_366,
+        3_092_251_623,
+        3_001_090_498,
+        281_084_412,
+        849_586_749,
+        2_207_008_400,
+        131_172_352,
+        1_820_973_075,
+        3_195_774_605,
+        2_962_673_849,
+        2_147_580_010,
+        1_090_677_336,
+        2_061_249_893,
+        1_724_513_375,
+        3_885_752_424,
+        1_135_918_139,
+        2_619_357_288,
+        4_012_575_714,
+        2_652_856_935,
+        2_029_480_458,
+        3_691_276_589,
+        2_623_865_075,
+        3_459_550_738,
+        2_097_670_126,
+        2_477_000_057,
+        2_209_844_713,
+        785_646_024,
+        1_052_349_661,
+        1_030_500_157,
+        1_430_246_618,
+        3_807_539_761,
+        2_157_629_976,
+        123_154_542,
+        2_560_049_331,
+        2_104_110_449,
+        1_332_109_867,
+        721_241_591,
+        4_136_042_859,
+        4_203_401_395,
+        998_151_922,
+        3_060_999_432,
+        3_207_929_139,
+        2_149_509_272,
+        1_385_268_511,
+        2_023_309_182,
+        1_366_796_638,
+        256_061_060,
+        4_090_836_236,
+        2_929_047_008,
+        2_296_609_403,
+        182_240_337,
+        3_744_374_619,
+        306_855_912,
+        4_014_087_816,
+        2_240_468_995,
+        2_865_233_169,
+        415_452_309,
+        1_244_206_523,
+        3_513_921_306,
+        281_425_419,
+        3_511_338_031,
+        995_954_022,
+        3_102_854_413,
+        3_026_765_331,
+        643_667_197,
+        837_979_907,
+        2_832_983_005,
+        1_813_414_171,
+        2_227_348_307,
+        4_020_325_887,
+    ];
+
+    #[test]
+    fn mersenne_twister_matches_reference() {
+        /* The whole block is checked, since the twist of its last element wraps around to the
+         * start of the array */
+        let mut mt = MersenneTwister::new_libtcod(5489);
+        for (i, &expected) in MT19937_FIRST_BLOCK.iter().enumerate() {
+            assert_eq!(expected, mt.get_int(), "output {}", i);
+        }
+
+        /* The 10000th output is the check value the C++ standard gives for std::mt19937 */
+        let mut ten_thousandth = MersenneTwister::new_libtcod(5489);
+        ten_thousandth.discard(9_999);
+        assert_eq!(4_123_659_995, ten_thousandth.get_int());
+    }
+
+    #[test]
+    fn pcg32_matches_reference() {
+        /* The output of the reference implementation's pcg32-demo */
+        let mut pcg = Pcg32::new(42, 54);
+        for &expected in &[
+            0xa15c_02b7,
+            0x7b47_f409,
+            0xba1d_3330,
+            0x83d2_f293,
+            0xbfa4_784b,
+            0xcbed_606e,
+        ] {
+            assert_eq!(expected, pcg.get_int());
+        }
+    }
+
+    #[test]
+    fn pcg32_streams_diverge() {
+        let mut a = Pcg32::new(42, 1);
+        let mut b = Pcg32::new(42, 2);
+        assert_ne!(a.get_int(), b.get_int());
+
+        let a: HashSet<_> = (0..10_000).map(|_| a.get_int()).collect();
+        let overlap = (0..10_000).filter(|_| a.contains(&b.get_int())).count();
+        assert!(overlap < 5, "{}", overlap);
+    }
+
+    #[test]
+    fn nearby_seeds_do_not_share_early_output() {
+        if cfg!(feature = "libtcod-compat") {
+            return;
+        }
+
+        let mut a = MersenneTwister::new(1);
+        let mut b = MersenneTwister::new(2);
+        let mut c = ComplementaryMultiplyWithCarry::new(1);
+        let mut d = ComplementaryMultiplyWithCarry::new(2);
+        for _ in 0..100 {
+            assert_ne!(a.get_int(), b.get_int());
+            assert_ne!(c.get_int(), d.get_int());
+        }
+    }
+
+    #[test]
+    fn algorithm_kind_from_str() {
+        assert_eq!(Ok(AlgorithmKind::MersenneTwister), "mersenne".parse());
+        assert_eq!(Ok(AlgorithmKind::MersenneTwister), "MT".parse());
+        assert_eq!(
+            Ok(AlgorithmKind::ComplementaryMultiplyWithCarry),
+            " cmwc ".parse()
+        );
+        assert!("xorshift".parse::<AlgorithmKind>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn algorithm_kind_creates_expected_algorithm() {
+        let mut mt = AlgorithmKind::MersenneTwister.create(42);
+        assert_eq!(MersenneTwister::new(42).get_int(), mt.get_int());
+
+        let mut cmwc = AlgorithmKind::ComplementaryMultiplyWithCarry.create(42);
+        assert_eq!(
+            ComplementaryMultiplyWithCarry::new(42).get_int(),
+            cmwc.get_int()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn time_seeds_do_not_collide() {
+        let seeds: HashSet<_> = (0..1000).map(|_| super::time_seed()).collect();
+        assert_eq!(1000, seeds.len());
+
+        let mut a = MersenneTwister::new_seeded();
+        let mut b = MersenneTwister::new_seeded();
+        assert_ne!(
+            (0..4).map(|_| a.get_int()).collect::<Vec<_>>(),
+            (0..4).map(|_| b.get_int()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn mersenne_twister_fill_u32_matches_get_int() {
+        let mut expected = MersenneTwister::new(42);
+        for _ in 0..100 {
+            expected.get_int();
+        }
+        let mut sut = expected;
+
+        let mut values = vec![0; 10_000];
+        sut.fill_u32(&mut values);
+
+        for &value in &values {
+            assert_eq!(expected.get_int(), value);
+        }
+        assert_eq!(expected.get_int(), sut.get_int());
+    }
+
+    #[test]
+    fn cmwc_default_params_are_unchanged() {
+        let libtcod = vec![
+            1_586_541_335,
+            66_084_410,
+            3_621_089_460,
+            3_755_974_890,
+            2_578_903_852,
+            1_282_770_898,
+        ];
+        let splitmix = vec![
+            3_302_590_503,
+            595_147_868,
+            2_418_067_817,
+            2_345_995_441,
+            1_346_774_310,
+            1_704_665_886,
+        ];
+
+        let mut cmwc = ComplementaryMultiplyWithCarry::new(42);
+        assert_eq!(
+            if cfg!(feature = "libtcod-compat") {
+                &libtcod
+            } else {
+                &splitmix
+            },
+            &(0..6).map(|_| cmwc.get_int()).collect::<Vec<_>>()
+        );
+        let mut libtcod_seeded = ComplementaryMultiplyWithCarry::new_libtcod(42);
+        assert_eq!(
+            libtcod,
+            (0..6).map(|_| libtcod_seeded.get_int()).collect::<Vec<_>>()
+        );
+
+        let mut expected = ComplementaryMultiplyWithCarry::new(42);
+        let mut custom = ComplementaryMultiplyWithCarry::with_params(42, 18782, 809_430_660);
+        for _ in 0..5000 {
+            assert_eq!(expected.get_int(), custom.get_int());
+        }
+    }
+
+    #[test]
+    fn cmwc_multiplier_changes_sequence() {
+        let mut default = ComplementaryMultiplyWithCarry::new(42);
+        let mut custom =
+            ComplementaryMultiplyWithCarry::with_params(42, 4_294_967_118, 809_430_660);
+
+        let expected = (0..16).map(|_| default.get_int()).collect::<Vec<_>>();
+        let actual = (0..16).map(|_| custom.get_int()).collect::<Vec<_>>();
+        assert_ne!(expected, actual);
+    }
+
+    #[test]
+    fn cmwc_largest_multiplier_keeps_the_carry_in_range() {
+        let mut cmwc = ComplementaryMultiplyWithCarry::with_params(7, 0xffff_fffe, u32::MAX);
+        for _ in 0..100_000 {
+            cmwc.get_int();
+        }
+
+        let mut state = cmwc.state();
+        state.multiplier = 0xffff_ffff;
+        assert_eq!(
+            Err(InvalidStateError::MultiplierOutOfRange {
+                multiplier: 0xffff_ffff,
+                limit: 0xffff_ffff,
+            }),
+            ComplementaryMultiplyWithCarry::from_state(state).map(|_| ())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "multiplier must be less than")]
+    fn cmwc_rejects_multipliers_that_overflow_the_carry() {
+        ComplementaryMultiplyWithCarry::with_params(7, 0xffff_ffff, 1000);
+    }
+
+    #[test]
+    fn cmwc_multiplier_survives_state_round_trip() {
+        let mut original = ComplementaryMultiplyWithCarry::with_params(7, 4_294_967_118, 1000);
+        original.get_int();
+
+        let mut restored = ComplementaryMultiplyWithCarry::from_state(original.state()).unwrap();
+        for _ in 0..100 {
+            assert_eq!(original.get_int(), restored.get_int());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn forks_continue_independently() {
+        let algorithms: Vec<Box<dyn Algorithm>> = vec![
+            Box::new(MersenneTwister::new(3)),
+            Box::new(ComplementaryMultiplyWithCarry::new(3)),
+            Box::new(SplitMix64::new(3)),
+            Box::new(Xoshiro256StarStar::new(3)),
+            Box::new(Pcg32::new(3, 5)),
+        ];
+        for mut boxed in algorithms {
+            boxed.get_int();
+            let parent: &mut dyn Algorithm = &mut *boxed;
+            let mut fork = parent.fork();
+
+            let expected: Vec<_> = (0..700).map(|_| parent.get_int()).collect();
+            assert_eq!(
+                expected,
+                (0..700).map(|_| fork.get_int()).collect::<Vec<_>>()
+            );
+
+            /* Drawing from the fork leaves the parent where it was */
+            let mut reference = parent.fork();
+            for _ in 0..10 {
+                fork.get_int();
+            }
+            let parent_values: Vec<_> = (0..16).map(|_| parent.get_int()).collect();
+            assert_eq!(
+                parent_values,
+                (0..16).map(|_| reference.get_int()).collect::<Vec<_>>()
+            );
+            assert_ne!(
+                parent_values,
+                (0..16).map(|_| fork.get_int()).collect::<Vec<_>>()
+            );
+
+            let mut copy = boxed.clone();
+            assert_eq!(copy.get_int(), boxed.get_int());
+        }
+    }
+
+    #[test]
+    fn cmwc_state_round_trip() {
+        let mut original = ComplementaryMultiplyWithCarry::new(42);
+        for _ in 0..5000 {
+            original.get_int();
+        }
+
+        let mut restored = ComplementaryMultiplyWithCarry::from_state(original.state()).unwrap();
+        for _ in 0..5000 {
+            assert_eq!(original.get_int(), restored.get_int());
+        }
+    }
+
+    #[test]
+    fn cmwc_state_rejects_out_of_range_cursor() {
+        let mut state = ComplementaryMultiplyWithCarry::new(42).state();
+        state.cur = 4096;
+
+        assert_eq!(
+            Err(InvalidStateError::CursorOutOfRange {
+                cursor: 4096,
+                max: 4095
+            }),
+            ComplementaryMultiplyWithCarry::from_state(state).map(|_| ())
+        );
+    }
 }
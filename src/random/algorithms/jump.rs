@@ -0,0 +1,175 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Polynomial jump-ahead for the Mersenne Twister.
+//!
+//! Advancing the MT19937 state by `n` steps is equivalent to evaluating the polynomial
+//! `x^n mod φ(x)` at the state transition, where `φ` is the characteristic polynomial of the
+//! state transition. The polynomial is computed by repeated squaring, so the cost grows with the
+//! logarithm of `n`, and then applied to the state using a single pass of 19937 steps.
+//!
+//! See "Efficient Jump Ahead for F2-Linear Random Number Generators" by Haramoto, Matsumoto,
+//! Nishimura, Panneton and L'Ecuyer.
+
+use super::MersenneTwister;
+
+const DEGREE: usize = 19937;
+const WORDS: usize = DEGREE.div_ceil(64);
+
+/// The exponents of the non-leading terms of the characteristic polynomial of MT19937,
+/// i.e. `φ(x) = x^19937 + Σ x^e`.
+const CHARACTERISTIC_POLYNOMIAL_TERMS: [u16; 134] = [
+    0, 1189, 1416, 1585, 1643, 1870, 2493, 2773, 3000, 3227, 3454, 3681, 3908, 4135, 4362, 4753,
+    5661, 6337, 6569, 7129, 7477, 7525, 7583, 7752, 7979, 8206, 9505, 9901, 9969, 10128, 10693,
+    10761, 10920, 11089, 11147, 11157, 11215, 11321, 11374, 11384, 11485, 11611, 11712, 11717,
+    11838, 11881, 11944, 11997, 12277, 12335, 12393, 12504, 12509, 12620, 12673, 12731, 12736,
+    12789, 12905, 12958, 12963, 13137, 13185, 13190, 13243, 13301, 13412, 13528, 13533, 13639,
+    13697, 13760, 13813, 13866, 14093, 14151, 14209, 14320, 14325, 14436, 14547, 14552, 14605,
+    14721, 14774, 14779, 14953, 15001, 15006, 15059, 15117, 15228, 15344, 15349, 15455, 15513,
+    15576, 15629, 15682, 15909, 15967, 16025, 16136, 16141, 16252, 16363, 16368, 16421, 16537,
+    16590, 16595, 16817, 16822, 16875, 16933, 17044, 17160, 17271, 17329, 17445, 17498, 17725,
+    17783, 17841, 17952, 18068, 18179, 18237, 18406, 18633, 18691, 18860, 19087, 19314,
+];
+
+type Polynomial = [u64; WORDS];
+
+/// Advances the state array `mt` by `blocks` full regenerations of 624 words.
+pub(super) fn jump_blocks(
+    mt: &mut [u32; MersenneTwister::MT19937_RECURRENCE_DEGREE],
+    blocks: u128,
+) {
+    let steps = blocks * MersenneTwister::MT19937_RECURRENCE_DEGREE as u128;
+    let jump = x_pow_mod(steps);
+
+    let n = MersenneTwister::MT19937_RECURRENCE_DEGREE;
+    let mut ring = *mt;
+    let mut result = [0; MersenneTwister::MT19937_RECURRENCE_DEGREE];
+    for i in 0..DEGREE {
+        if jump[i / 64] >> (i % 64) & 1 != 0 {
+            let p = i % n;
+            for (j, r) in result.iter_mut().enumerate() {
+                *r ^= ring[(p + j) % n];
+            }
+        }
+        MersenneTwister::twist_one(&mut ring, i % n);
+    }
+
+    *mt = result;
+}
+
+/// Computes `x^exponent mod φ(x)`.
+fn x_pow_mod(exponent: u128) -> Polynomial {
+    let mut result = [0; WORDS];
+    result[0] = 1;
+    if exponent == 0 {
+        return result;
+    }
+
+    for bit in (0..128 - exponent.leading_zeros()).rev() {
+        result = square_mod(&result);
+        if exponent >> bit & 1 != 0 {
+            multiply_by_x_mod(&mut result);
+        }
+    }
+
+    result
+}
+
+fn square_mod(p: &Polynomial) -> Polynomial {
+    let mut square = [0; 2 * WORDS];
+    for (i, &w) in p.iter().enumerate() {
+        square[2 * i] = spread(w as u32);
+        square[2 * i + 1] = spread((w >> 32) as u32);
+    }
+
+    // Reduce one word at a time from the top, using x^19937 ≡ Σ x^e. Since the highest
+    // non-leading term is far below 19937, the reduction of a word never touches itself.
+    for w in (WORDS..2 * WORDS).rev() {
+        let value = square[w];
+        if value != 0 {
+            square[w] = 0;
+            xor_reduced(&mut square, value, w * 64 - DEGREE);
+        }
+    }
+    let top_bits = DEGREE % 64;
+    let value = square[WORDS - 1] >> top_bits;
+    if value != 0 {
+        square[WORDS - 1] &= (1 << top_bits) - 1;
+        xor_reduced(&mut square, value, 0);
+    }
+
+    let mut result = [0; WORDS];
+    result.copy_from_slice(&square[..WORDS]);
+    result
+}
+
+fn multiply_by_x_mod(p: &mut Polynomial) {
+    let mut carry = 0;
+    for w in p.iter_mut() {
+        let next_carry = *w >> 63;
+        *w = *w << 1 | carry;
+        carry = next_carry;
+    }
+
+    let top_bit = DEGREE % 64;
+    if p[WORDS - 1] >> top_bit & 1 != 0 {
+        p[WORDS - 1] &= !(1 << top_bit);
+        for &e in &CHARACTERISTIC_POLYNOMIAL_TERMS {
+            let e = usize::from(e);
+            p[e / 64] ^= 1 << (e % 64);
+        }
+    }
+}
+
+/// XORs `value * x^shift * Σ x^e` into `target`.
+fn xor_reduced(target: &mut [u64], value: u64, shift: usize) {
+    for &e in &CHARACTERISTIC_POLYNOMIAL_TERMS {
+        let position = shift + usize::from(e);
+        let (word, bit) = (position / 64, position % 64);
+        target[word] ^= value << bit;
+        if bit != 0 {
+            target[word + 1] ^= value >> (64 - bit);
+        }
+    }
+}
+
+/// Spreads the bits of `v` so that bit `i` ends up at bit `2 * i`, which squares a
+/// polynomial over GF(2).
+fn spread(v: u32) -> u64 {
+    let mut x = u64::from(v);
+    x = (x | x << 16) & 0x0000_FFFF_0000_FFFF;
+    x = (x | x << 8) & 0x00FF_00FF_00FF_00FF;
+    x = (x | x << 4) & 0x0F0F_0F0F_0F0F_0F0F;
+    x = (x | x << 2) & 0x3333_3333_3333_3333;
+    x = (x | x << 1) & 0x5555_5555_5555_5555;
+    x
+}
@@ -0,0 +1,217 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+//! Distributions that numbers can be sampled from.
+//!
+//! Anything implementing [`Distribution`](trait.Distribution.html) can be sampled with
+//! [`Random::sample`](../struct.Random.html#method.sample), which makes it possible to add new
+//! distributions without the generator having to know about them.
+
+use crate::random::algorithms::Algorithm;
+use crate::random::Random;
+
+/// A distribution that values of type `T` can be sampled from.
+pub trait Distribution<T> {
+    /// Samples a value from the distribution, using `random` as the source of randomness.
+    fn sample<A: Algorithm>(&self, random: &mut Random<A>) -> T;
+}
+
+/// A uniform distribution between `min` and `max` (both inclusive); every number in the range
+/// is equally likely.
+///
+/// Sampling is the same as calling [`Random::get_int_range`], [`Random::get_float_range`] or
+/// [`Random::get_double_range`].
+///
+/// [`Random::get_int_range`]: ../struct.Random.html#method.get_int_range
+/// [`Random::get_float_range`]: ../struct.Random.html#method.get_float_range
+/// [`Random::get_double_range`]: ../struct.Random.html#method.get_double_range
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Uniform<T> {
+    min: T,
+    max: T,
+}
+
+impl<T> Uniform<T> {
+    /// Returns a uniform distribution between `min` and `max`. If `min` is greater than `max`,
+    /// they are swapped when sampling.
+    pub fn new(min: T, max: T) -> Self {
+        Self { min, max }
+    }
+}
+
+impl Distribution<i32> for Uniform<i32> {
+    fn sample<A: Algorithm>(&self, random: &mut Random<A>) -> i32 {
+        random.get_int_range(self.min, self.max)
+    }
+}
+
+impl Distribution<f32> for Uniform<f32> {
+    fn sample<A: Algorithm>(&self, random: &mut Random<A>) -> f32 {
+        random.get_float_range(self.min, self.max)
+    }
+}
+
+impl Distribution<f64> for Uniform<f64> {
+    fn sample<A: Algorithm>(&self, random: &mut Random<A>) -> f64 {
+        random.get_double_range(self.min, self.max)
+    }
+}
+
+/// A gaussian distribution with the given mean and standard deviation.
+///
+/// Sampling is the same as calling [`Random::get_gaussian_float`] or
+/// [`Random::get_gaussian_double`].
+///
+/// [`Random::get_gaussian_float`]: ../struct.Random.html#method.get_gaussian_float
+/// [`Random::get_gaussian_double`]: ../struct.Random.html#method.get_gaussian_double
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Gaussian<T> {
+    mean: T,
+    std_deviation: T,
+}
+
+impl<T> Gaussian<T> {
+    /// Returns a gaussian distribution with the given `mean` and `std_deviation`.
+    pub fn new(mean: T, std_deviation: T) -> Self {
+        Self {
+            mean,
+            std_deviation,
+        }
+    }
+}
+
+impl Distribution<f32> for Gaussian<f32> {
+    fn sample<A: Algorithm>(&self, random: &mut Random<A>) -> f32 {
+        random.get_gaussian_float(self.mean, self.std_deviation)
+    }
+}
+
+impl Distribution<f64> for Gaussian<f64> {
+    fn sample<A: Algorithm>(&self, random: &mut Random<A>) -> f64 {
+        random.get_gaussian_double(self.mean, self.std_deviation)
+    }
+}
+
+/// An exponential distribution with the rate `lambda`.
+///
+/// Sampling is the same as calling
+/// [`Random::get_exponential`](../struct.Random.html#method.get_exponential).
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde_derive::Serialize, serde_derive::Deserialize)
+)]
+pub struct Exponential {
+    lambda: f64,
+}
+
+impl Exponential {
+    /// Returns an exponential distribution with the rate `lambda`.
+    ///
+    /// # Panics
+    /// If `lambda` isn't positive.
+    pub fn new(lambda: f64) -> Self {
+        assert!(lambda > 0.0, "lambda must be positive.");
+
+        Self { lambda }
+    }
+}
+
+impl Distribution<f64> for Exponential {
+    fn sample<A: Algorithm>(&self, random: &mut Random<A>) -> f64 {
+        random.get_exponential(self.lambda)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random::algorithms::Algorithm;
+    use crate::random::distributions::{Distribution, Exponential, Gaussian, Uniform};
+    use crate::random::Random;
+
+    struct Coin {
+        heads: f64,
+    }
+
+    impl Distribution<bool> for Coin {
+        fn sample<A: Algorithm>(&self, random: &mut Random<A>) -> bool {
+            random.get_double_range(0.0, 1.0) < self.heads
+        }
+    }
+
+    #[test]
+    fn custom_distributions_are_reproducible() {
+        let coin = Coin { heads: 0.25 };
+        let mut first = Random::new_mt_from_seed(1234);
+        let mut second = Random::new_mt_from_seed(1234);
+
+        let flips: Vec<bool> = first.samples(&coin).take(1000).collect();
+        let again: Vec<_> = (0..1000).map(|_| second.sample(&coin)).collect();
+        assert_eq!(flips, again);
+
+        let heads = flips.iter().filter(|&&flip| flip).count();
+        assert!(heads > 200 && heads < 300);
+    }
+
+    #[test]
+    fn built_in_distributions_match_the_random_methods() {
+        let mut sampled = Random::new_mt_from_seed(99);
+        let mut direct = Random::new_mt_from_seed(99);
+
+        for _ in 0..100 {
+            assert_eq!(
+                direct.get_int_range(-5, 5),
+                sampled.sample(&Uniform::new(-5, 5))
+            );
+            assert_eq!(
+                direct.get_double_range(1.0, 2.0),
+                sampled.sample(&Uniform::new(1.0, 2.0))
+            );
+            assert_eq!(
+                direct.get_gaussian_float(3.0, 0.5),
+                sampled.sample(&Gaussian::new(3.0_f32, 0.5))
+            );
+            assert_eq!(
+                direct.get_exponential(2.0),
+                sampled.sample(&Exponential::new(2.0))
+            );
+        }
+    }
+}
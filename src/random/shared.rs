@@ -0,0 +1,231 @@
+/* BSD 3-Clause License
+ *
+ * Copyright © 2019, Alexander Krivács Schrøder <alexschrod@gmail.com>.
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice,
+ *    this list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE
+ * ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE
+ * LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR
+ * CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF
+ * SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS
+ * INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN
+ * CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE)
+ * ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE
+ * POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use crate::random::algorithms::{Algorithm, ComplementaryMultiplyWithCarry, MersenneTwister};
+use crate::random::{Random, Rng};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// A handle to a [`Random`](struct.Random.html) that can be shared between threads.
+///
+/// Cloning the handle gives another handle to the same generator. Each call locks the generator
+/// for as long as it takes to generate one number; for generating many numbers on a worker
+/// thread, [`spawn_substream`](#method.spawn_substream) gives it a generator of its own instead.
+#[derive(Debug)]
+pub struct SharedRng<A: Algorithm> {
+    random: Arc<Mutex<Random<A>>>,
+    /* how many substreams have been spawned; only changed while `random` is locked */
+    spawned: Arc<AtomicU64>,
+}
+
+impl<A: Algorithm> SharedRng<A> {
+    /// Returns a new shared handle to the given generator.
+    pub fn new(random: Random<A>) -> Self {
+        Self {
+            random: Arc::new(Mutex::new(random)),
+            spawned: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Locks the generator, giving access to all of its methods until the guard is dropped.
+    ///
+    /// A panic while the generator is locked can't leave it in an invalid state, so unlike most
+    /// locks, this one is never poisoned.
+    pub fn lock(&self) -> MutexGuard<'_, Random<A>> {
+        self.random.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get an `i32` between `min` and `max` (both inclusive), using the generator's
+    /// `distribution`. See [`Rng::get_i32`](trait.Rng.html#tymethod.get_i32).
+    pub fn get_i32(&self, min: i32, max: i32) -> i32 {
+        self.lock().get_i32(min, max)
+    }
+
+    /// Get an `i32` between `min` and `max` (both inclusive), always linearly. See
+    /// [`Rng::get_int`](trait.Rng.html#tymethod.get_int).
+    pub fn get_int(&self, min: i32, max: i32) -> i32 {
+        self.lock().get_int(min, max)
+    }
+
+    /// Get an `f32` between `min` and `max`, using the generator's `distribution`. See
+    /// [`Rng::get_f32`](trait.Rng.html#tymethod.get_f32).
+    pub fn get_float(&self, min: f32, max: f32) -> f32 {
+        self.lock().get_f32(min, max)
+    }
+
+    /// Get an `f64` between `min` and `max`, using the generator's `distribution`. See
+    /// [`Rng::get_f64`](trait.Rng.html#tymethod.get_f64).
+    pub fn get_double(&self, min: f64, max: f64) -> f64 {
+        self.lock().get_f64(min, max)
+    }
+
+    /// Get an `i32` between `min` and `max` (both inclusive), regardless of the generator's
+    /// `distribution`. See [`Random::get_int_range`](struct.Random.html#method.get_int_range).
+    pub fn get_int_range(&self, min: i32, max: i32) -> i32 {
+        self.lock().get_int_range(min, max)
+    }
+
+    /// Get an `f32` between `min` and `max` (both inclusive), regardless of the generator's
+    /// `distribution`. See [`Random::get_float_range`](struct.Random.html#method.get_float_range).
+    pub fn get_float_range(&self, min: f32, max: f32) -> f32 {
+        self.lock().get_float_range(min, max)
+    }
+
+    /// Get an `f64` between `min` and `max` (both inclusive), regardless of the generator's
+    /// `distribution`. See
+    /// [`Random::get_double_range`](struct.Random.html#method.get_double_range).
+    pub fn get_double_range(&self, min: f64, max: f64) -> f64 {
+        self.lock().get_double_range(min, max)
+    }
+
+    /// Shuffles the `slice` in place. See [`Random::shuffle`](struct.Random.html#method.shuffle).
+    pub fn shuffle<T>(&self, slice: &mut [T]) {
+        self.lock().shuffle(slice);
+    }
+
+    /* Derives a substream named after how many substreams came before it, so the same master
+     * seed always hands out the same sequence of substreams */
+    fn spawn_with(&self, substream: impl FnOnce(&Random<A>, &str) -> Random<A>) -> Random<A> {
+        let random = self.lock();
+        let name = format!(
+            "shared substream {}",
+            self.spawned.fetch_add(1, Ordering::Relaxed)
+        );
+        substream(&random, &name)
+    }
+}
+
+impl SharedRng<MersenneTwister> {
+    /// Returns a new, independent generator for the exclusive use of a worker, derived from the
+    /// shared generator's seed like [`Random::substream`](struct.Random.html#method.substream).
+    ///
+    /// Each call gives a different substream, and the `n`th call always gives the same one for
+    /// the same seed, so as long as the substreams are handed out in a deterministic order, every
+    /// worker gets reproducible numbers without ever holding the lock while generating them.
+    pub fn spawn_substream(&self) -> Random<MersenneTwister> {
+        self.spawn_with(Random::<MersenneTwister>::substream)
+    }
+}
+
+impl SharedRng<ComplementaryMultiplyWithCarry> {
+    /// Returns a new, independent generator for the exclusive use of a worker, derived from the
+    /// shared generator's seed like [`Random::substream`](struct.Random.html#method.substream).
+    ///
+    /// Each call gives a different substream, and the `n`th call always gives the same one for
+    /// the same seed, so as long as the substreams are handed out in a deterministic order, every
+    /// worker gets reproducible numbers without ever holding the lock while generating them.
+    pub fn spawn_substream(&self) -> Random<ComplementaryMultiplyWithCarry> {
+        self.spawn_with(Random::<ComplementaryMultiplyWithCarry>::substream)
+    }
+}
+
+impl<A: Algorithm> Clone for SharedRng<A> {
+    fn clone(&self) -> Self {
+        Self {
+            random: Arc::clone(&self.random),
+            spawned: Arc::clone(&self.spawned),
+        }
+    }
+}
+
+impl<A: Algorithm> From<Random<A>> for SharedRng<A> {
+    fn from(random: Random<A>) -> Self {
+        Self::new(random)
+    }
+}
+
+impl<A: Algorithm> Rng for SharedRng<A> {
+    fn get_i32(&mut self, min: i32, max: i32) -> i32 {
+        self.lock().get_i32(min, max)
+    }
+
+    fn get_int(&mut self, min: i32, max: i32) -> i32 {
+        self.lock().get_int(min, max)
+    }
+
+    fn get_f32(&mut self, min: f32, max: f32) -> f32 {
+        self.lock().get_f32(min, max)
+    }
+
+    fn get_f64(&mut self, min: f64, max: f64) -> f64 {
+        self.lock().get_f64(min, max)
+    }
+
+    fn get_i32_mean(&mut self, min: i32, max: i32, mean: i32) -> i32 {
+        self.lock().get_i32_mean(min, max, mean)
+    }
+
+    fn get_f32_mean(&mut self, min: f32, max: f32, mean: f32) -> f32 {
+        self.lock().get_f32_mean(min, max, mean)
+    }
+
+    fn get_f64_mean(&mut self, min: f64, max: f64, mean: f64) -> f64 {
+        self.lock().get_f64_mean(min, max, mean)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random::{Random, SharedRng};
+    use std::collections::HashSet;
+    use std::thread;
+
+    #[test]
+    fn spawned_substreams_are_independent() {
+        let shared = SharedRng::new(Random::new_mt_from_seed(1234));
+        let workers: Vec<_> = (0..8)
+            .map(|_| {
+                let shared = shared.clone();
+                thread::spawn(move || {
+                    let mut random = shared.spawn_substream();
+                    (0..16)
+                        .map(|_| random.get_int_range(0, i32::MAX))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let sequences: HashSet<_> = workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .collect();
+        assert_eq!(8, sequences.len());
+
+        /* The substreams are handed out in a reproducible order */
+        let first = SharedRng::new(Random::new_cmwc_from_seed(99));
+        let second = SharedRng::new(Random::new_cmwc_from_seed(99));
+        for _ in 0..4 {
+            let (mut a, mut b) = (first.spawn_substream(), second.spawn_substream());
+            assert_eq!(a.get_int_range(0, 1000), b.get_int_range(0, 1000));
+        }
+        assert_eq!(first.get_int(0, 1000), second.get_int(0, 1000));
+    }
+}